@@ -1,23 +1,156 @@
 use std::{
-    fs::File,
+    collections::HashMap,
+    fs::{self, File},
     io::{BufReader, Read},
+    sync::{Arc, RwLock},
 };
 
+use hyper::http::HeaderValue;
 use serde::{Deserialize, Serialize};
 
+use crate::auth::{load_key_files, AuthKeyFiles};
+
 #[derive(Serialize, serde::Deserialize, Clone, Default)]
 pub struct ApiConfig {
-    #[serde(rename = "http_response")]
+    #[serde(rename = "http_response", default)]
     pub http_response_config: ResponderConfig,
-    #[serde(rename = "auth")]
+    #[serde(rename = "auth", default)]
     pub auth_config: AuthConfig,
+    #[serde(rename = "quota", default)]
+    pub quota_config: QuotaConfig,
+    #[serde(rename = "server", default)]
+    pub server_config: ServerConfig,
+    #[serde(rename = "tracing", default)]
+    pub tracing_config: TracingConfig,
+    #[serde(rename = "audit", default)]
+    pub audit_config: AuditConfig,
+    #[serde(rename = "webhooks", default)]
+    pub webhook_config: WebhookConfig,
+    #[serde(rename = "operator", default)]
+    pub operator_config: OperatorConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ServerConfig {
+    /// The address the server binds to. Defaults to `0.0.0.0`.
+    pub host: Option<String>,
+    /// Defaults to `8477`.
+    pub port: Option<u16>,
+    /// The kubeconfig context used for all requests. Defaults to the current context.
+    pub kube_context: Option<String>,
+    /// Serves HTTPS instead of HTTP when set. Only read at startup.
+    pub tls: Option<TlsConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TlsConfig {
+    /// PEM file with the server certificate chain.
+    pub cert_path: String,
+    /// PEM file with the server's private key.
+    pub key_path: String,
+    /// PEM file with the CAs that client certificates must be signed by.
+    /// When set, clients must present a valid certificate.
+    pub client_ca_path: Option<String>,
+}
+
+/// Exports traces over OTLP when `otlp_endpoint` is set. Only read at startup.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TracingConfig {
+    /// The OTLP gRPC endpoint of a collector, e.g. `http://otel-collector:4317`.
+    pub otlp_endpoint: Option<String>,
+    /// Defaults to `stacks-devnet-api`.
+    pub service_name: Option<String>,
+    /// The fraction of traces started by this API that are sampled, from 0 to 1.
+    /// Traces started upstream follow the upstream sampling decision. Defaults to 1.
+    pub sample_ratio: Option<f64>,
+}
+
+/// Where the JSON lines audit log of devnet lifecycle and administrative actions is written.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AuditConfig {
+    /// The file records are appended to. Records are written to stdout when unset,
+    /// in which case they can't be queried through the API.
+    pub path: Option<String>,
+}
+
+/// Webhooks notified when devnets become ready, fail or are deleted.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct WebhookConfig {
+    /// URLs notified about every devnet, in addition to the `webhooks` of each devnet's config.
+    pub urls: Option<Vec<String>>,
+    /// The key payloads are signed with, as an HMAC-SHA256 sent in the
+    /// `x-devnet-api-signature` header. Payloads are unsigned when unset.
+    pub secret: Option<String>,
+    /// Attempts made to deliver each payload. Defaults to 5.
+    pub max_attempts: Option<u32>,
+    /// Seconds a new devnet has to become ready before it's reported as failed.
+    /// Defaults to 900.
+    pub ready_timeout: Option<u64>,
+    /// The file every delivery attempt is appended to as a JSON line. Attempts are
+    /// logged when unset.
+    pub delivery_log_path: Option<String>,
+    /// The hosts the `webhooks` of a devnet's config may be sent to, either exact or with a
+    /// wildcard subdomain, such as `*.example.com`. When unset, they may be sent to any host
+    /// that resolves to a public address.
+    pub allowed_hosts: Option<Vec<String>>,
+    /// Whether the `webhooks` of a devnet's config may use plain `http`. Defaults to `false`.
+    pub allow_http: Option<bool>,
+}
+
+/// Runs an operator reconciling `StacksDevnet` custom resources. Only read at startup.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct OperatorConfig {
+    /// Devnets created and deleted through the HTTP API are also backed by a
+    /// `StacksDevnet` when enabled. Defaults to `false`.
+    pub enabled: Option<bool>,
+}
+
+impl OperatorConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct ResponderConfig {
+    /// Origins allowed to make cross-origin requests: `*`, exact origins,
+    /// or wildcard subdomains such as `https://*.example.com`.
     pub allowed_origins: Option<Vec<String>>,
     pub allowed_methods: Option<Vec<String>>,
     pub allowed_headers: Option<String>,
+    /// Response headers that browsers may expose to cross-origin scripts.
+    pub exposed_headers: Option<Vec<String>>,
+    /// How long, in seconds, browsers may cache preflight responses. Defaults to 600.
+    pub max_age: Option<u64>,
+}
+
+impl ResponderConfig {
+    /// Checks that the configured CORS values can be sent as header values.
+    pub fn validate(&self) -> Result<(), String> {
+        let values = [
+            (
+                "allowed_methods",
+                self.allowed_methods
+                    .as_ref()
+                    .map(|methods| methods.join(", ")),
+            ),
+            ("allowed_headers", self.allowed_headers.clone()),
+            (
+                "exposed_headers",
+                self.exposed_headers
+                    .as_ref()
+                    .map(|headers| headers.join(", ")),
+            ),
+        ];
+        for (field, value) in values {
+            if let Some(value) = value {
+                HeaderValue::from_str(&value).map_err(|_| {
+                    format!("http_response.{field} is not a valid header value: {value}")
+                })?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -27,6 +160,106 @@ pub struct AuthConfig {
     /// this value will be prepended to the string to create
     /// the k8s namespace.
     pub namespace_prefix: Option<String>,
+    /// How requests are authenticated. Defaults to trusting the
+    /// `auth_header` set by an upstream proxy.
+    pub mode: Option<AuthMode>,
+    /// Required when `mode = "jwt"`.
+    pub jwt: Option<JwtConfig>,
+    /// The header API keys are read from. Defaults to `x-api-key`.
+    pub api_key_header: Option<String>,
+    pub api_keys: Option<Vec<ApiKeyConfig>>,
+    /// Path to a TOML file with additional `[[api_keys]]` entries,
+    /// e.g. from a mounted Secret.
+    pub api_keys_path: Option<String>,
+    /// The files above, read when the config is loaded.
+    #[serde(skip)]
+    pub key_files: AuthKeyFiles,
+}
+
+impl AuthConfig {
+    /// The key files the config refers to, which are reloaded when they change.
+    fn get_key_file_paths(&self) -> Vec<String> {
+        let jwks_path = self
+            .jwt
+            .as_ref()
+            .and_then(|jwt_config| jwt_config.jwks_path.clone());
+        self.api_keys_path
+            .iter()
+            .cloned()
+            .chain(jwks_path)
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyRole {
+    /// Acts as the configured `user_id`, like a user authenticated by header or JWT.
+    User,
+    /// May only create, delete or wait for networks whose id starts with `network_prefix`.
+    Ci,
+    /// May act on any network.
+    Admin,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApiKeyConfig {
+    /// Identifies the key in logs.
+    pub name: String,
+    pub key: String,
+    pub role: ApiKeyRole,
+    /// Required for the `user` role.
+    pub user_id: Option<String>,
+    /// Required for the `ci` role.
+    pub network_prefix: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ApiKeysFile {
+    pub api_keys: Vec<ApiKeyConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMode {
+    #[default]
+    Header,
+    Jwt,
+    /// Uses the common name of the client certificate as the user id.
+    /// Requires `[server.tls]` with a `client_ca_path`.
+    ClientCertificate,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct JwtConfig {
+    /// Path to a JWKS file containing the keys tokens may be signed with.
+    pub jwks_path: Option<String>,
+    /// An inline PEM encoded public key, used when no `jwks_path` is set.
+    pub public_key_pem: Option<String>,
+    /// The algorithm tokens signed with `public_key_pem` use. Defaults to `RS256`.
+    pub algorithm: Option<String>,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+    /// The claim used as the user id. Defaults to `sub`.
+    pub user_id_claim: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct QuotaConfig {
+    /// Maximum number of devnets running across the cluster.
+    pub max_devnets: Option<usize>,
+    /// Maximum number of devnets a single user or `ci` API key may run.
+    /// Does not apply to `admin` API keys.
+    pub max_devnets_per_user: Option<usize>,
+    /// Limits on the devnets running in namespaces that share a prefix,
+    /// e.g. all the namespaces of an organization.
+    pub groups: Option<Vec<QuotaGroupConfig>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QuotaGroupConfig {
+    pub namespace_prefix: String,
+    pub max_devnets: usize,
 }
 
 impl ApiConfig {
@@ -48,3 +281,560 @@ impl ApiConfig {
         config_file
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    /// Where the invalid setting came from: a file path, an environment variable or a flag.
+    pub source: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.source, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConfigValueKind {
+    String,
+    /// Comma separated values.
+    List,
+    Integer,
+    Float,
+    /// `true` or `false`.
+    Boolean,
+}
+
+/// A config field that can be overridden by an environment variable or a CLI flag.
+struct ConfigOption {
+    path: &'static [&'static str],
+    env: &'static str,
+    flag: &'static str,
+    kind: ConfigValueKind,
+}
+
+const fn option(
+    path: &'static [&'static str],
+    env: &'static str,
+    flag: &'static str,
+    kind: ConfigValueKind,
+) -> ConfigOption {
+    ConfigOption {
+        path,
+        env,
+        flag,
+        kind,
+    }
+}
+
+const CONFIG_PATH_ENV: &str = "CONFIG_PATH";
+const CONFIG_PATH_FLAG: &str = "--config";
+
+// `PORT` and `KUBE_CONTEXT` predate the other variables, so they are kept unprefixed.
+const CONFIG_OPTIONS: &[ConfigOption] = {
+    use ConfigValueKind::*;
+    &[
+        option(
+            &["server", "host"],
+            "STACKS_DEVNET_API_HOST",
+            "--host",
+            String,
+        ),
+        option(&["server", "port"], "PORT", "--port", Integer),
+        option(
+            &["server", "tls", "cert_path"],
+            "STACKS_DEVNET_API_TLS_CERT_PATH",
+            "--tls-cert-path",
+            String,
+        ),
+        option(
+            &["server", "tls", "key_path"],
+            "STACKS_DEVNET_API_TLS_KEY_PATH",
+            "--tls-key-path",
+            String,
+        ),
+        option(
+            &["server", "tls", "client_ca_path"],
+            "STACKS_DEVNET_API_TLS_CLIENT_CA_PATH",
+            "--tls-client-ca-path",
+            String,
+        ),
+        option(
+            &["server", "kube_context"],
+            "KUBE_CONTEXT",
+            "--kube-context",
+            String,
+        ),
+        option(
+            &["http_response", "allowed_origins"],
+            "STACKS_DEVNET_API_ALLOWED_ORIGINS",
+            "--allowed-origins",
+            List,
+        ),
+        option(
+            &["http_response", "allowed_methods"],
+            "STACKS_DEVNET_API_ALLOWED_METHODS",
+            "--allowed-methods",
+            List,
+        ),
+        option(
+            &["http_response", "allowed_headers"],
+            "STACKS_DEVNET_API_ALLOWED_HEADERS",
+            "--allowed-headers",
+            String,
+        ),
+        option(
+            &["http_response", "exposed_headers"],
+            "STACKS_DEVNET_API_EXPOSED_HEADERS",
+            "--exposed-headers",
+            List,
+        ),
+        option(
+            &["http_response", "max_age"],
+            "STACKS_DEVNET_API_MAX_AGE",
+            "--max-age",
+            Integer,
+        ),
+        option(
+            &["auth", "auth_header"],
+            "STACKS_DEVNET_API_AUTH_HEADER",
+            "--auth-header",
+            String,
+        ),
+        option(
+            &["auth", "namespace_prefix"],
+            "STACKS_DEVNET_API_NAMESPACE_PREFIX",
+            "--namespace-prefix",
+            String,
+        ),
+        option(
+            &["auth", "mode"],
+            "STACKS_DEVNET_API_AUTH_MODE",
+            "--auth-mode",
+            String,
+        ),
+        option(
+            &["auth", "api_key_header"],
+            "STACKS_DEVNET_API_API_KEY_HEADER",
+            "--api-key-header",
+            String,
+        ),
+        option(
+            &["auth", "api_keys_path"],
+            "STACKS_DEVNET_API_API_KEYS_PATH",
+            "--api-keys-path",
+            String,
+        ),
+        option(
+            &["auth", "jwt", "jwks_path"],
+            "STACKS_DEVNET_API_JWT_JWKS_PATH",
+            "--jwt-jwks-path",
+            String,
+        ),
+        option(
+            &["auth", "jwt", "public_key_pem"],
+            "STACKS_DEVNET_API_JWT_PUBLIC_KEY_PEM",
+            "--jwt-public-key-pem",
+            String,
+        ),
+        option(
+            &["auth", "jwt", "algorithm"],
+            "STACKS_DEVNET_API_JWT_ALGORITHM",
+            "--jwt-algorithm",
+            String,
+        ),
+        option(
+            &["auth", "jwt", "issuer"],
+            "STACKS_DEVNET_API_JWT_ISSUER",
+            "--jwt-issuer",
+            String,
+        ),
+        option(
+            &["auth", "jwt", "audience"],
+            "STACKS_DEVNET_API_JWT_AUDIENCE",
+            "--jwt-audience",
+            String,
+        ),
+        option(
+            &["auth", "jwt", "user_id_claim"],
+            "STACKS_DEVNET_API_JWT_USER_ID_CLAIM",
+            "--jwt-user-id-claim",
+            String,
+        ),
+        option(
+            &["quota", "max_devnets"],
+            "STACKS_DEVNET_API_MAX_DEVNETS",
+            "--max-devnets",
+            Integer,
+        ),
+        option(
+            &["quota", "max_devnets_per_user"],
+            "STACKS_DEVNET_API_MAX_DEVNETS_PER_USER",
+            "--max-devnets-per-user",
+            Integer,
+        ),
+        option(
+            &["tracing", "otlp_endpoint"],
+            "STACKS_DEVNET_API_OTLP_ENDPOINT",
+            "--otlp-endpoint",
+            String,
+        ),
+        option(
+            &["tracing", "service_name"],
+            "STACKS_DEVNET_API_TRACING_SERVICE_NAME",
+            "--tracing-service-name",
+            String,
+        ),
+        option(
+            &["tracing", "sample_ratio"],
+            "STACKS_DEVNET_API_TRACING_SAMPLE_RATIO",
+            "--tracing-sample-ratio",
+            Float,
+        ),
+        option(
+            &["audit", "path"],
+            "STACKS_DEVNET_API_AUDIT_LOG_PATH",
+            "--audit-log-path",
+            String,
+        ),
+        option(
+            &["webhooks", "urls"],
+            "STACKS_DEVNET_API_WEBHOOK_URLS",
+            "--webhook-urls",
+            List,
+        ),
+        option(
+            &["webhooks", "secret"],
+            "STACKS_DEVNET_API_WEBHOOK_SECRET",
+            "--webhook-secret",
+            String,
+        ),
+        option(
+            &["webhooks", "max_attempts"],
+            "STACKS_DEVNET_API_WEBHOOK_MAX_ATTEMPTS",
+            "--webhook-max-attempts",
+            Integer,
+        ),
+        option(
+            &["webhooks", "ready_timeout"],
+            "STACKS_DEVNET_API_WEBHOOK_READY_TIMEOUT",
+            "--webhook-ready-timeout",
+            Integer,
+        ),
+        option(
+            &["webhooks", "delivery_log_path"],
+            "STACKS_DEVNET_API_WEBHOOK_DELIVERY_LOG_PATH",
+            "--webhook-delivery-log-path",
+            String,
+        ),
+        option(
+            &["webhooks", "allowed_hosts"],
+            "STACKS_DEVNET_API_WEBHOOK_ALLOWED_HOSTS",
+            "--webhook-allowed-hosts",
+            List,
+        ),
+        option(
+            &["webhooks", "allow_http"],
+            "STACKS_DEVNET_API_WEBHOOK_ALLOW_HTTP",
+            "--webhook-allow-http",
+            Boolean,
+        ),
+        option(
+            &["operator", "enabled"],
+            "STACKS_DEVNET_API_OPERATOR_ENABLED",
+            "--operator-enabled",
+            Boolean,
+        ),
+    ]
+};
+
+#[derive(Debug, Clone)]
+struct ConfigOverride {
+    path: &'static [&'static str],
+    value: toml::Value,
+}
+
+/// The layers an `ApiConfig` is built from, in increasing order of precedence:
+/// defaults, the config file, environment variables and CLI flags.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    pub config_path: Option<String>,
+    overrides: Vec<ConfigOverride>,
+}
+
+impl ConfigSources {
+    /// Collects the config path and the overrides set by `env` and `args`, the latter
+    /// excluding the program name. Flags can be passed as `--flag value` or `--flag=value`.
+    pub fn from_env_and_args(
+        env: &HashMap<String, String>,
+        args: &[String],
+    ) -> Result<ConfigSources, ConfigError> {
+        let mut config_path = env.get(CONFIG_PATH_ENV).cloned();
+        let mut overrides = vec![];
+        for option in CONFIG_OPTIONS {
+            if let Some(value) = env.get(option.env) {
+                overrides.push(option.to_override(option.env, value)?);
+            }
+        }
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or(ConfigError {
+                        source: flag.to_string(),
+                        message: "missing value".into(),
+                    })
+            };
+            if flag == CONFIG_PATH_FLAG {
+                config_path = Some(value()?);
+                continue;
+            }
+            match CONFIG_OPTIONS.iter().find(|option| option.flag == flag) {
+                Some(option) => overrides.push(option.to_override(flag, &value()?)?),
+                None => {
+                    return Err(ConfigError {
+                        source: flag.to_string(),
+                        message: "unknown flag, see --help".into(),
+                    })
+                }
+            }
+        }
+        Ok(ConfigSources {
+            config_path,
+            overrides,
+        })
+    }
+
+    /// Lists the supported flags and environment variables.
+    pub fn usage() -> String {
+        let mut usage = format!("  {CONFIG_PATH_FLAG} <path> ({CONFIG_PATH_ENV})\n");
+        for option in CONFIG_OPTIONS {
+            let value = match option.kind {
+                ConfigValueKind::String => "<value>",
+                ConfigValueKind::List => "<value,...>",
+                ConfigValueKind::Integer | ConfigValueKind::Float => "<number>",
+                ConfigValueKind::Boolean => "<true|false>",
+            };
+            usage.push_str(&format!("  {} {value} ({})\n", option.flag, option.env));
+        }
+        usage
+    }
+
+    /// Reads the config file, if any, and builds the config from all layers.
+    pub fn load(&self) -> Result<ApiConfig, ConfigError> {
+        let file_buffer = match &self.config_path {
+            Some(config_path) => fs::read(config_path).map_err(|e| ConfigError {
+                source: config_path.clone(),
+                message: format!("unable to read file: {e}"),
+            })?,
+            None => vec![],
+        };
+        self.load_from_slice(&file_buffer)
+    }
+
+    fn load_from_slice(&self, file_buffer: &[u8]) -> Result<ApiConfig, ConfigError> {
+        let source = self.config_path.clone().unwrap_or("config".into());
+        let mut config: toml::Value = toml::from_slice(file_buffer).map_err(|e| ConfigError {
+            source: source.clone(),
+            message: format!("malformatted config: {e}"),
+        })?;
+        for ConfigOverride { path, value } in &self.overrides {
+            let mut table = &mut config;
+            for key in &path[..path.len() - 1] {
+                table = table
+                    .as_table_mut()
+                    .map(|t| {
+                        t.entry(key.to_string())
+                            .or_insert(toml::Value::Table(Default::default()))
+                    })
+                    .ok_or(ConfigError {
+                        source: source.clone(),
+                        message: format!("{} must be a table", path.join(".")),
+                    })?;
+            }
+            if let Some(table) = table.as_table_mut() {
+                table.insert(path[path.len() - 1].to_string(), value.clone());
+            }
+        }
+        let mut config: ApiConfig = config.try_into().map_err(|e| ConfigError {
+            source,
+            message: format!("invalid config: {e}"),
+        })?;
+        config
+            .http_response_config
+            .validate()
+            .map_err(|message| ConfigError { source, message })?;
+        load_key_files(&mut config.auth_config);
+        Ok(config)
+    }
+}
+
+impl ConfigOption {
+    fn to_override(&self, source: &str, value: &str) -> Result<ConfigOverride, ConfigError> {
+        let value = match self.kind {
+            ConfigValueKind::String => toml::Value::String(value.to_string()),
+            ConfigValueKind::List => toml::Value::Array(
+                value
+                    .split(',')
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+                    .map(|v| toml::Value::String(v.to_string()))
+                    .collect(),
+            ),
+            ConfigValueKind::Integer => {
+                toml::Value::Integer(value.parse().map_err(|_| ConfigError {
+                    source: source.to_string(),
+                    message: format!("expected a number, got {value}"),
+                })?)
+            }
+            ConfigValueKind::Float => {
+                toml::Value::Float(value.parse().map_err(|_| ConfigError {
+                    source: source.to_string(),
+                    message: format!("expected a number, got {value}"),
+                })?)
+            }
+            ConfigValueKind::Boolean => {
+                toml::Value::Boolean(value.parse().map_err(|_| ConfigError {
+                    source: source.to_string(),
+                    message: format!("expected true or false, got {value}"),
+                })?)
+            }
+        };
+        Ok(ConfigOverride {
+            path: self.path,
+            value,
+        })
+    }
+}
+
+/// Keeps a shared `ApiConfig` in sync with its file, reapplying the environment and CLI
+/// overrides on top. Unlike at startup, a config that can't be read or parsed is rejected
+/// and the current one is kept.
+pub struct ApiConfigReloader {
+    sources: ConfigSources,
+    config: Arc<RwLock<ApiConfig>>,
+    file_buffer: Vec<u8>,
+    /// The contents of the key files the current config refers to, if they could be read.
+    key_file_buffers: Vec<(String, Option<Vec<u8>>)>,
+}
+
+impl ApiConfigReloader {
+    pub fn new(sources: ConfigSources, config: Arc<RwLock<ApiConfig>>) -> ApiConfigReloader {
+        let file_buffer = match &sources.config_path {
+            Some(config_path) => fs::read(config_path).unwrap_or_default(),
+            None => vec![],
+        };
+        let mut reloader = ApiConfigReloader {
+            sources,
+            config,
+            file_buffer,
+            key_file_buffers: vec![],
+        };
+        reloader.key_file_buffers = read_key_files(&reloader.config().auth_config);
+        reloader
+    }
+
+    /// Gets a snapshot of the current config.
+    pub fn config(&self) -> ApiConfig {
+        match self.config.read() {
+            Ok(config) => config.clone(),
+            Err(e) => e.into_inner().clone(),
+        }
+    }
+
+    /// Re-reads the config file and the key files it refers to, and swaps in the new
+    /// config if any of them changed. Returns whether the config was swapped.
+    pub fn reload(&mut self) -> Result<bool, ConfigError> {
+        let source = self.sources.config_path.clone().unwrap_or("config".into());
+        let file_buffer = match &self.sources.config_path {
+            Some(config_path) => fs::read(config_path).map_err(|e| ConfigError {
+                source: config_path.clone(),
+                message: format!("unable to read file: {e}"),
+            })?,
+            None => vec![],
+        };
+        let key_file_buffers = read_key_files(&self.config().auth_config);
+        if file_buffer == self.file_buffer && key_file_buffers == self.key_file_buffers {
+            return Ok(false);
+        }
+        // remember rejected contents too, so an invalid file is only reported once
+        let config = self.sources.load_from_slice(&file_buffer);
+        self.file_buffer = file_buffer;
+        self.key_file_buffers = key_file_buffers;
+        let config = config?;
+        self.key_file_buffers = read_key_files(&config.auth_config);
+        match self.config.write() {
+            Ok(mut current) => *current = config,
+            Err(e) => {
+                return Err(ConfigError {
+                    source,
+                    message: format!("unable to update config: {e}"),
+                })
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn read_key_files(auth_config: &AuthConfig) -> Vec<(String, Option<Vec<u8>>)> {
+    auth_config
+        .get_key_file_paths()
+        .into_iter()
+        .map(|path| {
+            let buffer = fs::read(&path).ok();
+            (path, buffer)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // lists of tables, which can only be set in the config file
+    const FILE_ONLY_FIELDS: &[&str] = &["auth.api_keys", "quota.groups"];
+
+    fn get_field_paths(value: &serde_json::Value, prefix: &str, paths: &mut Vec<String>) {
+        match value.as_object() {
+            Some(fields) => {
+                for (key, value) in fields {
+                    let path = match prefix {
+                        "" => key.clone(),
+                        _ => format!("{prefix}.{key}"),
+                    };
+                    get_field_paths(value, &path, paths);
+                }
+            }
+            None => paths.push(prefix.to_string()),
+        }
+    }
+
+    #[test]
+    fn it_overrides_every_config_field() {
+        let mut config = ApiConfig::default();
+        // nested tables are only serialized when set
+        config.server_config.tls = Some(TlsConfig::default());
+        config.auth_config.jwt = Some(JwtConfig::default());
+
+        let mut paths = vec![];
+        get_field_paths(&serde_json::to_value(&config).unwrap(), "", &mut paths);
+        let missing: Vec<String> = paths
+            .into_iter()
+            .filter(|path| !FILE_ONLY_FIELDS.contains(&path.as_str()))
+            .filter(|path| {
+                !CONFIG_OPTIONS
+                    .iter()
+                    .any(|option| option.path.join(".") == *path)
+            })
+            .collect();
+        assert_eq!(missing, Vec::<String>::new());
+    }
+}
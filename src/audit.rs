@@ -0,0 +1,382 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    net::SocketAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hiro_system_kit::slog;
+use hyper::{Body, Method, Request, Response, Uri};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use crate::{
+    api_config::AuditConfig,
+    routes::{get_query_param, get_standardized_path_parts},
+    Context, DevNetError, ErrorCode,
+};
+
+const DEFAULT_QUERY_LIMIT: usize = 100;
+const MAX_QUERY_LIMIT: usize = 1000;
+
+/// The address of the client connected to the API, attached to each request by the server.
+#[derive(Clone, Copy, Debug)]
+pub struct PeerAddr(pub SocketAddr);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    CreateDevnet,
+    DeleteDevnet,
+    ReloadConfig,
+    ListDevnets,
+    QueryAuditLog,
+}
+
+impl AuditAction {
+    /// Gets the audited action a request is for, if any.
+    pub fn from_request(method: &Method, path: &str) -> Option<AuditAction> {
+        match (method, path.trim_end_matches('/')) {
+            (&Method::POST, "/api/v1/networks") => return Some(AuditAction::CreateDevnet),
+            (&Method::GET, "/api/v1/admin/networks") => return Some(AuditAction::ListDevnets),
+            (&Method::GET, "/api/v1/admin/audit") => return Some(AuditAction::QueryAuditLog),
+            _ => {}
+        }
+        let path_parts = get_standardized_path_parts(path);
+        match (method, path_parts.route.as_str(), &path_parts.subroute) {
+            (&Method::DELETE, "network", None) if path_parts.network.is_some() => {
+                Some(AuditAction::DeleteDevnet)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+/// A line of the audit log.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct AuditRecord {
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    pub request_id: Option<String>,
+    pub action: AuditAction,
+    /// The authenticated user or API key, if authentication succeeded.
+    pub user_id: Option<String>,
+    pub network: Option<String>,
+    /// The address of the client connected to the API.
+    pub source_ip: Option<String>,
+    /// The `X-Forwarded-For` header set by proxies in front of the API, as sent.
+    pub forwarded_for: Option<String>,
+    pub outcome: AuditOutcome,
+    pub status: Option<u16>,
+    pub error_code: Option<ErrorCode>,
+    pub message: Option<String>,
+    /// The SHA-256 of the submitted devnet config or project archive.
+    pub config_hash: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(action: AuditAction, ctx: &Context) -> AuditRecord {
+        AuditRecord {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Could not get current time in secs")
+                .as_secs(),
+            request_id: ctx.request_id.clone(),
+            action,
+            user_id: None,
+            network: None,
+            source_ip: None,
+            forwarded_for: None,
+            outcome: AuditOutcome::Success,
+            status: None,
+            error_code: None,
+            message: None,
+            config_hash: None,
+        }
+    }
+
+    /// Starts the record of a request, if it is for an audited action.
+    pub fn from_request(request: &Request<Body>, ctx: &Context) -> Option<AuditRecord> {
+        let action = AuditAction::from_request(request.method(), request.uri().path())?;
+        let mut record = AuditRecord::new(action, ctx);
+        if action == AuditAction::DeleteDevnet {
+            record.network = get_standardized_path_parts(request.uri().path()).network;
+        }
+        record.source_ip = request
+            .extensions()
+            .get::<PeerAddr>()
+            .map(|peer| peer.0.ip().to_string());
+        record.forwarded_for = request
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        Some(record)
+    }
+
+    /// Sets the outcome from the response. Error responses carry their `ErrorCode`
+    /// as an extension.
+    pub fn set_response(&mut self, response: &Response<Body>) {
+        let status = response.status();
+        self.status = Some(status.as_u16());
+        if status.is_client_error() || status.is_server_error() {
+            self.outcome = AuditOutcome::Failure;
+            self.error_code = Some(
+                response
+                    .extensions()
+                    .get::<ErrorCode>()
+                    .copied()
+                    .unwrap_or_else(|| ErrorCode::from_status(status.as_u16())),
+            );
+        }
+    }
+
+    pub fn set_config(&mut self, body: &[u8]) {
+        self.config_hash = Some(format!("{:x}", Sha256::digest(body)));
+    }
+}
+
+/// Filters of the audit log query, parsed from the `user_id`, `network`, `action`,
+/// `since` and `limit` query parameters.
+#[derive(Debug, Default, PartialEq)]
+pub struct AuditFilter {
+    pub user_id: Option<String>,
+    pub network: Option<String>,
+    pub action: Option<AuditAction>,
+    pub since: Option<u64>,
+    pub limit: usize,
+}
+
+impl AuditFilter {
+    pub fn from_uri(uri: &Uri) -> Result<AuditFilter, String> {
+        let action = match get_query_param(uri, "action") {
+            Some(action) => Some(
+                serde_json::from_value(serde_json::Value::String(action.clone()))
+                    .map_err(|_| format!("invalid action query parameter: {action}"))?,
+            ),
+            None => None,
+        };
+        let since = match get_query_param(uri, "since") {
+            Some(since) => Some(
+                since
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid since query parameter: {since}"))?,
+            ),
+            None => None,
+        };
+        let limit = match get_query_param(uri, "limit") {
+            Some(limit) => limit
+                .parse::<usize>()
+                .map_err(|_| format!("invalid limit query parameter: {limit}"))?,
+            None => DEFAULT_QUERY_LIMIT,
+        };
+        if limit == 0 || limit > MAX_QUERY_LIMIT {
+            return Err(format!(
+                "limit query parameter must be between 1 and {MAX_QUERY_LIMIT}"
+            ));
+        }
+        Ok(AuditFilter {
+            user_id: get_query_param(uri, "user_id"),
+            network: get_query_param(uri, "network"),
+            action,
+            since,
+            limit,
+        })
+    }
+
+    fn matches(&self, record: &AuditRecord) -> bool {
+        self.user_id
+            .as_ref()
+            .is_none_or(|user_id| record.user_id.as_ref() == Some(user_id))
+            && self
+                .network
+                .as_ref()
+                .is_none_or(|network| record.network.as_ref() == Some(network))
+            && self.action.is_none_or(|action| record.action == action)
+            && self.since.is_none_or(|since| record.timestamp >= since)
+    }
+}
+
+/// Appends audit records as JSON lines to the configured file, or to stdout.
+pub struct AuditLog {
+    path: Option<String>,
+}
+
+impl AuditLog {
+    pub fn new(audit_config: &AuditConfig) -> AuditLog {
+        AuditLog {
+            path: audit_config.path.clone(),
+        }
+    }
+
+    pub fn record(&self, record: &AuditRecord, ctx: &Context) {
+        if let Err(e) = self.append(record) {
+            ctx.try_log(|logger| slog::error!(logger, "failed to write audit record: {}", e));
+        }
+    }
+
+    fn append(&self, record: &AuditRecord) -> Result<(), String> {
+        let mut line = serde_json::to_vec(record).map_err(|e| e.to_string())?;
+        line.push(b'\n');
+        let result = match &self.path {
+            Some(path) => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(&line)),
+            None => io::stdout().lock().write_all(&line),
+        };
+        result.map_err(|e| e.to_string())
+    }
+
+    /// Gets the most recent records matching `filter`, newest first.
+    pub fn query(
+        &self,
+        filter: &AuditFilter,
+        ctx: &Context,
+    ) -> Result<Vec<AuditRecord>, DevNetError> {
+        let Some(path) = &self.path else {
+            return Err(DevNetError {
+                message: "audit records are written to stdout, set an audit log path to query them"
+                    .into(),
+                code: 501,
+                error_code: ErrorCode::NotImplemented,
+            });
+        };
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(DevNetError {
+                    message: format!("failed to read audit log: {e}"),
+                    code: 500,
+                    error_code: ErrorCode::InternalError,
+                })
+            }
+        };
+        let mut records = vec![];
+        for line in contents.lines().rev() {
+            if records.len() == filter.limit {
+                break;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<AuditRecord>(line) {
+                Ok(record) if filter.matches(&record) => records.push(record),
+                Ok(_) => {}
+                Err(e) => ctx.try_log(|logger| {
+                    slog::warn!(logger, "skipping malformed audit record: {}", e)
+                }),
+            }
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Method, Uri};
+
+    use super::{AuditAction, AuditFilter, AuditLog, AuditRecord};
+    use crate::{api_config::AuditConfig, Context};
+
+    #[test]
+    fn it_gets_audited_actions() {
+        for (method, path, action) in [
+            (
+                Method::POST,
+                "/api/v1/networks",
+                Some(AuditAction::CreateDevnet),
+            ),
+            (Method::GET, "/api/v1/networks", None),
+            (
+                Method::DELETE,
+                "/api/v1/network/some-network",
+                Some(AuditAction::DeleteDevnet),
+            ),
+            (
+                Method::DELETE,
+                "/api/v1/network/some-network/stacks-blockchain/v2",
+                None,
+            ),
+            (Method::GET, "/api/v1/network/some-network", None),
+            (
+                Method::GET,
+                "/api/v1/admin/networks",
+                Some(AuditAction::ListDevnets),
+            ),
+            (
+                Method::GET,
+                "/api/v1/admin/audit",
+                Some(AuditAction::QueryAuditLog),
+            ),
+        ] {
+            assert_eq!(
+                AuditAction::from_request(&method, path),
+                action,
+                "{method} {path}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_appends_and_queries_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let audit_log = AuditLog::new(&AuditConfig {
+            path: Some(path.to_str().unwrap().into()),
+        });
+        let ctx = Context::empty();
+
+        for (timestamp, user_id, action) in [
+            (100, "user-1", AuditAction::CreateDevnet),
+            (200, "user-2", AuditAction::CreateDevnet),
+            (300, "user-1", AuditAction::DeleteDevnet),
+        ] {
+            let mut record = AuditRecord::new(action, &ctx);
+            record.timestamp = timestamp;
+            record.user_id = Some(user_id.into());
+            record.network = Some(user_id.into());
+            record.set_config(b"{}");
+            audit_log.record(&record, &ctx);
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        let query = |query: &str| {
+            let uri = Uri::try_from(format!("/api/v1/admin/audit?{query}")).unwrap();
+            let filter = AuditFilter::from_uri(&uri).unwrap();
+            audit_log
+                .query(&filter, &ctx)
+                .unwrap()
+                .iter()
+                .map(|record| record.timestamp)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(query(""), vec![300, 200, 100]);
+        assert_eq!(query("user_id=user-1"), vec![300, 100]);
+        assert_eq!(query("action=create_devnet&since=150"), vec![200]);
+        assert_eq!(query("limit=1"), vec![300]);
+
+        let uri = Uri::from_static("/api/v1/admin/audit?action=reset");
+        assert!(AuditFilter::from_uri(&uri).is_err());
+    }
+
+    #[test]
+    fn it_hashes_submitted_configs() {
+        let mut record = AuditRecord::new(AuditAction::CreateDevnet, &Context::empty());
+        record.set_config(b"{}");
+        assert_eq!(
+            record.config_hash.unwrap(),
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+    }
+}
@@ -0,0 +1,713 @@
+use std::{collections::HashMap, fs, str::FromStr, sync::Arc};
+
+use hyper::{header::AUTHORIZATION, http::HeaderValue, HeaderMap};
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use serde_json::Value;
+
+use crate::{
+    api_config::{ApiKeyConfig, ApiKeyRole, ApiKeysFile, AuthConfig, AuthMode, JwtConfig},
+    tls::ClientCertificate,
+    DevNetError, ErrorCode,
+};
+
+pub(crate) const DEFAULT_AUTH_HEADER: &str = "x-auth-request-user";
+pub(crate) const DEFAULT_API_KEY_HEADER: &str = "x-api-key";
+const DEFAULT_USER_ID_CLAIM: &str = "sub";
+const DEFAULT_JWT_ALGORITHM: &str = "RS256";
+
+/// The contents of the files an `AuthConfig` refers to, read when the config is loaded
+/// or reloaded rather than on every request. Errors are kept, to be reported by the
+/// requests and readiness checks that need the files.
+#[derive(Clone, Default)]
+pub struct AuthKeyFiles {
+    api_keys: Option<Result<Arc<Vec<ApiKeyConfig>>, DevNetError>>,
+    jwks: Option<Result<Arc<JwkSet>, DevNetError>>,
+}
+
+/// Reads the `api_keys_path` and `jwt.jwks_path` files of the config.
+pub fn load_key_files(auth_config: &mut AuthConfig) {
+    auth_config.key_files = AuthKeyFiles {
+        api_keys: auth_config
+            .api_keys_path
+            .as_deref()
+            .map(|path| read_api_keys_file(path).map(Arc::new)),
+        jwks: auth_config
+            .jwt
+            .as_ref()
+            .and_then(|jwt_config| jwt_config.jwks_path.as_deref())
+            .map(|path| read_jwks(path).map(Arc::new)),
+    };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatedUser {
+    /// For the `user` role, the id (and namespace) of the user. For other roles,
+    /// the name of the API key used.
+    pub user_id: String,
+    pub role: ApiKeyRole,
+    pub network_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkAction {
+    Create,
+    Delete,
+    Read,
+    /// Waits for a network to be ready, which CI pipelines do between creating and
+    /// deleting a network.
+    Wait,
+    Proxy,
+}
+
+impl AuthenticatedUser {
+    fn user(user_id: String) -> AuthenticatedUser {
+        AuthenticatedUser {
+            user_id,
+            role: ApiKeyRole::User,
+            network_prefix: None,
+        }
+    }
+
+    pub fn is_privileged(&self) -> bool {
+        self.role != ApiKeyRole::User
+    }
+
+    /// Checks whether this user may use operator routes that span all networks.
+    pub fn authorize_admin(&self) -> Result<(), DevNetError> {
+        match self.role {
+            ApiKeyRole::Admin => Ok(()),
+            _ => Err(DevNetError {
+                message: "this route requires an admin api key".into(),
+                code: 403,
+                error_code: ErrorCode::Forbidden,
+            }),
+        }
+    }
+
+    /// Checks whether this user may perform `action` on `network`.
+    pub fn authorize(&self, network: &str, action: NetworkAction) -> Result<(), DevNetError> {
+        match self.role {
+            ApiKeyRole::Admin => Ok(()),
+            ApiKeyRole::User => match network == self.user_id {
+                true => Ok(()),
+                false => Err(DevNetError {
+                    message: "network id must match authenticated user id".into(),
+                    code: 403,
+                    error_code: ErrorCode::Forbidden,
+                }),
+            },
+            ApiKeyRole::Ci => {
+                let allowed_action = matches!(
+                    action,
+                    NetworkAction::Create | NetworkAction::Delete | NetworkAction::Wait
+                );
+                let allowed_network = self
+                    .network_prefix
+                    .as_ref()
+                    .is_some_and(|prefix| network.starts_with(prefix));
+                match allowed_action && allowed_network {
+                    true => Ok(()),
+                    false => Err(DevNetError {
+                        message: format!(
+                            "api key {} may only create, delete or wait for networks with prefix {}",
+                            self.user_id,
+                            self.network_prefix.as_deref().unwrap_or_default()
+                        ),
+                        code: 403,
+                        error_code: ErrorCode::Forbidden,
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// Authenticates a request with an API key, if one is provided, and otherwise
+/// according to the configured auth mode.
+pub fn authenticate(
+    headers: &HeaderMap<HeaderValue>,
+    client_certificate: Option<&ClientCertificate>,
+    auth_config: &AuthConfig,
+) -> Result<AuthenticatedUser, DevNetError> {
+    let api_key_header = auth_config
+        .api_key_header
+        .as_deref()
+        .unwrap_or(DEFAULT_API_KEY_HEADER);
+    if let Some(api_key) = headers.get(api_key_header) {
+        return authenticate_api_key(api_key, auth_config);
+    }
+    if auth_config.mode == Some(AuthMode::ClientCertificate) {
+        return match client_certificate {
+            Some(ClientCertificate { common_name }) => Ok(AuthenticatedUser::user(
+                apply_namespace_prefix(common_name, auth_config),
+            )),
+            None => Err(unauthorized("missing client certificate".into())),
+        };
+    }
+    get_user_id(headers, auth_config).map(AuthenticatedUser::user)
+}
+
+fn authenticate_api_key(
+    api_key: &HeaderValue,
+    auth_config: &AuthConfig,
+) -> Result<AuthenticatedUser, DevNetError> {
+    let ApiKeyConfig {
+        name,
+        role,
+        user_id,
+        network_prefix,
+        ..
+    } = auth_config
+        .api_keys
+        .iter()
+        .flatten()
+        .chain(get_api_keys_file(auth_config)?)
+        .find(|k| constant_time_eq(k.key.as_bytes(), api_key.as_bytes()))
+        .cloned()
+        .ok_or_else(|| unauthorized("invalid api key".into()))?;
+
+    match role {
+        ApiKeyRole::User => match user_id {
+            Some(user_id) => Ok(AuthenticatedUser::user(apply_namespace_prefix(
+                &user_id,
+                auth_config,
+            ))),
+            None => Err(DevNetError {
+                message: format!("api key {name} with the user role must set a user_id"),
+                code: 500,
+                error_code: ErrorCode::InternalError,
+            }),
+        },
+        ApiKeyRole::Ci if network_prefix.is_none() => Err(DevNetError {
+            message: format!("api key {name} with the ci role must set a network_prefix"),
+            code: 500,
+            error_code: ErrorCode::InternalError,
+        }),
+        role => Ok(AuthenticatedUser {
+            user_id: name,
+            role,
+            network_prefix,
+        }),
+    }
+}
+
+fn read_api_keys_file(api_keys_path: &str) -> Result<Vec<ApiKeyConfig>, DevNetError> {
+    let file = fs::read(api_keys_path).map_err(|e| DevNetError {
+        message: format!("unable to read api keys file {api_keys_path}: {e}"),
+        code: 500,
+        error_code: ErrorCode::InternalError,
+    })?;
+    let file: ApiKeysFile = toml::from_slice(&file).map_err(|e| DevNetError {
+        message: format!("unable to parse api keys file {api_keys_path}: {e}"),
+        code: 500,
+        error_code: ErrorCode::InternalError,
+    })?;
+    Ok(file.api_keys)
+}
+
+/// Gets the keys of the `api_keys_path` file, as loaded with the config.
+fn get_api_keys_file(auth_config: &AuthConfig) -> Result<&[ApiKeyConfig], DevNetError> {
+    match (&auth_config.api_keys_path, &auth_config.key_files.api_keys) {
+        (None, _) => Ok(&[]),
+        (Some(_), Some(Ok(api_keys))) => Ok(api_keys.as_slice()),
+        (Some(_), Some(Err(e))) => Err(e.clone()),
+        (Some(api_keys_path), None) => Err(get_not_loaded_error(api_keys_path)),
+    }
+}
+
+/// Gets the keys of the `jwt.jwks_path` file, as loaded with the config.
+fn get_jwks(auth_config: &AuthConfig) -> Result<Option<&JwkSet>, DevNetError> {
+    let jwks_path = auth_config
+        .jwt
+        .as_ref()
+        .and_then(|jwt_config| jwt_config.jwks_path.as_ref());
+    match (jwks_path, &auth_config.key_files.jwks) {
+        (None, _) => Ok(None),
+        (Some(_), Some(Ok(jwks))) => Ok(Some(jwks.as_ref())),
+        (Some(_), Some(Err(e))) => Err(e.clone()),
+        (Some(jwks_path), None) => Err(get_not_loaded_error(jwks_path)),
+    }
+}
+
+fn get_not_loaded_error(path: &str) -> DevNetError {
+    DevNetError {
+        message: format!("{path} was not loaded with the config"),
+        code: 500,
+        error_code: ErrorCode::InternalError,
+    }
+}
+
+/// Checks that the API keys file and JWT keys the auth config refers to can be loaded,
+/// since requests would otherwise fail with internal errors.
+pub fn check_auth_config(auth_config: &AuthConfig) -> Result<(), DevNetError> {
+    get_api_keys_file(auth_config)?;
+    if auth_config.mode != Some(AuthMode::Jwt) {
+        return Ok(());
+    }
+    match &auth_config.jwt {
+        Some(JwtConfig {
+            jwks_path: Some(_), ..
+        }) => get_jwks(auth_config).map(|_| ()),
+        // without a jwks file the key is the configured pem, whose algorithm comes
+        // from the config rather than the token
+        Some(jwt_config) => get_decoding_key(jwt_config, None, None, Algorithm::RS256).map(|_| ()),
+        None => Err(DevNetError {
+            message: "jwt auth mode requires an [auth.jwt] config".into(),
+            code: 500,
+            error_code: ErrorCode::InternalError,
+        }),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn apply_namespace_prefix(user_id: &str, auth_config: &AuthConfig) -> String {
+    let user_id = user_id.replace('|', "-");
+    match &auth_config.namespace_prefix {
+        Some(prefix) => format!("{prefix}{user_id}"),
+        None => user_id,
+    }
+}
+
+/// Authenticates a request according to the configured auth mode and returns
+/// the id of the requesting user, with the `namespace_prefix` applied.
+pub fn get_user_id(
+    headers: &HeaderMap<HeaderValue>,
+    auth_config: &AuthConfig,
+) -> Result<String, DevNetError> {
+    let user_id = match auth_config.mode.clone().unwrap_or_default() {
+        AuthMode::Header => get_user_id_from_header(headers, auth_config)?,
+        // the certificate is only known to `authenticate`
+        AuthMode::ClientCertificate => {
+            return Err(unauthorized("missing client certificate".into()))
+        }
+        AuthMode::Jwt => match &auth_config.jwt {
+            Some(jwt_config) => get_user_id_from_jwt(headers, jwt_config, get_jwks(auth_config)?)?,
+            None => {
+                return Err(DevNetError {
+                    message: "jwt auth mode requires an [auth.jwt] config".into(),
+                    code: 500,
+                    error_code: ErrorCode::InternalError,
+                })
+            }
+        },
+    };
+    Ok(apply_namespace_prefix(&user_id, auth_config))
+}
+
+fn get_user_id_from_header(
+    headers: &HeaderMap<HeaderValue>,
+    auth_config: &AuthConfig,
+) -> Result<String, DevNetError> {
+    let auth_header = auth_config
+        .auth_header
+        .as_deref()
+        .unwrap_or(DEFAULT_AUTH_HEADER);
+    match headers.get(auth_header) {
+        Some(auth_header_value) => match auth_header_value.to_str() {
+            Ok(user_id) => Ok(user_id.to_owned()),
+            Err(e) => Err(DevNetError {
+                message: format!("unable to parse auth header: {e}"),
+                code: 401,
+                error_code: ErrorCode::Unauthorized,
+            }),
+        },
+        None => Err(DevNetError {
+            message: "missing required auth header".into(),
+            code: 401,
+            error_code: ErrorCode::Unauthorized,
+        }),
+    }
+}
+
+fn unauthorized(message: String) -> DevNetError {
+    DevNetError {
+        message,
+        code: 401,
+        error_code: ErrorCode::Unauthorized,
+    }
+}
+
+fn get_user_id_from_jwt(
+    headers: &HeaderMap<HeaderValue>,
+    jwt_config: &JwtConfig,
+    jwks: Option<&JwkSet>,
+) -> Result<String, DevNetError> {
+    let token = match headers.get(AUTHORIZATION) {
+        Some(value) => value
+            .to_str()
+            .map_err(|e| unauthorized(format!("unable to parse authorization header: {e}")))?,
+        None => return Err(unauthorized("missing required authorization header".into())),
+    };
+    let token = token
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| unauthorized("authorization header must use the Bearer scheme".into()))?;
+
+    let header =
+        decode_header(token).map_err(|e| unauthorized(format!("invalid bearer token: {e}")))?;
+    let (key, algorithm) = get_decoding_key(jwt_config, jwks, header.kid.as_deref(), header.alg)?;
+
+    let mut validation = Validation::new(algorithm);
+    if let Some(issuer) = &jwt_config.issuer {
+        validation.set_issuer(&[issuer]);
+    }
+    match &jwt_config.audience {
+        Some(audience) => validation.set_audience(&[audience]),
+        None => validation.validate_aud = false,
+    }
+
+    let claims = decode::<HashMap<String, Value>>(token, &key, &validation)
+        .map_err(|e| unauthorized(format!("invalid bearer token: {e}")))?
+        .claims;
+
+    let user_id_claim = jwt_config
+        .user_id_claim
+        .as_deref()
+        .unwrap_or(DEFAULT_USER_ID_CLAIM);
+    match claims.get(user_id_claim) {
+        Some(Value::String(user_id)) if !user_id.is_empty() => Ok(user_id.to_owned()),
+        _ => Err(unauthorized(format!(
+            "bearer token is missing the {user_id_claim} claim"
+        ))),
+    }
+}
+
+/// Returns the key a token should be verified with and the only algorithm it may use.
+/// The algorithm comes from the key's configuration whenever possible so that tokens
+/// cannot pick a weaker one.
+fn get_decoding_key(
+    jwt_config: &JwtConfig,
+    jwks: Option<&JwkSet>,
+    kid: Option<&str>,
+    token_algorithm: Algorithm,
+) -> Result<(DecodingKey, Algorithm), DevNetError> {
+    let config_err = |message: String| DevNetError {
+        message,
+        code: 500,
+        error_code: ErrorCode::InternalError,
+    };
+
+    if let Some(jwks) = jwks {
+        let jwk = match kid {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| unauthorized("bearer token was not signed by a known key".into()))?;
+        let algorithm = get_jwk_algorithm(jwk)?.unwrap_or(token_algorithm);
+        let key = DecodingKey::from_jwk(jwk)
+            .map_err(|e| config_err(format!("unable to use key from jwks file: {e}")))?;
+        return Ok((key, algorithm));
+    }
+
+    if let Some(pem) = &jwt_config.public_key_pem {
+        let algorithm = jwt_config
+            .algorithm
+            .as_deref()
+            .unwrap_or(DEFAULT_JWT_ALGORITHM);
+        let algorithm = Algorithm::from_str(algorithm)
+            .map_err(|e| config_err(format!("invalid jwt algorithm {algorithm}: {e}")))?;
+        let key = match algorithm {
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512 => DecodingKey::from_rsa_pem(pem.as_bytes()),
+            Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem.as_bytes()),
+            Algorithm::EdDSA => DecodingKey::from_ed_pem(pem.as_bytes()),
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                return Err(config_err(
+                    "public_key_pem cannot be used with an HMAC algorithm".into(),
+                ))
+            }
+        }
+        .map_err(|e| config_err(format!("unable to parse public_key_pem: {e}")))?;
+        return Ok((key, algorithm));
+    }
+
+    Err(config_err(
+        "jwt auth requires either jwks_path or public_key_pem".into(),
+    ))
+}
+
+fn read_jwks(jwks_path: &str) -> Result<JwkSet, DevNetError> {
+    let config_err = |message: String| DevNetError {
+        message,
+        code: 500,
+        error_code: ErrorCode::InternalError,
+    };
+    let jwks = fs::read(jwks_path)
+        .map_err(|e| config_err(format!("unable to read jwks file {jwks_path}: {e}")))?;
+    serde_json::from_slice(&jwks)
+        .map_err(|e| config_err(format!("unable to parse jwks file {jwks_path}: {e}")))
+}
+
+fn get_jwk_algorithm(jwk: &Jwk) -> Result<Option<Algorithm>, DevNetError> {
+    let Some(key_algorithm) = &jwk.common.key_algorithm else {
+        return Ok(None);
+    };
+    serde_json::to_value(key_algorithm)
+        .ok()
+        .and_then(|v| v.as_str().and_then(|alg| Algorithm::from_str(alg).ok()))
+        .map(Some)
+        .ok_or_else(|| DevNetError {
+            message: format!("unsupported algorithm for jwks key: {key_algorithm:?}"),
+            code: 500,
+            error_code: ErrorCode::InternalError,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use hyper::{header::AUTHORIZATION, http::HeaderValue, HeaderMap};
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use serde_json::json;
+
+    use super::{authenticate, check_auth_config, get_user_id, load_key_files, NetworkAction};
+    use crate::{
+        api_config::{ApiKeyConfig, ApiKeyRole, AuthConfig, AuthMode, JwtConfig},
+        tls::ClientCertificate,
+    };
+
+    const ISSUER: &str = "https://auth.example.com/";
+    const AUDIENCE: &str = "stacks-devnet-api";
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn get_token(claims: serde_json::Value) -> String {
+        let private_key = fs::read("src/tests/fixtures/jwt-private-key.pem").unwrap();
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some("test-key".into());
+        encode(
+            &header,
+            &claims,
+            &EncodingKey::from_rsa_pem(&private_key).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn get_headers(token: &str) -> HeaderMap<HeaderValue> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        headers
+    }
+
+    fn get_jwks_auth_config() -> AuthConfig {
+        let mut auth_config = AuthConfig {
+            namespace_prefix: Some("platform-".into()),
+            mode: Some(AuthMode::Jwt),
+            jwt: Some(JwtConfig {
+                jwks_path: Some("src/tests/fixtures/jwks.json".into()),
+                issuer: Some(ISSUER.into()),
+                audience: Some(AUDIENCE.into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        load_key_files(&mut auth_config);
+        auth_config
+    }
+
+    #[test]
+    fn it_authenticates_jwt_with_jwks() {
+        let token = get_token(json!({
+            "sub": "auth0|some-user",
+            "iss": ISSUER,
+            "aud": AUDIENCE,
+            "exp": now() + 600,
+        }));
+        let user_id = get_user_id(&get_headers(&token), &get_jwks_auth_config()).unwrap();
+        assert_eq!(user_id, "platform-auth0-some-user");
+    }
+
+    #[test]
+    fn it_authenticates_jwt_with_pem_and_custom_claim() {
+        let auth_config = AuthConfig {
+            mode: Some(AuthMode::Jwt),
+            jwt: Some(JwtConfig {
+                public_key_pem: Some(
+                    fs::read_to_string("src/tests/fixtures/jwt-public-key.pem").unwrap(),
+                ),
+                user_id_claim: Some("email".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let token = get_token(json!({
+            "sub": "ignored",
+            "email": "dev@example.com",
+            "exp": now() + 600,
+        }));
+        let user_id = get_user_id(&get_headers(&token), &auth_config).unwrap();
+        assert_eq!(user_id, "dev@example.com");
+    }
+
+    #[test]
+    fn it_rejects_invalid_jwts() {
+        let auth_config = get_jwks_auth_config();
+        let valid_claims =
+            json!({ "sub": "user", "iss": ISSUER, "aud": AUDIENCE, "exp": now() + 600 });
+
+        let mut expired = valid_claims.clone();
+        expired["exp"] = json!(now() - 600);
+        let mut wrong_issuer = valid_claims.clone();
+        wrong_issuer["iss"] = json!("https://evil.example.com/");
+        let mut wrong_audience = valid_claims.clone();
+        wrong_audience["aud"] = json!("some-other-api");
+        let mut missing_user = valid_claims.clone();
+        missing_user.as_object_mut().unwrap().remove("sub");
+
+        for claims in [expired, wrong_issuer, wrong_audience, missing_user] {
+            let err = get_user_id(&get_headers(&get_token(claims)), &auth_config).unwrap_err();
+            assert_eq!(err.code, 401);
+        }
+
+        let err = get_user_id(&HeaderMap::new(), &auth_config).unwrap_err();
+        assert_eq!(err.code, 401);
+        assert_eq!(err.message, "missing required authorization header");
+    }
+
+    fn get_api_key_auth_config() -> AuthConfig {
+        let api_key = |name: &str, role: ApiKeyRole| ApiKeyConfig {
+            name: name.into(),
+            key: format!("{name}-secret"),
+            role,
+            user_id: Some("auth0|some-user".into()),
+            network_prefix: Some("ci-".into()),
+        };
+        AuthConfig {
+            namespace_prefix: Some("platform-".into()),
+            api_keys: Some(vec![
+                api_key("support", ApiKeyRole::Admin),
+                api_key("pipeline", ApiKeyRole::Ci),
+                api_key("personal", ApiKeyRole::User),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    fn get_api_key_headers(key: &str) -> HeaderMap<HeaderValue> {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_str(key).unwrap());
+        headers
+    }
+
+    #[test]
+    fn it_authorizes_api_keys_by_role() {
+        let auth_config = get_api_key_auth_config();
+
+        let admin =
+            authenticate(&get_api_key_headers("support-secret"), None, &auth_config).unwrap();
+        assert!(admin
+            .authorize("platform-anyone", NetworkAction::Read)
+            .is_ok());
+        assert!(admin
+            .authorize("platform-anyone", NetworkAction::Delete)
+            .is_ok());
+
+        let ci = authenticate(&get_api_key_headers("pipeline-secret"), None, &auth_config).unwrap();
+        assert!(ci.authorize("ci-build-1", NetworkAction::Create).is_ok());
+        assert!(ci.authorize("ci-build-1", NetworkAction::Delete).is_ok());
+        assert!(ci.authorize("ci-build-1", NetworkAction::Wait).is_ok());
+        assert_eq!(
+            ci.authorize("ci-build-1", NetworkAction::Proxy)
+                .unwrap_err()
+                .code,
+            403
+        );
+        assert_eq!(
+            ci.authorize("platform-anyone", NetworkAction::Create)
+                .unwrap_err()
+                .code,
+            403
+        );
+
+        let user =
+            authenticate(&get_api_key_headers("personal-secret"), None, &auth_config).unwrap();
+        assert_eq!(user.user_id, "platform-auth0-some-user");
+        assert!(user
+            .authorize("platform-auth0-some-user", NetworkAction::Read)
+            .is_ok());
+        assert_eq!(
+            user.authorize("platform-anyone", NetworkAction::Read)
+                .unwrap_err()
+                .code,
+            403
+        );
+
+        let err =
+            authenticate(&get_api_key_headers("wrong-secret"), None, &auth_config).unwrap_err();
+        assert_eq!(err.code, 401);
+    }
+
+    #[test]
+    fn it_authenticates_client_certificates() {
+        let auth_config = AuthConfig {
+            mode: Some(AuthMode::ClientCertificate),
+            namespace_prefix: Some("platform-".into()),
+            ..Default::default()
+        };
+        let client_certificate = ClientCertificate {
+            common_name: "auth0|some-user".into(),
+        };
+        let user =
+            authenticate(&HeaderMap::new(), Some(&client_certificate), &auth_config).unwrap();
+        assert_eq!(user.user_id, "platform-auth0-some-user");
+
+        let err = authenticate(&HeaderMap::new(), None, &auth_config).unwrap_err();
+        assert_eq!(
+            (err.code, err.message.as_str()),
+            (401, "missing client certificate")
+        );
+    }
+
+    #[test]
+    fn it_checks_auth_config_files_load() {
+        assert!(check_auth_config(&get_jwks_auth_config()).is_ok());
+        assert!(check_auth_config(&AuthConfig::default()).is_ok());
+
+        let mut auth_config = AuthConfig {
+            api_keys_path: Some("src/tests/fixtures/missing-api-keys.toml".into()),
+            ..Default::default()
+        };
+        let err = check_auth_config(&auth_config).unwrap_err();
+        assert_eq!(
+            err.message,
+            "src/tests/fixtures/missing-api-keys.toml was not loaded with the config"
+        );
+        load_key_files(&mut auth_config);
+        let err = check_auth_config(&auth_config).unwrap_err();
+        assert!(err.message.starts_with("unable to read api keys file"));
+
+        let err = check_auth_config(&AuthConfig {
+            mode: Some(AuthMode::Jwt),
+            jwt: Some(JwtConfig {
+                public_key_pem: Some("not a pem".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err.message.starts_with("unable to parse public_key_pem"));
+    }
+}
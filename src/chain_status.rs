@@ -0,0 +1,484 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarinet_deployments::types::{DeploymentSpecification, TransactionSpecification};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The epoch, PoX and signer state of a devnet's chain.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DevnetChainStatus {
+    /// The current epoch, e.g. `3.1`.
+    pub epoch: Option<String>,
+    pub pox: PoxStatus,
+    /// The signers of the current reward cycle. Unset until the cycle's signer set
+    /// has been chosen.
+    pub signers: Option<Vec<DevnetSigner>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PoxPhase {
+    Reward,
+    Prepare,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct PoxStatus {
+    pub reward_cycle: u64,
+    pub phase: PoxPhase,
+    /// The number of bitcoin blocks until the next phase starts.
+    pub blocks_until_next_phase: u64,
+    pub reward_cycle_length: u64,
+    pub prepare_phase_length: u64,
+    /// Whether enough STX are stacked for PoX to be active in the current cycle.
+    pub active: bool,
+    pub stacked_ustx: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DevnetSigner {
+    pub signing_key: String,
+    pub weight: u32,
+    pub stacked_ustx: u128,
+}
+
+/// The parts of the stacks node's `/v2/pox` response used for the chain status.
+#[derive(Deserialize, Debug)]
+pub struct StacksV2PoxResponse {
+    first_burnchain_block_height: u64,
+    current_burnchain_block_height: u64,
+    prepare_phase_block_length: u64,
+    reward_cycle_length: u64,
+    current_cycle: StacksV2PoxCycle,
+    #[serde(default)]
+    epochs: Vec<StacksV2PoxEpoch>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StacksV2PoxCycle {
+    id: u64,
+    stacked_ustx: u64,
+    is_pox_active: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct StacksV2PoxEpoch {
+    epoch_id: String,
+    start_height: u64,
+    end_height: u64,
+}
+
+/// The stacks node's `/v3/stacker_set/{cycle}` response.
+#[derive(Deserialize, Debug)]
+pub struct StackerSetResponse {
+    stacker_set: StackerSet,
+}
+
+#[derive(Deserialize, Debug)]
+struct StackerSet {
+    signers: Option<Vec<StackerSetSigner>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StackerSetSigner {
+    signing_key: String,
+    weight: u32,
+    stacked_amt: u128,
+}
+
+impl StacksV2PoxResponse {
+    pub fn reward_cycle(&self) -> u64 {
+        self.current_cycle.id
+    }
+
+    pub fn to_chain_status(&self, stacker_set: Option<StackerSetResponse>) -> DevnetChainStatus {
+        let (phase, blocks_until_next_phase) = get_pox_phase(
+            self.first_burnchain_block_height,
+            self.reward_cycle_length,
+            self.prepare_phase_block_length,
+            self.current_burnchain_block_height,
+        );
+        let epoch = self
+            .epochs
+            .iter()
+            .find(|epoch| {
+                epoch.start_height <= self.current_burnchain_block_height
+                    && self.current_burnchain_block_height < epoch.end_height
+            })
+            .map(|epoch| format_epoch_id(&epoch.epoch_id));
+        let signers = stacker_set
+            .and_then(|response| response.stacker_set.signers)
+            .map(|signers| {
+                signers
+                    .into_iter()
+                    .map(|signer| DevnetSigner {
+                        signing_key: signer.signing_key,
+                        weight: signer.weight,
+                        stacked_ustx: signer.stacked_amt,
+                    })
+                    .collect()
+            });
+        DevnetChainStatus {
+            epoch,
+            pox: PoxStatus {
+                reward_cycle: self.current_cycle.id,
+                phase,
+                blocks_until_next_phase,
+                reward_cycle_length: self.reward_cycle_length,
+                prepare_phase_length: self.prepare_phase_block_length,
+                active: self.current_cycle.is_pox_active,
+                stacked_ustx: self.current_cycle.stacked_ustx,
+            },
+            signers,
+        }
+    }
+}
+
+/// Gets the PoX phase of the bitcoin block at `height`, and the number of blocks until the
+/// next phase starts. The first block of a reward cycle has index 1, so the prepare phase
+/// runs through index 0 of the next cycle.
+fn get_pox_phase(
+    first_block_height: u64,
+    reward_cycle_length: u64,
+    prepare_phase_length: u64,
+    height: u64,
+) -> (PoxPhase, u64) {
+    if height <= first_block_height || reward_cycle_length == 0 {
+        return (PoxPhase::Reward, 0);
+    }
+    let reward_index = (height - first_block_height) % reward_cycle_length;
+    let prepare_start = reward_cycle_length.saturating_sub(prepare_phase_length) + 1;
+    if reward_index == 0 {
+        (PoxPhase::Prepare, 1)
+    } else if reward_index >= prepare_start {
+        (PoxPhase::Prepare, reward_cycle_length - reward_index + 1)
+    } else {
+        (PoxPhase::Reward, prepare_start - reward_index)
+    }
+}
+
+/// Formats the node's epoch ids, e.g. `Epoch2_05` or `Epoch30`, like the epochs of a
+/// deployment plan, e.g. `2.05` or `3.0`.
+fn format_epoch_id(epoch_id: &str) -> String {
+    let version = epoch_id.trim_start_matches("Epoch");
+    match version.split_once('_') {
+        Some((major, minor)) => format!("{major}.{minor}"),
+        None if version.len() > 1 => format!("{}.{}", &version[..1], &version[1..]),
+        None => version.to_string(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentTransactionStatus {
+    Confirmed,
+    Pending,
+    /// The stacks node couldn't be asked, or the transaction can't be tracked, e.g.
+    /// bitcoin transfers.
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DeploymentTransactionProgress {
+    /// The type of the transaction, e.g. `contract_publish` or `stx_transfer`.
+    pub transaction_type: String,
+    /// The contract published or called.
+    pub contract_id: Option<String>,
+    pub sender: String,
+    pub status: DeploymentTransactionStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DeploymentBatchProgress {
+    pub id: usize,
+    /// Whether every transaction of the batch is confirmed.
+    pub confirmed: bool,
+    pub transactions: Vec<DeploymentTransactionProgress>,
+}
+
+/// Which transactions of the deployment plan submitted with a devnet are confirmed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DeploymentProgress {
+    pub confirmed_transactions: usize,
+    pub total_transactions: usize,
+    pub batches: Vec<DeploymentBatchProgress>,
+}
+
+/// What to ask the stacks node to work out the progress of a deployment plan.
+#[derive(Debug, Default, PartialEq)]
+pub struct DeploymentQueries {
+    /// The contracts published by the plan, checked for on chain.
+    pub contracts: BTreeSet<String>,
+    /// The senders of the plan's contract calls and STX transfers, whose nonces tell
+    /// how many of their transactions are confirmed.
+    pub accounts: BTreeSet<String>,
+}
+
+enum TrackedBy {
+    Contract(String),
+    Nonce,
+    Untracked,
+}
+
+/// Describes a transaction of a deployment plan, and how its confirmation is tracked.
+fn describe_transaction(
+    transaction: &TransactionSpecification,
+) -> (&'static str, Option<String>, String, TrackedBy) {
+    match transaction {
+        TransactionSpecification::ContractPublish(spec) => {
+            let contract_id = format!("{}.{}", spec.expected_sender, spec.contract_name);
+            (
+                "contract_publish",
+                Some(contract_id.clone()),
+                spec.expected_sender.to_string(),
+                TrackedBy::Contract(contract_id),
+            )
+        }
+        // requirements are published by the remapped sender on devnets
+        TransactionSpecification::RequirementPublish(spec) => {
+            let contract_id = format!("{}.{}", spec.remap_sender, spec.contract_id.name);
+            (
+                "requirement_publish",
+                Some(contract_id.clone()),
+                spec.remap_sender.to_string(),
+                TrackedBy::Contract(contract_id),
+            )
+        }
+        TransactionSpecification::ContractCall(spec) => (
+            "contract_call",
+            Some(spec.contract_id.to_string()),
+            spec.expected_sender.to_string(),
+            TrackedBy::Nonce,
+        ),
+        TransactionSpecification::StxTransfer(spec) => (
+            "stx_transfer",
+            None,
+            spec.expected_sender.to_string(),
+            TrackedBy::Nonce,
+        ),
+        TransactionSpecification::BtcTransfer(spec) => (
+            "btc_transfer",
+            None,
+            spec.expected_sender.clone(),
+            TrackedBy::Untracked,
+        ),
+        TransactionSpecification::EmulatedContractPublish(spec) => (
+            "emulated_contract_publish",
+            Some(format!("{}.{}", spec.emulated_sender, spec.contract_name)),
+            spec.emulated_sender.to_string(),
+            TrackedBy::Untracked,
+        ),
+        TransactionSpecification::EmulatedContractCall(spec) => (
+            "emulated_contract_call",
+            Some(spec.contract_id.to_string()),
+            spec.emulated_sender.to_string(),
+            TrackedBy::Untracked,
+        ),
+    }
+}
+
+pub fn get_deployment_queries(plan: &DeploymentSpecification) -> DeploymentQueries {
+    let mut queries = DeploymentQueries::default();
+    for transaction in plan.plan.batches.iter().flat_map(|b| b.transactions.iter()) {
+        match describe_transaction(transaction) {
+            (_, _, _, TrackedBy::Contract(contract_id)) => {
+                queries.contracts.insert(contract_id);
+            }
+            (_, _, sender, TrackedBy::Nonce) => {
+                queries.accounts.insert(sender);
+            }
+            (_, _, _, TrackedBy::Untracked) => {}
+        }
+    }
+    queries
+}
+
+/// Works out which transactions of `plan` are confirmed, from whether the contracts it
+/// publishes exist and from the nonces of its senders. The transactions of a sender are
+/// broadcast in plan order, so the one with index `n` among them is confirmed once the
+/// sender's nonce is above `n`. Contracts and accounts missing from the maps couldn't be
+/// checked.
+pub fn get_deployment_progress(
+    plan: &DeploymentSpecification,
+    deployed_contracts: &BTreeMap<String, bool>,
+    nonces: &BTreeMap<String, u64>,
+) -> DeploymentProgress {
+    let mut sent: BTreeMap<String, u64> = BTreeMap::new();
+    let mut batches = vec![];
+    for batch in plan.plan.batches.iter() {
+        let mut transactions = vec![];
+        for transaction in batch.transactions.iter() {
+            let (transaction_type, contract_id, sender, tracked_by) =
+                describe_transaction(transaction);
+            let status = match tracked_by {
+                TrackedBy::Contract(contract_id) => match deployed_contracts.get(&contract_id) {
+                    Some(true) => DeploymentTransactionStatus::Confirmed,
+                    Some(false) => DeploymentTransactionStatus::Pending,
+                    None => DeploymentTransactionStatus::Unknown,
+                },
+                TrackedBy::Nonce => match nonces.get(&sender) {
+                    Some(nonce) if *nonce > sent.get(&sender).copied().unwrap_or_default() => {
+                        DeploymentTransactionStatus::Confirmed
+                    }
+                    Some(_) => DeploymentTransactionStatus::Pending,
+                    None => DeploymentTransactionStatus::Unknown,
+                },
+                TrackedBy::Untracked => DeploymentTransactionStatus::Unknown,
+            };
+            if !matches!(transaction, TransactionSpecification::BtcTransfer(_)) {
+                *sent.entry(sender.clone()).or_default() += 1;
+            }
+            transactions.push(DeploymentTransactionProgress {
+                transaction_type: transaction_type.to_string(),
+                contract_id,
+                sender,
+                status,
+            });
+        }
+        batches.push(DeploymentBatchProgress {
+            id: batch.id,
+            confirmed: transactions
+                .iter()
+                .all(|tx| tx.status == DeploymentTransactionStatus::Confirmed),
+            transactions,
+        });
+    }
+    let transactions = batches.iter().flat_map(|batch| batch.transactions.iter());
+    DeploymentProgress {
+        confirmed_transactions: transactions
+            .clone()
+            .filter(|tx| tx.status == DeploymentTransactionStatus::Confirmed)
+            .count(),
+        total_transactions: transactions.count(),
+        batches,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use clarinet_deployments::types::DeploymentSpecification;
+
+    use super::{
+        format_epoch_id, get_deployment_progress, get_deployment_queries, get_pox_phase,
+        DeploymentTransactionStatus, PoxPhase, StackerSetResponse, StacksV2PoxResponse,
+    };
+
+    const DEPLOYER: &str = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+    const WALLET: &str = "ST2REHHS5J3CERCRBEPMGH7921Q6PYKAADT7JP2VB";
+
+    #[test]
+    fn it_gets_pox_phases() {
+        // reward cycles of 20 blocks with a 5 block prepare phase, starting after block 100
+        for (height, expected) in [
+            (100, (PoxPhase::Reward, 0)),
+            (101, (PoxPhase::Reward, 15)),
+            (115, (PoxPhase::Reward, 1)),
+            (116, (PoxPhase::Prepare, 5)),
+            (119, (PoxPhase::Prepare, 2)),
+            (120, (PoxPhase::Prepare, 1)),
+            (121, (PoxPhase::Reward, 15)),
+        ] {
+            assert_eq!(get_pox_phase(100, 20, 5, height), expected, "{height}");
+        }
+    }
+
+    #[test]
+    fn it_formats_epoch_ids() {
+        assert_eq!(format_epoch_id("Epoch2_05"), "2.05");
+        assert_eq!(format_epoch_id("Epoch21"), "2.1");
+        assert_eq!(format_epoch_id("Epoch30"), "3.0");
+        assert_eq!(format_epoch_id("Epoch10"), "1.0");
+    }
+
+    #[test]
+    fn it_gets_chain_status_from_pox_info() {
+        let pox: StacksV2PoxResponse = serde_json::from_value(serde_json::json!({
+            "contract_id": "ST000000000000000000002AMW42H.pox-4",
+            "first_burnchain_block_height": 100,
+            "current_burnchain_block_height": 137,
+            "prepare_phase_block_length": 5,
+            "reward_cycle_length": 20,
+            "current_cycle": { "id": 1, "stacked_ustx": 500, "is_pox_active": true },
+            "epochs": [
+                { "epoch_id": "Epoch25", "start_height": 108, "end_height": 131 },
+                { "epoch_id": "Epoch30", "start_height": 131, "end_height": 9223372036854775807_u64 }
+            ]
+        }))
+        .unwrap();
+        let stacker_set: StackerSetResponse = serde_json::from_value(serde_json::json!({
+            "stacker_set": {
+                "signers": [{ "signing_key": "02a1b2", "weight": 3, "stacked_amt": 500 }]
+            }
+        }))
+        .unwrap();
+
+        let status = pox.to_chain_status(Some(stacker_set));
+        assert_eq!(status.epoch.as_deref(), Some("3.0"));
+        assert_eq!(status.pox.reward_cycle, 1);
+        assert_eq!(status.pox.phase, PoxPhase::Prepare);
+        assert_eq!(status.pox.blocks_until_next_phase, 4);
+        let signers = status.signers.unwrap();
+        assert_eq!(signers.len(), 1);
+        assert_eq!(signers[0].weight, 3);
+    }
+
+    #[test]
+    fn it_tracks_deployment_plan_progress() {
+        let plan: DeploymentSpecification = serde_yaml::from_slice(
+            &std::fs::read("src/tests/fixtures/deployment-plan.yaml").unwrap(),
+        )
+        .unwrap();
+
+        let queries = get_deployment_queries(&plan);
+        assert_eq!(queries.contracts.len(), 5);
+        assert!(queries.contracts.contains(&format!("{DEPLOYER}.nft-trait")));
+        assert!(queries.contracts.contains(&format!("{DEPLOYER}.px")));
+        assert_eq!(
+            queries.accounts.into_iter().collect::<Vec<_>>(),
+            vec![DEPLOYER.to_string(), WALLET.to_string()]
+        );
+
+        // the requirements are published, but not the px contract or the call to it
+        let mut deployed_contracts: BTreeMap<String, bool> = queries
+            .contracts
+            .iter()
+            .map(|contract_id| (contract_id.clone(), true))
+            .collect();
+        deployed_contracts.insert(format!("{DEPLOYER}.px"), false);
+        let nonces = BTreeMap::from([(DEPLOYER.to_string(), 4), (WALLET.to_string(), 1)]);
+
+        let progress = get_deployment_progress(&plan, &deployed_contracts, &nonces);
+        assert_eq!(progress.total_transactions, 7);
+        assert_eq!(progress.confirmed_transactions, 5);
+        assert_eq!(
+            progress
+                .batches
+                .iter()
+                .map(|batch| batch.confirmed)
+                .collect::<Vec<_>>(),
+            vec![true, true, false, false]
+        );
+        let statuses: Vec<(&str, DeploymentTransactionStatus)> = progress.batches[3]
+            .transactions
+            .iter()
+            .map(|tx| (tx.transaction_type.as_str(), tx.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("contract_call", DeploymentTransactionStatus::Pending),
+                ("stx_transfer", DeploymentTransactionStatus::Confirmed),
+            ]
+        );
+
+        let progress = get_deployment_progress(&plan, &BTreeMap::new(), &BTreeMap::new());
+        assert_eq!(progress.confirmed_transactions, 0);
+        assert!(progress
+            .batches
+            .iter()
+            .flat_map(|batch| batch.transactions.iter())
+            .all(|tx| tx.status == DeploymentTransactionStatus::Unknown));
+    }
+}
@@ -1,12 +1,23 @@
-use clarinet_deployments::types::{DeploymentSpecification, TransactionSpecification};
-use clarinet_files::{AccountConfig, DevnetConfig, FileLocation, NetworkManifest, ProjectManifest};
+use clarinet_deployments::{
+    load_deployment,
+    types::{DeploymentSpecification, TransactionSpecification},
+};
+use clarinet_files::{
+    AccountConfig, DevnetConfig, FileLocation, NetworkManifest, ProjectManifest, StacksNetwork,
+};
 use hiro_system_kit::slog;
+use hyper::Uri;
+use kube::CustomResource;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{collections::BTreeMap, path::PathBuf};
+use utoipa::ToSchema;
 
 use crate::{
+    operator::StacksDevnetStatus,
+    project_archive::{unpack_project_archive, ProjectArchiveFormat},
     resources::service::{get_service_port, ServicePort, StacksDevnetService},
-    Context, DevNetError,
+    Context, DevNetError, ErrorCode,
 };
 
 const PROJECT_ROOT: &str = "/etc/stacks-network/project";
@@ -22,18 +33,127 @@ pub struct ValidatedStacksDevnetConfig {
     pub deployment_plan_yaml_string: String,
     pub contract_configmap_data: Vec<(String, String)>,
     pub disable_stacks_api: bool,
+    pub webhooks: Vec<String>,
 }
-#[derive(Serialize, Deserialize, Debug, Clone)]
+
+impl ValidatedStacksDevnetConfig {
+    /// The SHA-256 of the config, recorded with a deployed devnet to tell whether a later
+    /// creation in its namespace is a retry of the same one.
+    pub fn get_hash(&self) -> Result<String, DevNetError> {
+        let config = serde_json::to_vec(self).map_err(|e| DevNetError {
+            message: format!("failed to serialize devnet config: {e}"),
+            code: 500,
+            error_code: ErrorCode::InternalError,
+        })?;
+        Ok(format!("{:x}", Sha256::digest(config)))
+    }
+}
+
+/// The config of a devnet, which is also the spec of the `StacksDevnet` custom resource
+/// reconciled in operator mode. Its schema is written by hand in
+/// `templates/crds/stacksdevnets.yaml`, since the Clarinet types have none.
+#[derive(CustomResource, Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[kube(
+    group = "devnet.hiro.so",
+    version = "v1alpha1",
+    kind = "StacksDevnet",
+    plural = "stacksdevnets",
+    namespaced,
+    status = "StacksDevnetStatus",
+    schema = "disabled"
+)]
 pub struct StacksDevnetConfig {
     pub namespace: String,
     pub disable_stacks_api: bool,
     disable_bitcoin_explorer: Option<bool>, // todo: currently unused
     disable_stacks_explorer: Option<bool>,  // todo: currently unused
+    /// A Clarinet deployment plan, as found in `deployments/default.devnet-plan.yaml`.
+    #[schema(value_type = Object)]
     deployment_plan: DeploymentSpecification,
+    /// A Clarinet network manifest, as found in `settings/Devnet.toml`.
+    #[schema(value_type = Object)]
     pub network_manifest: NetworkManifest,
+    /// A Clarinet project manifest, as found in `Clarinet.toml`.
+    #[schema(value_type = Object)]
     project_manifest: ProjectManifest,
+    /// URLs notified when this devnet becomes ready, fails or is deleted, in addition
+    /// to the webhooks configured for the API.
+    pub webhooks: Option<Vec<String>>,
+    /// The labels of the submitted `network_manifest.accounts` list, in order. The manifest
+    /// keys accounts by label, so this is what validation errors point into.
+    #[serde(skip)]
+    account_labels: Vec<String>,
 }
 impl StacksDevnetConfig {
+    /// Parses a config submitted as JSON, remembering the order of its accounts.
+    pub fn from_json(body: &[u8]) -> Result<StacksDevnetConfig, String> {
+        let submitted: serde_json::Value =
+            serde_json::from_slice(body).map_err(|e| e.to_string())?;
+        let account_labels = submitted
+            .pointer("/network_manifest/accounts")
+            .and_then(|accounts| accounts.as_array())
+            .map(|accounts| {
+                accounts
+                    .iter()
+                    .filter_map(|account| Some(account.get("label")?.as_str()?.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut config: StacksDevnetConfig =
+            serde_json::from_value(submitted).map_err(|e| e.to_string())?;
+        config.account_labels = account_labels;
+        Ok(config)
+    }
+
+    /// Builds a config from a `.tar.gz` or `.zip` of a Clarinet project, reading
+    /// `Clarinet.toml`, `settings/Devnet.toml` and `deployments/default.devnet-plan.yaml`
+    /// the same way Clarinet would.
+    pub fn from_project_archive(
+        namespace: &str,
+        archive: &[u8],
+        format: ProjectArchiveFormat,
+        disable_stacks_api: bool,
+    ) -> Result<StacksDevnetConfig, String> {
+        let unpack_dir = tempfile::tempdir()
+            .map_err(|e| format!("failed to create directory for project archive: {e}"))?;
+        let project_root = unpack_project_archive(archive, format, unpack_dir.path())?;
+
+        let manifest_location = FileLocation::from_path(project_root.join("Clarinet.toml"));
+        let project_manifest = ProjectManifest::from_location(&manifest_location)
+            .map_err(|e| format!("failed to parse Clarinet.toml: {e}"))?;
+
+        let network_manifest = NetworkManifest::from_project_manifest_location(
+            &manifest_location,
+            &StacksNetwork::Devnet.get_networks(),
+            false,
+            Some(&project_manifest.project.cache_location),
+            None,
+        )
+        .map_err(|e| format!("failed to parse settings/Devnet.toml: {e}"))?;
+
+        let deployment_plan_location =
+            FileLocation::from_path(project_root.join("deployments/default.devnet-plan.yaml"));
+        if !deployment_plan_location.exists() {
+            return Err(
+                "project archive is missing deployments/default.devnet-plan.yaml".to_string(),
+            );
+        }
+        let deployment_plan = load_deployment(&project_manifest, &deployment_plan_location)
+            .map_err(|e| format!("failed to parse deployment plan: {e}"))?;
+
+        Ok(StacksDevnetConfig {
+            namespace: namespace.to_owned(),
+            disable_stacks_api,
+            disable_bitcoin_explorer: None,
+            disable_stacks_explorer: None,
+            deployment_plan,
+            network_manifest,
+            project_manifest,
+            webhooks: None,
+            account_labels: vec![],
+        })
+    }
+
     pub fn to_validated_config(
         self,
         user_id: &str,
@@ -44,11 +164,12 @@ impl StacksDevnetConfig {
             self.namespace
         );
 
-        if user_id != self.namespace {
-            let message =
-                format!("{context}, ERROR: devnet namespace must match authenticated user id");
-            ctx.try_log(|logger| slog::warn!(logger, "{}", message));
-            return Err(DevNetError { message, code: 400 });
+        self.check_namespace(user_id, ctx)?;
+
+        let validation_errors = self.validate();
+        if !validation_errors.is_empty() {
+            let errors: Vec<String> = validation_errors.into_iter().map(|e| e.message).collect();
+            return Err(log_and_return_err(errors.join(", "), &context, ctx));
         }
 
         let project_manifest_yaml_string = self
@@ -63,9 +184,15 @@ impl StacksDevnetConfig {
             .get_deployment_plan_yaml_string()
             .map_err(|e| log_and_return_err(e, &context, ctx))?;
 
+        let contract_paths = self.get_contract_paths();
         let mut contracts: Vec<(String, String)> = vec![];
         for (contract_identifier, (src, _)) in self.deployment_plan.contracts {
-            contracts.push((contract_identifier.name.to_string(), src));
+            let name = contract_identifier.name.to_string();
+            let path = contract_paths
+                .get(&name)
+                .cloned()
+                .unwrap_or_else(|| format!("contracts/{name}.clar"));
+            contracts.push((path, src));
         }
 
         Ok(ValidatedStacksDevnetConfig {
@@ -78,6 +205,7 @@ impl StacksDevnetConfig {
             deployment_plan_yaml_string,
             contract_configmap_data: contracts,
             disable_stacks_api: self.disable_stacks_api,
+            webhooks: self.webhooks.unwrap_or_default(),
         })
     }
 
@@ -154,48 +282,253 @@ impl StacksDevnetConfig {
             .map_err(|e| format!("failed to parse project manifest: {e}"))
     }
 
-    pub fn get_deployment_plan_yaml_string(&self) -> Result<String, String> {
-        let deployment = self.deployment_plan.clone();
-        let contracts_loc = FileLocation::from_path(PathBuf::from(CONTRACT_DIR));
-        for b in deployment.plan.batches {
-            for t in b.transactions {
-                match t {
-                    TransactionSpecification::ContractPublish(mut spec) => {
-                        spec.location = contracts_loc.clone();
+    /// Maps each published contract's name to its location relative to the project root,
+    /// as referenced by the deployment plan, so nested contract paths are preserved.
+    fn get_contract_paths(&self) -> BTreeMap<String, String> {
+        let mut paths = BTreeMap::new();
+        for batch in self.deployment_plan.plan.batches.iter() {
+            for transaction in batch.transactions.iter() {
+                if let TransactionSpecification::ContractPublish(spec) = transaction {
+                    paths.insert(
+                        spec.contract_name.to_string(),
+                        get_contract_path(
+                            &spec.location.to_string(),
+                            &spec.contract_name.to_string(),
+                        ),
+                    );
+                }
+            }
+        }
+        paths
+    }
+
+    /// Checks that the devnet is created in the namespace of the authenticated user. Kept
+    /// apart from `validate` because a mismatch is a permission error, not a bad config.
+    pub fn check_namespace(&self, user_id: &str, ctx: &Context) -> Result<(), DevNetError> {
+        if user_id == self.namespace {
+            return Ok(());
+        }
+        let message = format!(
+            "failed to validate config for NAMESPACE: {}, ERROR: devnet namespace must match authenticated user id",
+            self.namespace
+        );
+        ctx.try_log(|logger: &hiro_system_kit::Logger| slog::warn!(logger, "{}", message));
+        Err(DevNetError {
+            message,
+            code: 403,
+            error_code: ErrorCode::Forbidden,
+        })
+    }
+
+    /// Runs every config check and collects all problems found, each with a JSON pointer
+    /// into the request body, rather than stopping at the first error like
+    /// `to_validated_config` does.
+    pub fn validate(&self) -> Vec<ConfigValidationError> {
+        let mut errors = vec![];
+
+        match &self.network_manifest.devnet {
+            Some(devnet_config) => errors.append(&mut validate_epochs(devnet_config)),
+            None => errors.push(ConfigValidationError::new(
+                "/network_manifest/devnet_settings",
+                "network manifest is missing required devnet config",
+            )),
+        }
+
+        for (i, account) in self.network_manifest.accounts.values().enumerate() {
+            // configs not submitted as JSON, e.g. project archives, have no list to point into
+            let i = self
+                .account_labels
+                .iter()
+                .position(|label| label == &account.label)
+                .unwrap_or(i);
+            if !is_valid_devnet_stx_address(&account.stx_address) {
+                errors.push(ConfigValidationError::new(
+                    &format!("/network_manifest/accounts/{i}/stx_address"),
+                    &format!(
+                        "account {} has invalid devnet STX address {}",
+                        account.label, account.stx_address
+                    ),
+                ));
+            }
+        }
+
+        for (i, url) in self.webhooks.iter().flatten().enumerate() {
+            if !is_valid_webhook_url(url) {
+                errors.push(ConfigValidationError::new(
+                    &format!("/webhooks/{i}"),
+                    &format!("webhook {url} must be an http or https url"),
+                ));
+            }
+        }
+
+        for (i, batch) in self.deployment_plan.plan.batches.iter().enumerate() {
+            for (j, transaction) in batch.transactions.iter().enumerate() {
+                let pointer = format!("/deployment_plan/batches/{i}/transactions/{j}");
+                match transaction {
+                    TransactionSpecification::EmulatedContractCall(_)
+                    | TransactionSpecification::EmulatedContractPublish(_) => {
+                        errors.push(ConfigValidationError::new(
+                            &format!("{pointer}/transaction_type"),
+                            "devnet deployment plans do not support emulated-contract-calls or emulated-contract-publish types",
+                        ));
                     }
-                    TransactionSpecification::RequirementPublish(mut spec) => {
-                        spec.location = contracts_loc.clone();
-                    },
-                    TransactionSpecification::EmulatedContractCall(_) | TransactionSpecification::EmulatedContractPublish(_) => {
-                        return Err("devnet deployment plans do not support emulated-contract-calls or emulated-contract-publish types".to_string())
+                    TransactionSpecification::ContractPublish(spec) => {
+                        let has_source =
+                            self.deployment_plan.contracts.iter().any(|(id, (src, _))| {
+                                id.name == spec.contract_name && !src.is_empty()
+                            });
+                        if !has_source {
+                            errors.push(ConfigValidationError::new(
+                                &format!("{pointer}/contract_name"),
+                                &format!(
+                                    "contract {} is published by the deployment plan but has no source in deployment_plan.contracts",
+                                    spec.contract_name
+                                ),
+                            ));
+                        }
                     }
-                    TransactionSpecification::ContractCall(_) => {},
-                    TransactionSpecification::BtcTransfer(_) => {},
-                    TransactionSpecification::StxTransfer(_) => {},
+                    TransactionSpecification::RequirementPublish(spec) => {
+                        if spec.source.is_empty() {
+                            errors.push(ConfigValidationError::new(
+                                &format!("{pointer}/source"),
+                                &format!("requirement {} has no source", spec.contract_id),
+                            ));
+                        }
+                    }
+                    TransactionSpecification::ContractCall(_)
+                    | TransactionSpecification::BtcTransfer(_)
+                    | TransactionSpecification::StxTransfer(_) => {}
                 }
             }
         }
-        serde_yaml::to_string(&self.deployment_plan)
+
+        errors
+    }
+
+    /// Serializes the deployment plan with each published contract located where its
+    /// source is mounted, relative to the project root.
+    pub fn get_deployment_plan_yaml_string(&self) -> Result<String, String> {
+        let mut deployment = self.deployment_plan.clone();
+        for batch in deployment.plan.batches.iter_mut() {
+            for transaction in batch.transactions.iter_mut() {
+                if let TransactionSpecification::ContractPublish(spec) = transaction {
+                    let path = get_contract_path(
+                        &spec.location.to_string(),
+                        &spec.contract_name.to_string(),
+                    );
+                    spec.location = FileLocation::from_path(PathBuf::from(path));
+                }
+            }
+        }
+        serde_yaml::to_string(&deployment)
             .map_err(|e| format!("failed to parse deployment plan config: {e}"))
     }
 }
 
+/// Where a published contract's source is mounted, relative to the project root: its
+/// location in the plan when that's within the project, or `contracts/{name}.clar`.
+fn get_contract_path(location: &str, contract_name: &str) -> String {
+    get_project_relative_path(location).unwrap_or_else(|| format!("contracts/{contract_name}.clar"))
+}
+
+/// Returns a contract location relative to the project root. Absolute locations from the
+/// client's machine are resolved from their last `contracts` directory.
+fn get_project_relative_path(location: &str) -> Option<String> {
+    let location = location.trim_start_matches("./");
+    let path = if location.starts_with('/') {
+        let (_, path) = location.rsplit_once("/contracts/")?;
+        format!("contracts/{path}")
+    } else {
+        location.to_string()
+    };
+    match path.split('/').any(|part| part == "..") {
+        true => None,
+        false => Some(path),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ConfigValidationError {
+    /// A JSON pointer (RFC 6901) to the offending field of the submitted config.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl ConfigValidationError {
+    pub(crate) fn new(pointer: &str, message: &str) -> ConfigValidationError {
+        ConfigValidationError {
+            pointer: pointer.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+}
+
+pub(crate) fn is_valid_webhook_url(url: &str) -> bool {
+    url.parse::<Uri>().is_ok_and(|uri| {
+        matches!(uri.scheme_str(), Some("http") | Some("https")) && uri.host().is_some()
+    })
+}
+
+fn validate_epochs(devnet_config: &DevnetConfig) -> Vec<ConfigValidationError> {
+    let epochs = [
+        ("epoch_2_0", devnet_config.epoch_2_0),
+        ("epoch_2_05", devnet_config.epoch_2_05),
+        ("epoch_2_1", devnet_config.epoch_2_1),
+        ("epoch_2_2", devnet_config.epoch_2_2),
+        ("epoch_2_3", devnet_config.epoch_2_3),
+        ("epoch_2_4", devnet_config.epoch_2_4),
+        ("epoch_2_5", devnet_config.epoch_2_5),
+        ("epoch_3_0", devnet_config.epoch_3_0),
+        ("epoch_3_1", devnet_config.epoch_3_1),
+    ];
+    epochs
+        .windows(2)
+        .filter(|pair| pair[1].1 < pair[0].1)
+        .map(|pair| {
+            let ((prev_name, prev_height), (name, height)) = (pair[0], pair[1]);
+            ConfigValidationError::new(
+                &format!("/network_manifest/devnet_settings/{name}"),
+                &format!("{name} ({height}) must not start before {prev_name} ({prev_height})"),
+            )
+        })
+        .collect()
+}
+
+const C32_ALPHABET: &str = "0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Devnets use testnet addresses, so only single-sig (`ST`) and multi-sig (`SN`)
+/// testnet versions are accepted.
+fn is_valid_devnet_stx_address(address: &str) -> bool {
+    let Some(data) = address
+        .strip_prefix("ST")
+        .or_else(|| address.strip_prefix("SN"))
+    else {
+        return false;
+    };
+    (38..=40).contains(&data.len()) && data.chars().all(|c| C32_ALPHABET.contains(c))
+}
+
 fn log_and_return_err(e: String, context: &str, ctx: &Context) -> DevNetError {
     let message = format!("{context}, ERROR: {e}");
     ctx.try_log(|logger: &hiro_system_kit::Logger| slog::warn!(logger, "{}", message));
-    DevNetError { message, code: 400 }
+    DevNetError {
+        message,
+        code: 400,
+        error_code: ErrorCode::InvalidConfig,
+    }
 }
 #[cfg(test)]
 mod tests {
     use std::{
         fs::File,
         io::{BufReader, Read},
+        path::PathBuf,
         str::from_utf8,
     };
 
     use crate::Context;
 
-    use super::StacksDevnetConfig;
+    use super::{FileLocation, StacksDevnetConfig, TransactionSpecification};
 
     fn read_file(file_path: &str) -> Vec<u8> {
         let file = File::open(file_path)
@@ -263,6 +596,43 @@ mod tests {
         );
     }
 
+    #[test_case::test_case("contracts/px.clar" => Some("contracts/px.clar".to_string()); "for relative paths")]
+    #[test_case::test_case("./contracts/defi/pool.clar" => Some("contracts/defi/pool.clar".to_string()); "for dot-relative nested paths")]
+    #[test_case::test_case("/Users/me/stx-px/contracts/defi/pool.clar" => Some("contracts/defi/pool.clar".to_string()); "for absolute paths")]
+    #[test_case::test_case("/Users/me/stx-px/src/pool.clar" => None; "for absolute paths outside contracts")]
+    #[test_case::test_case("contracts/../../etc/passwd" => None; "for paths escaping the project")]
+    fn it_gets_project_relative_contract_path(location: &str) -> Option<String> {
+        super::get_project_relative_path(location)
+    }
+
+    #[test]
+    fn it_locates_contracts_where_their_source_is_mounted() {
+        let mut template = get_template_config("src/tests/fixtures/stacks-devnet-config.json");
+        for batch in template.deployment_plan.plan.batches.iter_mut() {
+            for transaction in batch.transactions.iter_mut() {
+                if let TransactionSpecification::ContractPublish(spec) = transaction {
+                    spec.location = FileLocation::from_path(PathBuf::from(
+                        "/Users/me/stx-px/contracts/defi/px.clar",
+                    ));
+                }
+            }
+        }
+        let user_id = template.namespace.clone();
+        let validated_config = template
+            .to_validated_config(&user_id, &Context::empty())
+            .unwrap();
+        assert!(validated_config
+            .deployment_plan_yaml_string
+            .contains("path: contracts/defi/px.clar"));
+        assert!(!validated_config
+            .deployment_plan_yaml_string
+            .contains("/Users/me"));
+        assert!(validated_config
+            .contract_configmap_data
+            .iter()
+            .any(|(path, _)| path == "contracts/defi/px.clar"));
+    }
+
     #[test]
     #[should_panic]
     fn it_requires_devnet_config() {
@@ -272,6 +642,7 @@ mod tests {
         let ctx = Context {
             logger: None,
             tracer: false,
+            request_id: None,
         };
         template.network_manifest.devnet = None;
         let user_id = template.clone().namespace;
@@ -280,6 +651,87 @@ mod tests {
             .unwrap_or_else(|e| panic!("config validation test failed: {}", e.message));
     }
 
+    #[test]
+    fn it_points_to_submitted_accounts() {
+        let config = read_file("src/tests/fixtures/stacks-devnet-config.json");
+        let mut config: serde_json::Value = serde_json::from_slice(&config).unwrap();
+        // the deployer sorts first by label, but is submitted last
+        let accounts = config["network_manifest"]["accounts"]
+            .as_array_mut()
+            .unwrap();
+        let mut deployer = accounts.remove(0);
+        deployer["stx_address"] = "not-an-address".into();
+        accounts.push(deployer);
+        let last = accounts.len() - 1;
+
+        let template =
+            StacksDevnetConfig::from_json(&serde_json::to_vec(&config).unwrap()).unwrap();
+        let pointers: Vec<String> = template.validate().into_iter().map(|e| e.pointer).collect();
+        assert_eq!(
+            pointers,
+            vec![format!("/network_manifest/accounts/{last}/stx_address")]
+        );
+    }
+
+    #[test]
+    fn it_collects_all_validation_errors() {
+        let mut template = get_template_config("src/tests/fixtures/stacks-devnet-config.json");
+        if let Some(account) = template.network_manifest.accounts.values_mut().next() {
+            account.stx_address = "not-an-address".to_string();
+        }
+        if let Some(devnet) = template.network_manifest.devnet.as_mut() {
+            devnet.epoch_3_0 = devnet.epoch_2_5 - 1;
+        }
+        template.webhooks = Some(vec![
+            "https://hooks.example.com/devnets".to_string(),
+            "ftp://hooks.example.com".to_string(),
+        ]);
+        let pointers: Vec<String> = template.validate().into_iter().map(|e| e.pointer).collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/network_manifest/devnet_settings/epoch_3_0",
+                "/network_manifest/accounts/0/stx_address",
+                "/webhooks/1"
+            ]
+        );
+
+        template.network_manifest.devnet = None;
+        assert!(template
+            .validate()
+            .iter()
+            .any(|e| e.pointer == "/network_manifest/devnet_settings"));
+    }
+
+    #[test]
+    fn it_validates_template_config() {
+        let template = get_template_config("src/tests/fixtures/stacks-devnet-config.json");
+        assert_eq!(template.validate(), vec![]);
+    }
+
+    #[test]
+    fn it_hashes_validated_configs() {
+        let template = get_template_config("src/tests/fixtures/stacks-devnet-config.json");
+        let user_id = template.namespace.clone();
+        let ctx = Context::empty();
+        let get_hash = |config: &StacksDevnetConfig| {
+            config
+                .clone()
+                .to_validated_config(&user_id, &ctx)
+                .unwrap()
+                .get_hash()
+                .unwrap()
+        };
+
+        let hash = get_hash(&template);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, get_hash(&template));
+
+        let mut changed = template.clone();
+        changed.disable_stacks_api = true;
+        assert_ne!(hash, get_hash(&changed));
+    }
+
     #[test]
     fn it_rejects_config_with_namespace_user_id_mismatch() {
         let template = get_template_config("src/tests/fixtures/stacks-devnet-config.json");
@@ -290,7 +742,8 @@ mod tests {
                 panic!("config validation with non-matching user_id should have been rejected")
             }
             Err(e) => {
-                assert_eq!(e.code, 400);
+                assert_eq!(e.code, 403);
+                assert_eq!(e.error_code, ErrorCode::Forbidden);
                 assert_eq!(e.message, format!("failed to validate config for NAMESPACE: {namespace}, ERROR: devnet namespace must match authenticated user id"));
             }
         }
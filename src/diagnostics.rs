@@ -0,0 +1,411 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::{ContainerState, ContainerStatus, Event, Pod};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The number of events reported for each component, newest first.
+const MAX_EVENTS_PER_COMPONENT: usize = 10;
+/// Reasons a container waits that won't resolve without changing the devnet.
+const FAILED_WAITING_REASONS: &[&str] = &[
+    "ImagePullBackOff",
+    "InvalidImageName",
+    "CreateContainerConfigError",
+];
+/// Restarts after which a container in `CrashLoopBackOff` is considered failed, since
+/// components may crash while the nodes they depend on start.
+const MIN_CRASH_LOOP_RESTARTS: i32 = 3;
+
+/// The status of one of the pods making up a devnet.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, ToSchema)]
+pub struct DevnetComponentStatus {
+    /// The phase of the pod, e.g. `Pending`, `Running` or `Failed`. Unset when the pod
+    /// hasn't been created yet.
+    pub phase: Option<String>,
+    /// Whether the pod is ready to serve requests.
+    pub ready: bool,
+    pub started_at: Option<String>,
+    /// The total restarts of the pod's containers.
+    pub restarts: i32,
+    /// The state of each of the pod's containers, e.g. both the stacks API and its
+    /// Postgres database for `stacks-blockchain-api`.
+    pub containers: Vec<DevnetContainerStatus>,
+    /// The most recent Kubernetes events about the component, newest first.
+    pub events: Vec<DevnetEvent>,
+}
+
+impl DevnetComponentStatus {
+    /// Gets why the component failed, when its pod failed or one of its containers can't
+    /// start, e.g. while it is stuck in `CrashLoopBackOff`.
+    pub fn get_failure(&self) -> Option<String> {
+        if self.phase.as_deref() == Some("Failed") {
+            return Some("failed".into());
+        }
+        self.containers.iter().find_map(|container| {
+            let reason = container.reason.as_deref()?;
+            let failed = container.state.as_deref() == Some("waiting")
+                && (FAILED_WAITING_REASONS.contains(&reason)
+                    || (reason == "CrashLoopBackOff"
+                        && container.restart_count >= MIN_CRASH_LOOP_RESTARTS));
+            failed.then(|| format!("container {} is in {reason}", container.name))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, ToSchema)]
+pub struct DevnetContainerStatus {
+    pub name: String,
+    pub image: String,
+    pub ready: bool,
+    /// `running`, `waiting` or `terminated`.
+    pub state: Option<String>,
+    /// Why the container is waiting or terminated, e.g. `ImagePullBackOff`,
+    /// `CrashLoopBackOff` or `OOMKilled`.
+    pub reason: Option<String>,
+    pub message: Option<String>,
+    pub exit_code: Option<i32>,
+    pub restart_count: i32,
+    /// Why the previous run of the container terminated, if it restarted.
+    pub last_termination_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, ToSchema)]
+pub struct DevnetEvent {
+    /// `Normal` or `Warning`.
+    pub event_type: Option<String>,
+    pub reason: Option<String>,
+    pub message: Option<String>,
+    /// The kind and name of the object the event is about, e.g. `Pod/stacks-blockchain-0`.
+    pub object: String,
+    /// How many times the event occurred.
+    pub count: Option<i32>,
+    pub last_seen: Option<String>,
+}
+
+fn get_state_details(state: &ContainerState) -> (&'static str, Option<String>, Option<String>) {
+    if let Some(waiting) = &state.waiting {
+        return ("waiting", waiting.reason.clone(), waiting.message.clone());
+    }
+    if let Some(terminated) = &state.terminated {
+        return (
+            "terminated",
+            terminated.reason.clone(),
+            terminated.message.clone(),
+        );
+    }
+    ("running", None, None)
+}
+
+fn get_container_status(status: &ContainerStatus) -> DevnetContainerStatus {
+    let (state, reason, message) = match &status.state {
+        Some(state) => {
+            let (state, reason, message) = get_state_details(state);
+            (Some(state.to_string()), reason, message)
+        }
+        None => (None, None, None),
+    };
+    DevnetContainerStatus {
+        name: status.name.clone(),
+        image: status.image.clone(),
+        ready: status.ready,
+        state,
+        reason,
+        message,
+        exit_code: status
+            .state
+            .as_ref()
+            .and_then(|state| state.terminated.as_ref())
+            .map(|terminated| terminated.exit_code),
+        restart_count: status.restart_count,
+        last_termination_reason: status
+            .last_state
+            .as_ref()
+            .and_then(|state| state.terminated.as_ref())
+            .and_then(|terminated| terminated.reason.clone()),
+    }
+}
+
+/// Gets the state of the init containers and containers of `pod`.
+pub fn get_container_statuses(pod: &Pod) -> Vec<DevnetContainerStatus> {
+    let Some(status) = &pod.status else {
+        return vec![];
+    };
+    status
+        .init_container_statuses
+        .iter()
+        .flatten()
+        .chain(status.container_statuses.iter().flatten())
+        .map(get_container_status)
+        .collect()
+}
+
+/// Gets the status of a component from its pod, without its events.
+pub fn get_component_status(pod: &Pod) -> DevnetComponentStatus {
+    let containers = get_container_statuses(pod);
+    let Some(status) = &pod.status else {
+        return DevnetComponentStatus {
+            containers,
+            ..Default::default()
+        };
+    };
+    let ready = status
+        .conditions
+        .iter()
+        .flatten()
+        .any(|condition| condition.type_ == "Ready" && condition.status == "True");
+    DevnetComponentStatus {
+        phase: status.phase.clone(),
+        ready,
+        started_at: status.start_time.as_ref().map(|t| t.0.to_string()),
+        restarts: containers.iter().map(|c| c.restart_count).sum(),
+        containers,
+        events: vec![],
+    }
+}
+
+/// Gets the component an object belongs to from its name. Pods, replica sets and
+/// volumes are named after their component with a suffix, so the longest matching
+/// component is used, e.g. `stacks-blockchain-api` rather than `stacks-blockchain`.
+fn get_object_component<'a>(name: &str, components: &[&'a str]) -> Option<&'a str> {
+    components
+        .iter()
+        .filter(|component| {
+            name == **component
+                || name
+                    .strip_prefix(**component)
+                    .is_some_and(|suffix| suffix.starts_with('-'))
+        })
+        .max_by_key(|component| component.len())
+        .copied()
+}
+
+/// Groups the namespace `events` by the component they are about, keeping the most recent
+/// ones. Events about other objects, such as the namespace itself, are dropped.
+pub fn group_events_by_component(
+    mut events: Vec<Event>,
+    components: &[&str],
+) -> BTreeMap<String, Vec<DevnetEvent>> {
+    let last_seen = |event: &Event| {
+        event
+            .last_timestamp
+            .as_ref()
+            .map(|t| t.0)
+            .or(event.event_time.as_ref().map(|t| t.0))
+            .or(event.first_timestamp.as_ref().map(|t| t.0))
+    };
+    events.sort_by_key(|event| std::cmp::Reverse(last_seen(event)));
+
+    let mut grouped: BTreeMap<String, Vec<DevnetEvent>> = BTreeMap::new();
+    for event in events {
+        let object = &event.involved_object;
+        let Some(name) = &object.name else {
+            continue;
+        };
+        let Some(component) = get_object_component(name, components) else {
+            continue;
+        };
+        let component_events = grouped.entry(component.to_string()).or_default();
+        if component_events.len() == MAX_EVENTS_PER_COMPONENT {
+            continue;
+        }
+        component_events.push(DevnetEvent {
+            event_type: event.type_.clone(),
+            reason: event.reason.clone(),
+            message: event.message.clone(),
+            object: format!("{}/{}", object.kind.as_deref().unwrap_or_default(), name),
+            count: event.count,
+            last_seen: last_seen(&event).map(|t| t.to_string()),
+        });
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::api::core::v1::{Event, Pod};
+
+    use super::{
+        get_component_status, get_container_statuses, get_object_component,
+        group_events_by_component,
+    };
+
+    const COMPONENTS: [&str; 3] = [
+        "bitcoind-chain-coordinator",
+        "stacks-blockchain",
+        "stacks-blockchain-api",
+    ];
+
+    #[test]
+    fn it_gets_container_failure_reasons() {
+        let pod: Pod = serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "stacks-blockchain" },
+            "status": {
+                "phase": "Running",
+                "containerStatuses": [
+                    {
+                        "name": "stacks-blockchain",
+                        "image": "blockstack/stacks-blockchain:latest",
+                        "imageID": "",
+                        "ready": false,
+                        "restartCount": 4,
+                        "state": {
+                            "waiting": {
+                                "reason": "CrashLoopBackOff",
+                                "message": "back-off 1m20s restarting failed container"
+                            }
+                        },
+                        "lastState": {
+                            "terminated": { "exitCode": 137, "reason": "OOMKilled" }
+                        }
+                    },
+                    {
+                        "name": "sidecar",
+                        "image": "busybox",
+                        "imageID": "",
+                        "ready": true,
+                        "restartCount": 0,
+                        "state": { "running": { "startedAt": "2023-07-11T00:30:07Z" } }
+                    }
+                ]
+            }
+        }))
+        .unwrap();
+        let statuses = get_container_statuses(&pod);
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].state.as_deref(), Some("waiting"));
+        assert_eq!(statuses[0].reason.as_deref(), Some("CrashLoopBackOff"));
+        assert_eq!(statuses[0].restart_count, 4);
+        assert_eq!(
+            statuses[0].last_termination_reason.as_deref(),
+            Some("OOMKilled")
+        );
+        assert_eq!(statuses[1].state.as_deref(), Some("running"));
+        assert_eq!(statuses[1].reason, None);
+        assert_eq!(
+            get_component_status(&pod).get_failure().as_deref(),
+            Some("container stacks-blockchain is in CrashLoopBackOff")
+        );
+    }
+
+    #[test]
+    fn it_gets_component_status() {
+        let pod: Pod = serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "stacks-blockchain-api-0" },
+            "status": {
+                "phase": "Running",
+                "startTime": "2023-07-11T00:30:11Z",
+                "conditions": [
+                    { "type": "PodScheduled", "status": "True" },
+                    { "type": "Ready", "status": "False" }
+                ],
+                "containerStatuses": [
+                    {
+                        "name": "stacks-blockchain-api",
+                        "image": "hirosystems/stacks-blockchain-api:master",
+                        "imageID": "",
+                        "ready": false,
+                        "restartCount": 2,
+                        "state": { "waiting": { "reason": "CrashLoopBackOff" } }
+                    },
+                    {
+                        "name": "postgres",
+                        "image": "postgres:15",
+                        "imageID": "",
+                        "ready": true,
+                        "restartCount": 1,
+                        "state": { "running": {} }
+                    }
+                ]
+            }
+        }))
+        .unwrap();
+        let status = get_component_status(&pod);
+        assert_eq!(status.phase.as_deref(), Some("Running"));
+        assert!(!status.ready);
+        assert_eq!(
+            status.started_at.as_deref(),
+            Some("2023-07-11 00:30:11 UTC")
+        );
+        assert_eq!(status.restarts, 3);
+        let images: Vec<_> = status.containers.iter().map(|c| c.image.as_str()).collect();
+        assert_eq!(
+            images,
+            vec!["hirosystems/stacks-blockchain-api:master", "postgres:15"]
+        );
+        assert!(status.containers[1].ready);
+        // crashing a couple of times doesn't fail the component yet
+        assert_eq!(status.get_failure(), None);
+    }
+
+    #[test]
+    fn it_gets_object_components() {
+        for (name, component) in [
+            ("stacks-blockchain", Some("stacks-blockchain")),
+            ("stacks-blockchain-0", Some("stacks-blockchain")),
+            (
+                "stacks-blockchain-api-7d9f8b6c4-x2x7q",
+                Some("stacks-blockchain-api"),
+            ),
+            (
+                "bitcoind-chain-coordinator-5c8d7",
+                Some("bitcoind-chain-coordinator"),
+            ),
+            ("stacks-blockchainx", None),
+            ("platform-some-user", None),
+        ] {
+            assert_eq!(get_object_component(name, &COMPONENTS), component, "{name}");
+        }
+    }
+
+    #[test]
+    fn it_groups_recent_events_by_component() {
+        let event = |name: &str, reason: &str, last_timestamp: &str| -> Event {
+            serde_json::from_value(serde_json::json!({
+                "metadata": { "name": format!("{name}.{reason}") },
+                "involvedObject": { "kind": "Pod", "name": name },
+                "type": "Warning",
+                "reason": reason,
+                "message": format!("{reason} for {name}"),
+                "count": 1,
+                "lastTimestamp": last_timestamp
+            }))
+            .unwrap()
+        };
+        let mut events = vec![
+            event("stacks-blockchain-0", "Scheduled", "2023-07-11T00:30:00Z"),
+            event("stacks-blockchain-0", "BackOff", "2023-07-11T00:35:00Z"),
+            event(
+                "stacks-blockchain-api-7d9f8b6c4-x2x7q",
+                "Failed",
+                "2023-07-11T00:31:00Z",
+            ),
+            event("platform-some-user", "Created", "2023-07-11T00:29:00Z"),
+        ];
+        for i in 0..12 {
+            events.push(event(
+                "bitcoind-chain-coordinator-5c8d7",
+                "Pulling",
+                &format!("2023-07-11T00:{:02}:00Z", 10 + i),
+            ));
+        }
+
+        let grouped = group_events_by_component(events, &COMPONENTS);
+        assert_eq!(grouped.len(), 3);
+        let reasons: Vec<_> = grouped["stacks-blockchain"]
+            .iter()
+            .map(|e| e.reason.as_deref().unwrap())
+            .collect();
+        assert_eq!(reasons, vec!["BackOff", "Scheduled"]);
+        assert_eq!(
+            grouped["stacks-blockchain-api"][0].object,
+            "Pod/stacks-blockchain-api-7d9f8b6c4-x2x7q"
+        );
+        let bitcoind_events = &grouped["bitcoind-chain-coordinator"];
+        assert_eq!(bitcoind_events.len(), 10);
+        assert!(bitcoind_events[0]
+            .last_seen
+            .as_ref()
+            .unwrap()
+            .starts_with("2023-07-11 00:21:00"));
+    }
+}
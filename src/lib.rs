@@ -1,27 +1,49 @@
-use std::{collections::BTreeMap, str::FromStr, time::Duration};
-use std::{env, thread::sleep};
+use std::env;
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
 
-use clarinet_deployments::types::BurnchainEpochConfig;
+use chain_status::{
+    get_deployment_progress, get_deployment_queries, DeploymentProgress, DevnetChainStatus,
+    StackerSetResponse, StacksV2PoxResponse,
+};
+use clarinet_deployments::types::{BurnchainEpochConfig, DeploymentSpecification};
 use clarinet_files::{compute_addresses, StacksNetwork};
-use futures::future::try_join3;
+use diagnostics::{get_component_status, group_events_by_component, DevnetComponentStatus};
+use futures::{
+    future::{join, try_join3, try_join_all},
+    stream, StreamExt,
+};
 use hiro_system_kit::{slog, Logger};
 use hyper::{
     body::{Bytes, HttpBody},
+    client::HttpConnector,
     Body, Client as HttpClient, Request, Response, Uri,
 };
 use k8s_openapi::{
     api::{
         apps::v1::{Deployment, StatefulSet},
-        core::v1::{ConfigMap, Namespace, PersistentVolumeClaim, Pod, Service},
+        core::v1::{
+            ConfigMap, ConfigMapProjection, Event, KeyToPath, Namespace, PersistentVolumeClaim,
+            Pod, Service, VolumeProjection,
+        },
     },
+    apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference},
     NamespaceResourceScope,
 };
 use kube::{
-    api::{Api, DeleteParams, ListParams, PostParams},
+    api::{Api, DeleteParams, ListParams, Patch, PatchParams},
+    client::ClientBuilder,
     config::KubeConfigOptions,
-    Client, Config,
+    Client, Config, ResourceExt,
 };
+use metrics::{metrics, KubeMetricsService};
+use opentelemetry::KeyValue;
 use resources::{
+    configmap::{get_project_dir_configmap_name, shard_configmap_data, MAX_CONFIGMAP_DATA_BYTES},
     deployment::StacksDevnetDeployment,
     pvc::StacksDevnetPvc,
     service::{get_service_port, ServicePort},
@@ -30,7 +52,9 @@ use resources::{
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use strum::IntoEnumIterator;
-use tower::BoxError;
+use telemetry::with_span;
+use tower::{layer::layer_fn, BoxError};
+use utoipa::ToSchema;
 
 pub mod config;
 use config::ValidatedStacksDevnetConfig;
@@ -39,9 +63,21 @@ mod template_parser;
 use template_parser::get_yaml_from_resource;
 
 pub mod api_config;
+pub mod audit;
+pub mod auth;
+pub mod chain_status;
+pub mod diagnostics;
+pub mod metrics;
+pub mod openapi;
+pub mod operator;
+pub mod project_archive;
+pub mod quota;
 pub mod resources;
 pub mod responder;
 pub mod routes;
+pub mod telemetry;
+pub mod tls;
+pub mod webhooks;
 use crate::resources::configmap::StacksDevnetConfigmap;
 use crate::resources::pod::StacksDevnetPod;
 use crate::resources::service::{get_service_url, StacksDevnetService};
@@ -49,16 +85,86 @@ use crate::resources::service::{get_service_url, StacksDevnetService};
 const COMPONENT_SELECTOR: &str = "app.kubernetes.io/component";
 const USER_SELECTOR: &str = "app.kubernetes.io/instance";
 const NAME_SELECTOR: &str = "app.kubernetes.io/name";
+const MANAGED_BY_SELECTOR: &str = "app.kubernetes.io/managed-by";
+const MANAGED_BY: &str = "stacks-devnet-api";
+/// The field manager resources are server-side applied with.
+const FIELD_MANAGER: &str = "stacks-devnet-api";
+/// Holds the webhooks of a devnet's config, so they can be notified when it's deleted.
+const WEBHOOKS_CONFIGMAP: &str = "webhooks";
+/// Holds the hash of the config a devnet was deployed from, so that retried creations
+/// can be told apart from conflicting ones.
+const CONFIG_HASH_CONFIGMAP: &str = "config-hash";
+/// The most requests made to stacks nodes at once, when checking a devnet's deployment
+/// progress or the chain tips of a page of devnets.
+const MAX_CONCURRENT_NODE_REQUESTS: usize = 8;
+
+/// Shared by every request to the devnets' stacks nodes, so their connections are reused.
+static STACKS_NODE_CLIENT: LazyLock<HttpClient<HttpConnector>> = LazyLock::new(HttpClient::new);
+
 #[derive(Clone, Debug)]
 pub struct DevNetError {
     pub message: String,
+    /// The HTTP status code of the error.
     pub code: u16,
+    pub error_code: ErrorCode,
+}
+
+impl std::fmt::Display for DevNetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for DevNetError {}
+
+/// A stable, machine-readable identifier for an error, returned in the body of error responses.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, ToSchema, strum::IntoStaticStr)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    MethodNotAllowed,
+    PayloadTooLarge,
+    TooManyRequests,
+    InternalError,
+    NotImplemented,
+    InvalidConfig,
+    DevnetAlreadyExists,
+    DevnetNotFound,
+    DevnetNotReady,
+    NamespaceNotFound,
+    QuotaExceeded,
+    KubernetesError,
+    UpstreamError,
+    ServiceUnavailable,
+}
+
+impl ErrorCode {
+    /// The generic code for an HTTP status, for errors without a more specific code.
+    pub fn from_status(code: u16) -> ErrorCode {
+        match code {
+            401 => ErrorCode::Unauthorized,
+            403 => ErrorCode::Forbidden,
+            404 => ErrorCode::NotFound,
+            405 => ErrorCode::MethodNotAllowed,
+            413 => ErrorCode::PayloadTooLarge,
+            429 => ErrorCode::TooManyRequests,
+            501 => ErrorCode::NotImplemented,
+            503 => ErrorCode::ServiceUnavailable,
+            400..=499 => ErrorCode::BadRequest,
+            _ => ErrorCode::InternalError,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Context {
     pub logger: Option<Logger>,
     pub tracer: bool,
+    pub request_id: Option<String>,
 }
 
 impl Context {
@@ -66,6 +172,19 @@ impl Context {
         Context {
             logger: None,
             tracer: false,
+            request_id: None,
+        }
+    }
+
+    /// Creates a context for handling a single request, whose log records include `request_id`.
+    pub fn with_request_id(&self, request_id: &str) -> Context {
+        Context {
+            logger: self
+                .logger
+                .as_ref()
+                .map(|logger| logger.new(slog::o!("request_id" => request_id.to_string()))),
+            tracer: self.tracer,
+            request_id: Some(request_id.to_string()),
         }
     }
 
@@ -83,40 +202,111 @@ impl Context {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The optional parts of a devnet's status, which each take more requests to its stacks node.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DevnetInfoIncludes {
+    /// The stacks and bitcoin chain tips, which the other parts also fetch.
+    pub tips: bool,
+    /// The epoch, PoX state and signers of the chain.
+    pub chain: bool,
+    /// Which transactions of the submitted deployment plan are confirmed.
+    pub deployment: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct StacksDevnetInfoResponse {
-    pub bitcoind_node_status: Option<String>,
-    pub stacks_node_status: Option<String>,
-    pub stacks_api_status: Option<String>,
-    pub bitcoind_node_started_at: Option<String>,
-    pub stacks_node_started_at: Option<String>,
-    pub stacks_api_started_at: Option<String>,
+    /// The status of each pod of the devnet, keyed by component, e.g. `stacks-blockchain`
+    /// or `stacks-signer-0`.
+    pub components: BTreeMap<String, DevnetComponentStatus>,
+    /// Only included when requested, and 0 while the stacks node can't be reached.
+    pub stacks_chain_tip: Option<u64>,
+    /// Only included when requested, and 0 while the stacks node can't be reached.
+    pub bitcoin_chain_tip: Option<u64>,
+    /// The epoch, PoX state and signers of the chain. Only included when requested, and
+    /// unset while the stacks node can't be reached.
+    pub chain: Option<DevnetChainStatus>,
+    /// Which batches and transactions of the submitted deployment plan are confirmed. Only
+    /// included when requested, and unset while the stacks node can't be reached.
+    pub deployment: Option<DeploymentProgress>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
+pub struct StacksDevnetSummary {
+    pub namespace: String,
+    pub owner: Option<String>,
+    pub created_at: Option<String>,
+    /// Phase of each devnet pod, keyed by component.
+    pub pod_phases: BTreeMap<String, String>,
     pub stacks_chain_tip: u64,
     pub bitcoin_chain_tip: u64,
+    /// Unix time of the owner's last request, as tracked by the request store.
+    pub last_request_time: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
-struct PodStatusResponse {
-    status: Option<String>,
-    start_time: Option<String>,
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DevnetListFilter {
+    pub owner: Option<String>,
+    pub namespace_prefix: Option<String>,
+    /// Only include devnets with at least one pod in this phase.
+    pub phase: Option<String>,
+}
+
+impl DevnetListFilter {
+    fn matches(&self, summary: &StacksDevnetSummary) -> bool {
+        let owner_matches = self
+            .owner
+            .as_ref()
+            .map_or(true, |owner| summary.owner.as_ref() == Some(owner));
+        let namespace_matches = self
+            .namespace_prefix
+            .as_ref()
+            .map_or(true, |prefix| summary.namespace.starts_with(prefix));
+        let phase_matches = self.phase.as_ref().map_or(true, |phase| {
+            summary
+                .pod_phases
+                .values()
+                .any(|p| p.eq_ignore_ascii_case(phase))
+        });
+        owner_matches && namespace_matches && phase_matches
+    }
 }
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 struct StacksV2InfoResponse {
     burn_block_height: u64,
     stacks_tip_height: u64,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct StacksV2AccountResponse {
+    nonce: u64,
+}
+
 #[derive(Clone)]
 pub struct StacksDevnetApiK8sManager {
     client: Client,
     ctx: Context,
+    /// Whether devnets are created and deleted through `StacksDevnet` resources, which
+    /// the operator deploys.
+    operator: bool,
+    /// Set on every resource deployed, so that they're deleted along with the
+    /// `StacksDevnet` they were deployed for.
+    owner_reference: Option<OwnerReference>,
 }
 
 impl StacksDevnetApiK8sManager {
     pub async fn new(ctx: &Context) -> StacksDevnetApiK8sManager {
-        let context = match env::var("KUBE_CONTEXT") {
-            Ok(context) => Some(context),
-            Err(_) => {
+        StacksDevnetApiK8sManager::with_kube_context(ctx, env::var("KUBE_CONTEXT").ok()).await
+    }
+
+    /// Creates a manager using the given kubeconfig context, or the default context if `None`.
+    pub async fn with_kube_context(
+        ctx: &Context,
+        kube_context: Option<String>,
+    ) -> StacksDevnetApiK8sManager {
+        let context = match kube_context {
+            Some(context) => Some(context),
+            None => {
                 if cfg!(test) {
                     let is_ci = match env::var("GITHUB_ACTIONS") {
                         Ok(is_ci) => is_ci == *"true",
@@ -134,27 +324,39 @@ impl StacksDevnetApiK8sManager {
                 }
             }
         };
-        let client = match context {
+        let client_config = match context {
             Some(context) => {
                 let kube_config = KubeConfigOptions {
                     context: Some(context.clone()),
                     cluster: Some(context),
                     user: None,
                 };
-                let client_config = Config::from_kubeconfig(&kube_config)
+                Config::from_kubeconfig(&kube_config)
                     .await
-                    .unwrap_or_else(|e| panic!("could not create kube client config: {e}"));
-                Client::try_from(client_config)
-                    .unwrap_or_else(|e| panic!("could not create kube client: {e}"))
+                    .map_err(|e| e.to_string())
             }
-            None => Client::try_default()
-                .await
-                .expect("could not create kube client"),
-        };
+            None => Config::infer().await.map_err(|e| e.to_string()),
+        }
+        .unwrap_or_else(|e| panic!("could not create kube client config: {e}"));
+        let client = ClientBuilder::try_from(client_config)
+            .unwrap_or_else(|e| panic!("could not create kube client: {e}"))
+            .with_layer(&layer_fn(KubeMetricsService::new))
+            .build();
 
         StacksDevnetApiK8sManager {
             client,
             ctx: ctx.to_owned(),
+            operator: false,
+            owner_reference: None,
+        }
+    }
+
+    /// Creates a manager sharing this manager's client that logs with `ctx`,
+    /// so that the logs of a request's Kubernetes operations include its request id.
+    pub fn with_context(&self, ctx: &Context) -> StacksDevnetApiK8sManager {
+        StacksDevnetApiK8sManager {
+            ctx: ctx.to_owned(),
+            ..self.clone()
         }
     }
 
@@ -171,10 +373,12 @@ impl StacksDevnetApiK8sManager {
         B::Error: Into<BoxError>,
         T: Into<String>,
     {
-        let client = Client::new(service, default_namespace);
+        let client = Client::new(KubeMetricsService::new(service), default_namespace);
         StacksDevnetApiK8sManager {
             client,
             ctx: ctx.to_owned(),
+            operator: false,
+            owner_reference: None,
         }
     }
 
@@ -182,59 +386,169 @@ impl StacksDevnetApiK8sManager {
         &self,
         config: ValidatedStacksDevnetConfig,
     ) -> Result<(), DevNetError> {
+        let start = Instant::now();
+        let attributes = vec![
+            KeyValue::new("k8s.namespace.name", config.namespace.clone()),
+            KeyValue::new("devnet.disable_stacks_api", config.disable_stacks_api),
+        ];
+        let result = with_span(
+            &self.ctx,
+            "deploy_devnet",
+            attributes,
+            self._deploy_devnet(config),
+        )
+        .await;
+        metrics().observe_devnet_operation("deploy", start, &result);
+        result
+    }
+
+    async fn _deploy_devnet(&self, config: ValidatedStacksDevnetConfig) -> Result<(), DevNetError> {
         let namespace = &config.namespace;
         let user_id = &config.user_id;
 
         let context = format!("NAMESPACE: {}", &namespace);
-        let namespace_exists = self.check_namespace_exists(namespace).await?;
-        if !namespace_exists {
-            if cfg!(debug_assertions) {
-                self.deploy_namespace(namespace).await?;
-            } else {
-                let message =
-                    format!("cannot create devnet because namespace {namespace} does not exist");
-                self.ctx
-                    .try_log(|logger| slog::warn!(logger, "{}", message));
-                return Err(DevNetError { message, code: 400 });
-            }
-        }
+        self.ensure_namespace_exists(namespace).await?;
 
+        let config_hash = config.get_hash()?;
         let any_assets_exist = self
             .check_any_devnet_assets_exist(namespace, user_id)
             .await?;
-        if any_assets_exist {
-            let message = format!("cannot create devnet because assets already exist {context}");
+        // devnets deployed for a `StacksDevnet` converge on its spec whenever it changes
+        if any_assets_exist && self.owner_reference.is_none() {
+            // resources are applied, so creating the same devnet again converges on it,
+            // e.g. when a client retries after a partial failure. The hash is only written
+            // once every component is applied, so a deployment that didn't finish has none
+            // and converges on whichever config is submitted next. A different config isn't
+            // applied over a deployed devnet, which must be deleted first.
+            let deployed_hash = self.get_devnet_config_hash(namespace).await?;
+            if deployed_hash.is_some_and(|hash| hash != config_hash) {
+                let message = format!(
+                    "cannot create devnet because assets of a different config already exist {context}"
+                );
+                self.ctx
+                    .try_log(|logger| slog::warn!(logger, "{}", message));
+                return Err(DevNetError {
+                    message,
+                    code: 409,
+                    error_code: ErrorCode::DevnetAlreadyExists,
+                });
+            }
             self.ctx
-                .try_log(|logger| slog::warn!(logger, "{}", message));
-            return Err(DevNetError { message, code: 409 });
+                .try_log(|logger| slog::info!(logger, "reapplying devnet {}", context));
         };
 
-        self.deploy_bitcoin_node(&config).await?;
+        // each component is deployed in its own span, grouping the spans of its resources
+        let namespace_attribute = KeyValue::new("k8s.namespace.name", namespace.clone());
+        with_span(
+            &self.ctx,
+            "deploy_bitcoin_node",
+            vec![namespace_attribute.clone()],
+            self.deploy_bitcoin_node(&config),
+        )
+        .await?;
 
-        sleep(Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_secs(5)).await;
 
-        self.deploy_stacks_blockchain(&config).await?;
-        self.deploy_stacks_signer(
-            &config,
-            SignerIdx::Signer0,
-            "7287ba251d44a4d3fd9276c88ce34c5c52a038955511cccaf77e61068649c17801",
+        with_span(
+            &self.ctx,
+            "deploy_stacks_blockchain",
+            vec![namespace_attribute.clone()],
+            self.deploy_stacks_blockchain(&config),
+        )
+        .await?;
+        with_span(
+            &self.ctx,
+            "deploy_stacks_signer",
+            vec![
+                namespace_attribute.clone(),
+                KeyValue::new("devnet.signer", SignerIdx::Signer0.to_string()),
+            ],
+            self.deploy_stacks_signer(
+                &config,
+                SignerIdx::Signer0,
+                "7287ba251d44a4d3fd9276c88ce34c5c52a038955511cccaf77e61068649c17801",
+            ),
         )
         .await?;
 
-        self.deploy_stacks_signer(
-            &config,
-            SignerIdx::Signer1,
-            "530d9f61984c888536871c6573073bdfc0058896dc1adfe9a6a10dfacadc209101",
+        with_span(
+            &self.ctx,
+            "deploy_stacks_signer",
+            vec![
+                namespace_attribute.clone(),
+                KeyValue::new("devnet.signer", SignerIdx::Signer1.to_string()),
+            ],
+            self.deploy_stacks_signer(
+                &config,
+                SignerIdx::Signer1,
+                "530d9f61984c888536871c6573073bdfc0058896dc1adfe9a6a10dfacadc209101",
+            ),
         )
         .await?;
 
         if !config.disable_stacks_api {
-            self.deploy_stacks_blockchain_api(&config).await?;
+            with_span(
+                &self.ctx,
+                "deploy_stacks_blockchain_api",
+                vec![namespace_attribute],
+                self.deploy_stacks_blockchain_api(&config),
+            )
+            .await?;
         }
+
+        if !config.webhooks.is_empty() {
+            self.deploy_webhooks_configmap(namespace, user_id, &config.webhooks)
+                .await?;
+        }
+
+        self.deploy_devnet_configmap(
+            namespace,
+            user_id,
+            CONFIG_HASH_CONFIGMAP,
+            BTreeMap::from([("sha256".to_string(), config_hash)]),
+        )
+        .await?;
         Ok(())
     }
 
+    /// Devnets are only created in existing namespaces, except in debug builds where the
+    /// namespace is created for them.
+    async fn ensure_namespace_exists(&self, namespace: &str) -> Result<(), DevNetError> {
+        if self.check_namespace_exists(namespace).await? {
+            return Ok(());
+        }
+        if cfg!(debug_assertions) {
+            return self.deploy_namespace(namespace).await;
+        }
+        let message = format!("cannot create devnet because namespace {namespace} does not exist");
+        self.ctx
+            .try_log(|logger| slog::warn!(logger, "{}", message));
+        Err(DevNetError {
+            message,
+            code: 400,
+            error_code: ErrorCode::NamespaceNotFound,
+        })
+    }
+
     pub async fn delete_devnet(&self, namespace: &str, user_id: &str) -> Result<(), DevNetError> {
+        let start = Instant::now();
+        let result = with_span(
+            &self.ctx,
+            "delete_devnet",
+            vec![KeyValue::new("k8s.namespace.name", namespace.to_string())],
+            self._delete_devnet(namespace, user_id),
+        )
+        .await;
+        metrics().observe_devnet_operation("delete", start, &result);
+        result
+    }
+
+    async fn _delete_devnet(&self, namespace: &str, user_id: &str) -> Result<(), DevNetError> {
+        // the resources of a `StacksDevnet` are deleted along with it by the garbage
+        // collector, while devnets created before operator mode was enabled have none
+        if self.operator && self.delete_stacks_devnet(namespace).await? {
+            return Ok(());
+        }
         match self
             .check_any_devnet_assets_exist(namespace, user_id)
             .await?
@@ -278,6 +592,27 @@ impl StacksDevnetApiK8sManager {
                     }
                 }
 
+                for configmap in [WEBHOOKS_CONFIGMAP, CONFIG_HASH_CONFIGMAP] {
+                    if let Err(e) = self
+                        .delete_resource_by_label::<ConfigMap>(namespace, configmap, user_id)
+                        .await
+                    {
+                        errors.push(e);
+                    }
+                }
+
+                // any contract shards beyond the first `project-dir` configmap
+                if let Err(e) = self
+                    .delete_resource_by_label::<ConfigMap>(
+                        namespace,
+                        &StacksDevnetConfigmap::ProjectDir.to_string(),
+                        user_id,
+                    )
+                    .await
+                {
+                    errors.push(e);
+                }
+
                 let services: Vec<String> =
                     StacksDevnetService::iter().map(|s| s.to_string()).collect();
                 for service in services {
@@ -312,6 +647,7 @@ impl StacksDevnetApiK8sManager {
                     Err(DevNetError {
                         message: msg,
                         code: 500,
+                        error_code: ErrorCode::KubernetesError,
                     })
                 }
             }
@@ -322,44 +658,72 @@ impl StacksDevnetApiK8sManager {
                 );
                 self.ctx
                     .try_log(|logger| slog::warn!(logger, "{}", message));
-                Err(DevNetError { message, code: 409 })
+                Err(DevNetError {
+                    message,
+                    code: 409,
+                    error_code: ErrorCode::DevnetNotFound,
+                })
             }
         }
     }
 
     pub async fn check_namespace_exists(&self, namespace_str: &str) -> Result<bool, DevNetError> {
-        self.ctx.try_log(|logger| {
-            slog::info!(
-                logger,
-                "checking if namespace NAMESPACE: {}",
-                &namespace_str
-            )
-        });
-        let namespace_api: Api<Namespace> = kube::Api::all(self.client.to_owned());
-        match namespace_api.get(namespace_str).await {
-            Ok(_) => Ok(true),
-            Err(kube::Error::Api(api_error)) => {
-                if api_error.code == 404 {
-                    Ok(false)
-                } else {
-                    let msg = format!(
-                        "error getting namespace {}: {}",
-                        namespace_str, api_error.message
-                    );
-                    self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-                    Err(DevNetError {
-                        message: msg,
-                        code: api_error.code,
-                    })
+        with_span(
+            &self.ctx,
+            "check_namespace_exists",
+            vec![KeyValue::new(
+                "k8s.namespace.name",
+                namespace_str.to_string(),
+            )],
+            async {
+                self.ctx.try_log(|logger| {
+                    slog::info!(
+                        logger,
+                        "checking if namespace NAMESPACE: {}",
+                        &namespace_str
+                    )
+                });
+                let namespace_api: Api<Namespace> = kube::Api::all(self.client.to_owned());
+                match namespace_api.get(namespace_str).await {
+                    Ok(_) => Ok(true),
+                    Err(kube::Error::Api(api_error)) => {
+                        if api_error.code == 404 {
+                            Ok(false)
+                        } else {
+                            let msg = format!(
+                                "error getting namespace {}: {}",
+                                namespace_str, api_error.message
+                            );
+                            self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+                            Err(DevNetError {
+                                message: msg,
+                                code: api_error.code,
+                                error_code: ErrorCode::KubernetesError,
+                            })
+                        }
+                    }
+                    Err(e) => {
+                        let message = format!("error getting namespace {namespace_str}: {e}");
+                        self.ctx
+                            .try_log(|logger| slog::error!(logger, "{}", message));
+                        Err(DevNetError {
+                            message,
+                            code: 500,
+                            error_code: ErrorCode::KubernetesError,
+                        })
+                    }
                 }
-            }
-            Err(e) => {
-                let message = format!("error getting namespace {namespace_str}: {e}");
-                self.ctx
-                    .try_log(|logger| slog::error!(logger, "{}", message));
-                Err(DevNetError { message, code: 500 })
-            }
-        }
+            },
+        )
+        .await
+    }
+
+    /// Checks that the Kubernetes API can be reached and that the API is allowed to get
+    /// namespaces, with a get of the client's default namespace.
+    pub async fn check_kube_access(&self) -> Result<(), DevNetError> {
+        self.check_namespace_exists(self.client.default_namespace())
+            .await
+            .map(|_| ())
     }
 
     pub async fn check_any_devnet_assets_exist(
@@ -489,7 +853,7 @@ impl StacksDevnetApiK8sManager {
         namespace: &str,
         user_id: &str,
         pod: StacksDevnetPod,
-    ) -> Result<PodStatusResponse, DevNetError> {
+    ) -> Result<DevnetComponentStatus, DevNetError> {
         let context = format!("NAMESPACE: {namespace}, POD: {pod}");
 
         self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
@@ -510,20 +874,16 @@ impl StacksDevnetApiK8sManager {
 
         match pod_api.list(&lp).await {
             Ok(pods) => {
-                let pod_with_status = &pods.items[0];
-                match &pod_with_status.status {
-                    Some(status) => {
-                        self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
-                            slog::info!(logger, "successfully retrieved pod status {}", context)
-                        });
-                        let start_time = status.start_time.as_ref().map(|st| st.0.to_string());
-                        Ok(PodStatusResponse {
-                            status: status.phase.to_owned(),
-                            start_time,
-                        })
-                    }
-                    None => Ok(PodStatusResponse::default()),
-                }
+                self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
+                    slog::info!(logger, "successfully retrieved pod status {}", context)
+                });
+                // a pod that hasn't been created yet, e.g. while its stateful set is scaling up,
+                // is reported without a phase
+                Ok(pods
+                    .items
+                    .first()
+                    .map(get_component_status)
+                    .unwrap_or_default())
             }
             Err(e) => {
                 let (msg, code) = match e {
@@ -532,95 +892,285 @@ impl StacksDevnetApiK8sManager {
                 };
                 let msg = format!("failed to get pod status {context}, ERROR: {msg}");
                 self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-                Err(DevNetError { message: msg, code })
+                Err(DevNetError {
+                    message: msg,
+                    code,
+                    error_code: ErrorCode::KubernetesError,
+                })
             }
         }
     }
 
-    async fn get_stacks_v2_info(
+    /// Lists the events of `namespace`. Events only add detail to the devnet info,
+    /// so failing to list them is logged rather than returned.
+    async fn get_namespace_events(&self, namespace: &str) -> Vec<Event> {
+        let event_api: Api<Event> = Api::namespaced(self.client.to_owned(), namespace);
+        match event_api.list(&ListParams::default()).await {
+            Ok(events) => events.items,
+            Err(e) => {
+                self.ctx.try_log(|logger| {
+                    slog::warn!(
+                        logger,
+                        "failed to list events NAMESPACE: {}, ERROR: {}",
+                        namespace,
+                        e
+                    )
+                });
+                vec![]
+            }
+        }
+    }
+
+    /// Gets the webhooks set in the config of the devnet in `namespace`. Failing to read
+    /// them is logged rather than returned, so that the devnet can still be deleted.
+    pub async fn get_devnet_webhooks(&self, namespace: &str) -> Vec<String> {
+        let configmap_api: Api<ConfigMap> = Api::namespaced(self.client.to_owned(), namespace);
+        let urls = match configmap_api.get_opt(WEBHOOKS_CONFIGMAP).await {
+            Ok(configmap) => configmap
+                .and_then(|configmap| configmap.data)
+                .and_then(|mut data| data.remove("urls")),
+            Err(e) => {
+                self.ctx.try_log(|logger| {
+                    slog::warn!(
+                        logger,
+                        "failed to get webhooks NAMESPACE: {}, ERROR: {}",
+                        namespace,
+                        e
+                    )
+                });
+                None
+            }
+        };
+        urls.and_then(|urls| serde_json::from_str(&urls).ok())
+            .unwrap_or_default()
+    }
+
+    /// Checks whether the devnet in the namespace of `config` was deployed from the same
+    /// config, so that creating it again reapplies it rather than running another devnet.
+    pub async fn is_devnet_reapply(
         &self,
-        namespace: &str,
-    ) -> Result<StacksV2InfoResponse, DevNetError> {
-        let client = HttpClient::new();
+        config: &ValidatedStacksDevnetConfig,
+    ) -> Result<bool, DevNetError> {
+        let config_hash = config.get_hash()?;
+        Ok(self
+            .get_devnet_config_hash(&config.namespace)
+            .await?
+            .as_ref()
+            == Some(&config_hash))
+    }
+
+    /// Gets the hash of the config the devnet in `namespace` was deployed from. Devnets
+    /// deployed before the hash was recorded, or whose deployment didn't finish, have none.
+    async fn get_devnet_config_hash(&self, namespace: &str) -> Result<Option<String>, DevNetError> {
+        let configmap_api: Api<ConfigMap> = Api::namespaced(self.client.to_owned(), namespace);
+        match configmap_api.get_opt(CONFIG_HASH_CONFIGMAP).await {
+            Ok(configmap) => Ok(configmap
+                .and_then(|configmap| configmap.data)
+                .and_then(|mut data| data.remove("sha256"))),
+            Err(e) => {
+                let (message, code) = match e {
+                    kube::Error::Api(api_error) => (api_error.message, api_error.code),
+                    e => (e.to_string(), 500),
+                };
+                let message =
+                    format!("failed to get config hash NAMESPACE: {namespace}, ERROR: {message}");
+                self.ctx
+                    .try_log(|logger| slog::error!(logger, "{}", message));
+                Err(DevNetError {
+                    message,
+                    code,
+                    error_code: ErrorCode::KubernetesError,
+                })
+            }
+        }
+    }
 
+    /// Requests `path` from the RPC API of the devnet's stacks node. Responses other than
+    /// a 200 are returned as errors with the node's status code.
+    async fn get_stacks_node_json<T: DeserializeOwned>(
+        &self,
+        namespace: &str,
+        path: &str,
+    ) -> Result<T, DevNetError> {
         let url = get_service_url(namespace, StacksDevnetService::StacksBlockchain);
         let port =
             get_service_port(StacksDevnetService::StacksBlockchain, ServicePort::RPC).unwrap();
-        let url = format!("http://{url}:{port}/v2/info");
+        let url = format!("http://{url}:{port}{path}");
 
         let context = format!("NAMESPACE: {namespace}");
 
-        self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
-            slog::info!(logger, "Requesting URL: {}", url); // Log the full URL
-        });
-
         self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
             slog::info!(
                 logger,
-                "requesting /v2/info route of stacks node {}",
+                "requesting {} route of stacks node {}",
+                path,
                 context
             );
         });
 
-        match Uri::from_str(&url) {
-            Ok(uri) => {
-                match client.get(uri).await {
-                    Ok(response) => match hyper::body::to_bytes(response.into_body()).await {
-                        Ok(body) => {
-                            let body_str = String::from_utf8_lossy(&body);
-                            self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
-                                slog::info!(logger, "Raw response body: {}", body_str);
-                            });
-
-                            match serde_json::from_slice::<StacksV2InfoResponse>(&body) {
-                                Ok(config) => {
-                                    self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
-                                        slog::info!(logger, "successfully requested /v2/info route of stacks node {}", context);
-                                    });
-                                    Ok(config)
-                                }
-                                Err(e) => {
-                                    let msg = format!("failed to parse JSON response: {context}, ERROR: {e}, Raw body: {body_str}");
-                                    self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-                                    Err(DevNetError {
-                                        message: msg,
-                                        code: 500,
-                                    })
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            let msg =
-                                format!("failed to parse response bytes: {context}, ERROR: {e}");
-                            self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-                            Err(DevNetError {
-                                message: msg,
-                                code: 500,
-                            })
-                        }
-                    },
-                    Err(e) => {
-                        let msg = format!("failed to query stacks node: {context}, ERROR: {e}");
-                        self.ctx.try_log(|logger| slog::warn!(logger, "{}", msg));
-                        Ok(StacksV2InfoResponse::default()) // Return default response on error
-                    }
-                }
+        let uri = Uri::from_str(&url).map_err(|e| {
+            let msg = format!("failed to parse url: {url} ERROR: {e}");
+            self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+            DevNetError {
+                message: msg,
+                code: 500,
+                error_code: ErrorCode::InternalError,
             }
-            Err(e) => {
-                let msg = format!("failed to parse url: {url} ERROR: {e}");
+        })?;
+        let response = STACKS_NODE_CLIENT.get(uri).await.map_err(|e| {
+            let msg = format!("failed to query stacks node: {context}, ERROR: {e}");
+            self.ctx.try_log(|logger| slog::warn!(logger, "{}", msg));
+            DevNetError {
+                message: msg,
+                code: 502,
+                error_code: ErrorCode::UpstreamError,
+            }
+        })?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(|e| {
+                let msg = format!("failed to parse response bytes: {context}, ERROR: {e}");
                 self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-                Err(DevNetError {
+                DevNetError {
                     message: msg,
                     code: 500,
-                })
-            }
+                    error_code: ErrorCode::UpstreamError,
+                }
+            })?;
+        if !status.is_success() {
+            return Err(DevNetError {
+                message: format!(
+                    "stacks node responded to {path} with {status}: {}",
+                    String::from_utf8_lossy(&body)
+                ),
+                code: status.as_u16(),
+                error_code: ErrorCode::UpstreamError,
+            });
         }
+        serde_json::from_slice::<T>(&body).map_err(|e| {
+            let body_str = String::from_utf8_lossy(&body);
+            let msg = format!(
+                "failed to parse JSON response: {context}, ERROR: {e}, Raw body: {body_str}"
+            );
+            self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+            DevNetError {
+                message: msg,
+                code: 500,
+                error_code: ErrorCode::UpstreamError,
+            }
+        })
+    }
+
+    async fn get_stacks_v2_info(
+        &self,
+        namespace: &str,
+    ) -> Result<StacksV2InfoResponse, DevNetError> {
+        self.get_stacks_node_json(namespace, "/v2/info").await
+    }
+
+    /// Gets the epoch, PoX state and signer set of the devnet's chain. The signer set is
+    /// only known once the current cycle's prepare phase has passed, so failing to get it
+    /// leaves the signers unset.
+    async fn get_chain_status(&self, namespace: &str) -> Result<DevnetChainStatus, DevNetError> {
+        let pox: StacksV2PoxResponse = self.get_stacks_node_json(namespace, "/v2/pox").await?;
+        let stacker_set = self
+            .get_stacks_node_json::<StackerSetResponse>(
+                namespace,
+                &format!("/v3/stacker_set/{}", pox.reward_cycle()),
+            )
+            .await
+            .ok();
+        Ok(pox.to_chain_status(stacker_set))
     }
 
+    /// Checks which transactions of the deployment plan submitted with the devnet are
+    /// confirmed. Contracts and accounts the stacks node can't be asked about are
+    /// reported as unknown.
+    async fn get_deployment_progress(
+        &self,
+        namespace: &str,
+    ) -> Result<DeploymentProgress, DevNetError> {
+        let configmap_api: Api<ConfigMap> = Api::namespaced(self.client.to_owned(), namespace);
+        let configmap_name = StacksDevnetConfigmap::DeploymentPlan.to_string();
+        let plan_yaml = configmap_api
+            .get(&configmap_name)
+            .await
+            .map_err(|e| {
+                let (message, code) = match e {
+                    kube::Error::Api(api_error) => (api_error.message, api_error.code),
+                    e => (e.to_string(), 500),
+                };
+                DevNetError {
+                    message: format!(
+                        "failed to get deployment plan NAMESPACE: {namespace}, ERROR: {message}"
+                    ),
+                    code,
+                    error_code: ErrorCode::KubernetesError,
+                }
+            })?
+            .data
+            .and_then(|mut data| data.remove("default.devnet-plan.yaml"))
+            .unwrap_or_default();
+        let plan: DeploymentSpecification =
+            serde_yaml::from_str(&plan_yaml).map_err(|e| DevNetError {
+                message: format!(
+                    "failed to parse deployment plan NAMESPACE: {namespace}, ERROR: {e}"
+                ),
+                code: 500,
+                error_code: ErrorCode::InternalError,
+            })?;
+
+        let queries = get_deployment_queries(&plan);
+        let deployed_contracts: BTreeMap<String, bool> = stream::iter(&queries.contracts)
+            .map(|contract_id| async move {
+                let (address, name) = contract_id.split_once('.').unwrap_or_default();
+                let result = self
+                    .get_stacks_node_json::<serde_json::Value>(
+                        namespace,
+                        &format!("/v2/contracts/interface/{address}/{name}"),
+                    )
+                    .await;
+                (contract_id, result)
+            })
+            .buffer_unordered(MAX_CONCURRENT_NODE_REQUESTS)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .filter_map(|(contract_id, result)| match result {
+                Ok(_) => Some((contract_id.clone(), true)),
+                Err(e) if e.code == 404 => Some((contract_id.clone(), false)),
+                Err(_) => None,
+            })
+            .collect();
+
+        let nonces: BTreeMap<String, u64> = stream::iter(&queries.accounts)
+            .map(|address| async move {
+                let result = self
+                    .get_stacks_node_json::<StacksV2AccountResponse>(
+                        namespace,
+                        &format!("/v2/accounts/{address}?proof=0"),
+                    )
+                    .await;
+                (address, result)
+            })
+            .buffer_unordered(MAX_CONCURRENT_NODE_REQUESTS)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .filter_map(|(address, result)| Some((address.clone(), result.ok()?.nonce)))
+            .collect();
+
+        Ok(get_deployment_progress(&plan, &deployed_contracts, &nonces))
+    }
+
+    /// Gets the status of the devnet's pods, along with the `includes` that were requested.
+    /// The devnet's stacks node is only queried when some were.
     pub async fn get_devnet_info(
         &self,
         namespace: &str,
         user_id: &str,
+        includes: DevnetInfoIncludes,
     ) -> Result<StacksDevnetInfoResponse, DevNetError> {
         let context = format!("NAMESPACE: {namespace}");
 
@@ -632,6 +1182,7 @@ impl StacksDevnetApiK8sManager {
                 Err(DevNetError {
                     message: msg,
                     code: 404,
+                    error_code: ErrorCode::DevnetNotFound,
                 })
             }
             true => {
@@ -639,95 +1190,283 @@ impl StacksDevnetApiK8sManager {
                     slog::info!(logger, "getting devnet info {}", context)
                 });
 
-                // Fetch the pod status information
-                let (
-                    PodStatusResponse {
-                        status: bitcoind_node_status,
-                        start_time: bitcoind_node_started_at,
-                    },
-                    PodStatusResponse {
-                        status: stacks_node_status,
-                        start_time: stacks_node_started_at,
-                    },
-                    PodStatusResponse {
-                        status: stacks_api_status,
-                        start_time: stacks_api_started_at,
-                    },
-                ) = try_join3(
-                    self.get_pod_status_info(namespace, user_id, StacksDevnetPod::BitcoindNode),
-                    self.get_pod_status_info(namespace, user_id, StacksDevnetPod::StacksBlockchain),
-                    self.get_pod_status_info(
-                        namespace,
-                        user_id,
-                        StacksDevnetPod::StacksBlockchainApi,
-                    ),
+                // Fetch the status of every pod of the devnet
+                let component_names: Vec<String> =
+                    StacksDevnetPod::iter().map(|pod| pod.to_string()).collect();
+                let statuses = try_join_all(
+                    StacksDevnetPod::iter()
+                        .map(|pod| self.get_pod_status_info(namespace, user_id, pod)),
                 )
                 .await?;
+                let mut components: BTreeMap<String, DevnetComponentStatus> =
+                    component_names.into_iter().zip(statuses).collect();
+
+                let component_names: Vec<&str> = components.keys().map(|c| c.as_str()).collect();
+                let mut events = group_events_by_component(
+                    self.get_namespace_events(namespace).await,
+                    &component_names,
+                );
+                for (component, status) in components.iter_mut() {
+                    status.events = events.remove(component).unwrap_or_default();
+                }
+
+                if !(includes.tips || includes.chain || includes.deployment) {
+                    return Ok(StacksDevnetInfoResponse {
+                        components,
+                        stacks_chain_tip: None,
+                        bitcoin_chain_tip: None,
+                        chain: None,
+                        deployment: None,
+                    });
+                }
 
                 // Try to fetch chain info, but handle errors by using default values for the chain tips
-                let chain_info = match self.get_stacks_v2_info(namespace).await {
-                    Ok(info) => info,
+                let (chain_info, chain, deployment) = match self.get_stacks_v2_info(namespace).await
+                {
+                    Ok(info) => {
+                        // the chain and deployment status only add detail, so failing to
+                        // get them leaves them unset
+                        let (chain, deployment) = join(
+                            async {
+                                match includes.chain {
+                                    true => Some(self.get_chain_status(namespace).await),
+                                    false => None,
+                                }
+                            },
+                            async {
+                                match includes.deployment {
+                                    true => Some(self.get_deployment_progress(namespace).await),
+                                    false => None,
+                                }
+                            },
+                        )
+                        .await;
+                        for e in [
+                            chain.as_ref().and_then(|chain| chain.as_ref().err()),
+                            deployment
+                                .as_ref()
+                                .and_then(|deployment| deployment.as_ref().err()),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        {
+                            self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
+                                slog::warn!(logger, "Failed to get chain info: {}", e.message);
+                            });
+                        }
+                        (
+                            info,
+                            chain.and_then(Result::ok),
+                            deployment.and_then(Result::ok),
+                        )
+                    }
                     Err(e) => {
                         self.ctx.try_log(|logger: &hiro_system_kit::Logger| {
                             slog::warn!(logger, "Failed to get chain info: {}", e.message);
                         });
-                        StacksV2InfoResponse {
-                            stacks_tip_height: 0,
-                            burn_block_height: 0,
-                        }
+                        (
+                            StacksV2InfoResponse {
+                                stacks_tip_height: 0,
+                                burn_block_height: 0,
+                            },
+                            None,
+                            None,
+                        )
                     }
                 };
 
                 Ok(StacksDevnetInfoResponse {
-                    bitcoind_node_status,
-                    stacks_node_status,
-                    stacks_api_status,
-                    bitcoind_node_started_at,
-                    stacks_node_started_at,
-                    stacks_api_started_at,
-                    stacks_chain_tip: chain_info.stacks_tip_height,
-                    bitcoin_chain_tip: chain_info.burn_block_height,
+                    components,
+                    stacks_chain_tip: Some(chain_info.stacks_tip_height),
+                    bitcoin_chain_tip: Some(chain_info.burn_block_height),
+                    chain,
+                    deployment,
                 })
             }
         }
     }
 
-    pub async fn deploy_namespace(&self, namespace_str: &str) -> Result<(), DevNetError> {
-        let mut namespace: Namespace =
-            self.get_resource_from_file(StacksDevnetResource::Namespace)?;
-
-        namespace.metadata.name = Some(namespace_str.to_owned());
-        namespace.metadata.labels =
-            Some(BTreeMap::from([("name".into(), namespace_str.to_owned())]));
-
-        let namespace_api: Api<Namespace> = kube::Api::all(self.client.to_owned());
+    /// Lists every devnet managed by this API across all namespaces, sorted by namespace.
+    /// Returns the total number of devnets matching `filter` along with the requested page,
+    /// which is the only part for which chain tips are fetched.
+    pub async fn list_devnets(
+        &self,
+        filter: &DevnetListFilter,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(usize, Vec<StacksDevnetSummary>), DevNetError> {
+        let devnets: Vec<StacksDevnetSummary> = self
+            .get_devnet_summaries()
+            .await?
+            .into_iter()
+            .filter(|summary| filter.matches(summary))
+            .collect();
+        let total = devnets.len();
+        let mut page: Vec<StacksDevnetSummary> =
+            devnets.into_iter().skip(offset).take(limit).collect();
+
+        let chain_infos: Vec<_> = stream::iter(&page)
+            .map(|summary| self.get_stacks_v2_info(&summary.namespace))
+            .buffered(MAX_CONCURRENT_NODE_REQUESTS)
+            .collect()
+            .await;
+        for (summary, chain_info) in page.iter_mut().zip(chain_infos) {
+            let chain_info = chain_info.unwrap_or_default();
+            summary.stacks_chain_tip = chain_info.stacks_tip_height;
+            summary.bitcoin_chain_tip = chain_info.burn_block_height;
+        }
+        Ok((total, page))
+    }
 
-        let pp = PostParams::default();
+    /// Summarizes every devnet managed by this API across all namespaces, without chain tips.
+    pub async fn get_devnet_summaries(&self) -> Result<Vec<StacksDevnetSummary>, DevNetError> {
+        let lp = ListParams::default().labels(&format!("{MANAGED_BY_SELECTOR}={MANAGED_BY}"));
+        let (deployments, stateful_sets, pods) = try_join3(
+            self.list_all_resources::<Deployment>(&lp),
+            self.list_all_resources::<StatefulSet>(&lp),
+            self.list_all_resources::<Pod>(&lp),
+        )
+        .await?;
 
-        self.ctx
-            .try_log(|logger| slog::info!(logger, "creating namespace {}", namespace_str));
-        match namespace_api.create(&pp, &namespace).await {
-            Ok(_) => {
-                self.ctx.try_log(|logger| {
-                    slog::info!(logger, "successfully created namespace {}", namespace_str)
+        let mut devnets: BTreeMap<String, StacksDevnetSummary> = BTreeMap::new();
+        let workload_metadata = deployments
+            .into_iter()
+            .map(|d| d.metadata)
+            .chain(stateful_sets.into_iter().map(|s| s.metadata));
+        for metadata in workload_metadata {
+            let Some(namespace) = metadata.namespace else {
+                continue;
+            };
+            let summary = devnets
+                .entry(namespace.clone())
+                .or_insert_with(|| StacksDevnetSummary {
+                    namespace,
+                    ..Default::default()
                 });
-                Ok(())
+            if summary.owner.is_none() {
+                summary.owner = metadata
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get(USER_SELECTOR).cloned());
+            }
+            // the devnet was created when its first workload was
+            if let Some(created_at) = metadata.creation_timestamp.map(|t| t.0.to_string()) {
+                if summary
+                    .created_at
+                    .as_ref()
+                    .map_or(true, |c| &created_at < c)
+                {
+                    summary.created_at = Some(created_at);
+                }
+            }
+        }
+        for pod in pods {
+            let Some(summary) = pod
+                .metadata
+                .namespace
+                .as_ref()
+                .and_then(|namespace| devnets.get_mut(namespace))
+            else {
+                continue;
+            };
+            let component = pod
+                .metadata
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.get(COMPONENT_SELECTOR).cloned())
+                .or(pod.metadata.name);
+            let phase = pod.status.and_then(|status| status.phase);
+            if let (Some(component), Some(phase)) = (component, phase) {
+                summary.pod_phases.insert(component, phase);
             }
+        }
+
+        Ok(devnets.into_values().collect())
+    }
+
+    async fn list_all_resources<K>(&self, lp: &ListParams) -> Result<Vec<K>, DevNetError>
+    where
+        <K as kube::Resource>::DynamicType: Default,
+        K: kube::Resource,
+        K: Clone,
+        K: DeserializeOwned,
+        K: std::fmt::Debug,
+    {
+        let resource_api: Api<K> = Api::all(self.client.to_owned());
+        let resource_details = format!("RESOURCE: {}", std::any::type_name::<K>());
+        self.ctx.try_log(|logger| {
+            slog::info!(logger, "listing {} across namespaces", resource_details)
+        });
+        match resource_api.list(lp).await {
+            Ok(list) => Ok(list.items),
             Err(e) => {
-                let e = match e {
+                let (msg, code) = match e {
                     kube::Error::Api(api_error) => (api_error.message, api_error.code),
                     e => (e.to_string(), 500),
                 };
-                let msg = format!("failed to create namespace {}: {}", namespace_str, e.0);
+                let msg =
+                    format!("failed to list {resource_details} across namespaces, ERROR: {msg}");
                 self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
                 Err(DevNetError {
                     message: msg,
-                    code: e.1,
+                    code,
+                    error_code: ErrorCode::KubernetesError,
                 })
             }
         }
     }
 
+    pub async fn deploy_namespace(&self, namespace_str: &str) -> Result<(), DevNetError> {
+        with_span(
+            &self.ctx,
+            "deploy_namespace",
+            vec![KeyValue::new(
+                "k8s.namespace.name",
+                namespace_str.to_string(),
+            )],
+            async {
+                let mut namespace: Namespace =
+                    self.get_resource_from_file(StacksDevnetResource::Namespace)?;
+
+                namespace.metadata.name = Some(namespace_str.to_owned());
+                namespace.metadata.labels =
+                    Some(BTreeMap::from([("name".into(), namespace_str.to_owned())]));
+
+                let namespace_api: Api<Namespace> = kube::Api::all(self.client.to_owned());
+
+                let pp = PatchParams::apply(FIELD_MANAGER).force();
+
+                self.ctx
+                    .try_log(|logger| slog::info!(logger, "creating namespace {}", namespace_str));
+                match namespace_api
+                    .patch(namespace_str, &pp, &Patch::Apply(&namespace))
+                    .await
+                {
+                    Ok(_) => {
+                        self.ctx.try_log(|logger| {
+                            slog::info!(logger, "successfully created namespace {}", namespace_str)
+                        });
+                        Ok(())
+                    }
+                    Err(e) => {
+                        let e = match e {
+                            kube::Error::Api(api_error) => (api_error.message, api_error.code),
+                            e => (e.to_string(), 500),
+                        };
+                        let msg = format!("failed to create namespace {}: {}", namespace_str, e.0);
+                        self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+                        Err(DevNetError {
+                            message: msg,
+                            code: e.1,
+                            error_code: ErrorCode::KubernetesError,
+                        })
+                    }
+                }
+            },
+        )
+        .await
+    }
+
     async fn get_resource_by_label<K>(
         &self,
         namespace: &str,
@@ -785,7 +1524,11 @@ impl StacksDevnetApiK8sManager {
                 let message = format!("failed to fetch {resource_details}, ERROR: {msg}");
                 self.ctx
                     .try_log(|logger| slog::error!(logger, "{}", message));
-                Err(DevNetError { message, code })
+                Err(DevNetError {
+                    message,
+                    code,
+                    error_code: ErrorCode::KubernetesError,
+                })
             }
         }
     }
@@ -833,7 +1576,11 @@ impl StacksDevnetApiK8sManager {
                 let message = format!("failed to fetch {resource_details}, ERROR: {msg}");
                 self.ctx
                     .try_log(|logger| slog::error!(logger, "{}", message));
-                Err(DevNetError { message, code })
+                Err(DevNetError {
+                    message,
+                    code,
+                    error_code: ErrorCode::KubernetesError,
+                })
             }
         }
     }
@@ -852,13 +1599,28 @@ impl StacksDevnetApiK8sManager {
         K: Serialize,
         K: kube::Resource<Scope = NamespaceResourceScope>,
     {
-        match self
-            .get_resource_by_label::<K>(namespace, name, user_id)
-            .await?
-        {
-            Some(_) => Ok(true),
-            None => Ok(false),
-        }
+        with_span(
+            &self.ctx,
+            "check_resource_exists_by_label",
+            vec![
+                KeyValue::new("k8s.namespace.name", namespace.to_string()),
+                KeyValue::new(
+                    "k8s.resource.kind",
+                    K::kind(&Default::default()).to_string(),
+                ),
+                KeyValue::new("k8s.resource.name", name.to_string()),
+            ],
+            async {
+                match self
+                    .get_resource_by_label::<K>(namespace, name, user_id)
+                    .await?
+                {
+                    Some(_) => Ok(true),
+                    None => Ok(false),
+                }
+            },
+        )
+        .await
     }
 
     async fn check_resource_exists<K>(
@@ -874,10 +1636,25 @@ impl StacksDevnetApiK8sManager {
         K: Serialize,
         K: kube::Resource<Scope = NamespaceResourceScope>,
     {
-        match self.get_resource::<K>(namespace, name).await? {
-            Some(_) => Ok(true),
-            None => Ok(false),
-        }
+        with_span(
+            &self.ctx,
+            "check_resource_exists",
+            vec![
+                KeyValue::new("k8s.namespace.name", namespace.to_string()),
+                KeyValue::new(
+                    "k8s.resource.kind",
+                    K::kind(&Default::default()).to_string(),
+                ),
+                KeyValue::new("k8s.resource.name", name.to_string()),
+            ],
+            async {
+                match self.get_resource::<K>(namespace, name).await? {
+                    Some(_) => Ok(true),
+                    None => Ok(false),
+                }
+            },
+        )
+        .await
     }
 
     async fn deploy_resource<K>(
@@ -894,46 +1671,71 @@ impl StacksDevnetApiK8sManager {
         K: Serialize,
         K: kube::Resource<Scope = NamespaceResourceScope>,
     {
-        let resource_api: Api<K> = Api::namespaced(self.client.to_owned(), namespace);
-        let pp = PostParams::default();
-
-        let name = match resource.meta().name.as_ref() {
-            Some(name) => name,
-            None => {
-                self.ctx.try_log(|logger| {
-                    slog::warn!(
-                        logger,
-                        "resource does not have a name field. it really should"
-                    )
-                });
-                "no-name"
-            }
-        };
-        let resource_details =
-            format!("RESOURCE: {resource_type}, NAME: {name}, NAMESPACE: {namespace}");
-        self.ctx
-            .try_log(|logger| slog::info!(logger, "creating {}", resource_details));
-
-        match resource_api.create(&pp, &resource).await {
-            Ok(_) => {
-                self.ctx.try_log(|logger| {
-                    slog::info!(logger, "successfully created {}", resource_details)
-                });
-                Ok(())
-            }
-            Err(e) => {
-                let e = match e {
-                    kube::Error::Api(api_error) => (api_error.message, api_error.code),
-                    e => (e.to_string(), 500),
+        with_span(
+            &self.ctx,
+            "deploy_resource",
+            vec![
+                KeyValue::new("k8s.namespace.name", namespace.to_string()),
+                KeyValue::new(
+                    "k8s.resource.kind",
+                    K::kind(&Default::default()).to_string(),
+                ),
+                KeyValue::new("k8s.resource.name", resource.name_any()),
+            ],
+            async {
+                let mut resource = resource;
+                if let Some(owner_reference) = &self.owner_reference {
+                    resource.meta_mut().owner_references = Some(vec![owner_reference.clone()]);
+                }
+                let resource_api: Api<K> = Api::namespaced(self.client.to_owned(), namespace);
+                // forcing the apply takes over fields changed by hand, so the resource
+                // converges on the desired state
+                let pp = PatchParams::apply(FIELD_MANAGER).force();
+
+                let name = match resource.meta().name.as_ref() {
+                    Some(name) => name,
+                    None => {
+                        self.ctx.try_log(|logger| {
+                            slog::warn!(
+                                logger,
+                                "resource does not have a name field. it really should"
+                            )
+                        });
+                        "no-name"
+                    }
                 };
-                let msg = format!("failed to create {}, ERROR: {}", resource_details, e.0);
-                self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-                Err(DevNetError {
-                    message: msg,
-                    code: e.1,
-                })
-            }
-        }
+                let resource_details =
+                    format!("RESOURCE: {resource_type}, NAME: {name}, NAMESPACE: {namespace}");
+                self.ctx
+                    .try_log(|logger| slog::info!(logger, "applying {}", resource_details));
+
+                match resource_api
+                    .patch(name, &pp, &Patch::Apply(&resource))
+                    .await
+                {
+                    Ok(_) => {
+                        self.ctx.try_log(|logger| {
+                            slog::info!(logger, "successfully applied {}", resource_details)
+                        });
+                        Ok(())
+                    }
+                    Err(e) => {
+                        let e = match e {
+                            kube::Error::Api(api_error) => (api_error.message, api_error.code),
+                            e => (e.to_string(), 500),
+                        };
+                        let msg = format!("failed to apply {}, ERROR: {}", resource_details, e.0);
+                        self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+                        Err(DevNetError {
+                            message: msg,
+                            code: e.1,
+                            error_code: ErrorCode::KubernetesError,
+                        })
+                    }
+                }
+            },
+        )
+        .await
     }
 
     async fn deploy_deployment(
@@ -942,6 +1744,17 @@ impl StacksDevnetApiK8sManager {
         namespace: &str,
         user_id: &str,
     ) -> Result<(), DevNetError> {
+        let deployment = self.get_deployment(deployment_type, namespace, user_id)?;
+        self.deploy_resource(namespace, deployment, "deployment")
+            .await
+    }
+
+    fn get_deployment(
+        &self,
+        deployment_type: StacksDevnetDeployment,
+        namespace: &str,
+        user_id: &str,
+    ) -> Result<Deployment, DevNetError> {
         let deployment_type_moved = deployment_type.clone();
         let mut deployment: Deployment =
             self.get_resource_from_file(StacksDevnetResource::Deployment(deployment_type_moved))?;
@@ -989,8 +1802,7 @@ impl StacksDevnetApiK8sManager {
         }
 
         deployment.metadata.namespace = Some(namespace.to_owned());
-        self.deploy_resource(namespace, deployment, "deployment")
-            .await
+        Ok(deployment)
     }
 
     async fn deploy_stateful_set(
@@ -1105,6 +1917,84 @@ impl StacksDevnetApiK8sManager {
             .await
     }
 
+    async fn deploy_project_dir_configmap(
+        &self,
+        configmap_name: &str,
+        namespace: &str,
+        user_id: &str,
+        configmap_data: Vec<(String, String)>,
+    ) -> Result<(), DevNetError> {
+        let mut configmap: ConfigMap = self.get_resource_from_file(
+            StacksDevnetResource::Configmap(StacksDevnetConfigmap::ProjectDir),
+        )?;
+
+        configmap.metadata.name = Some(configmap_name.to_owned());
+        configmap.metadata.namespace = Some(namespace.to_owned());
+        // the user label allows additional shards to be found and deleted with the devnet
+        configmap
+            .metadata
+            .labels
+            .get_or_insert_with(BTreeMap::new)
+            .insert(USER_SELECTOR.to_string(), user_id.to_owned());
+        configmap.data = Some(configmap_data.into_iter().collect());
+
+        self.deploy_resource(namespace, configmap, "configmap")
+            .await
+    }
+
+    /// Deploys a configmap holding details of the devnet for the API itself, with the same
+    /// labels as the devnet's other configmaps so it is deleted along with them.
+    async fn deploy_devnet_configmap(
+        &self,
+        namespace: &str,
+        user_id: &str,
+        name: &str,
+        data: BTreeMap<String, String>,
+    ) -> Result<(), DevNetError> {
+        let labels = [
+            (MANAGED_BY_SELECTOR, MANAGED_BY),
+            (NAME_SELECTOR, name),
+            (COMPONENT_SELECTOR, name),
+            (USER_SELECTOR, user_id),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let configmap = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some(namespace.to_owned()),
+                labels: Some(labels),
+                ..Default::default()
+            },
+            data: Some(data),
+            ..Default::default()
+        };
+
+        self.deploy_resource(namespace, configmap, "configmap")
+            .await
+    }
+
+    async fn deploy_webhooks_configmap(
+        &self,
+        namespace: &str,
+        user_id: &str,
+        webhooks: &[String],
+    ) -> Result<(), DevNetError> {
+        let urls = serde_json::to_string(webhooks).map_err(|e| DevNetError {
+            message: format!("failed to serialize webhooks: {e}"),
+            code: 500,
+            error_code: ErrorCode::InternalError,
+        })?;
+        self.deploy_devnet_configmap(
+            namespace,
+            user_id,
+            WEBHOOKS_CONFIGMAP,
+            BTreeMap::from([("urls".to_string(), urls)]),
+        )
+        .await
+    }
+
     async fn deploy_bitcoin_node(
         &self,
         config: &ValidatedStacksDevnetConfig,
@@ -1186,14 +2076,78 @@ impl StacksDevnetApiK8sManager {
         )
         .await?;
 
-        self.deploy_configmap(
-            StacksDevnetConfigmap::ProjectDir,
-            namespace,
-            Some(config.contract_configmap_data.to_owned()),
+        let project_dir_shards = shard_configmap_data(
+            config.contract_configmap_data.to_owned(),
+            MAX_CONFIGMAP_DATA_BYTES,
         )
-        .await?;
+        .map_err(|message| {
+            let message = format!("cannot create devnet NAMESPACE: {namespace}, ERROR: {message}");
+            self.ctx
+                .try_log(|logger| slog::warn!(logger, "{}", message));
+            DevNetError {
+                message,
+                code: 400,
+                error_code: ErrorCode::InvalidConfig,
+            }
+        })?;
+        let mut project_sources = vec![];
+        for (shard_idx, shard) in project_dir_shards.into_iter().enumerate() {
+            let configmap_name = get_project_dir_configmap_name(shard_idx);
+            let mut items = vec![];
+            let mut data = vec![];
+            for (contract_idx, (path, source)) in shard.into_iter().enumerate() {
+                let key = format!("contract-{contract_idx}");
+                items.push(KeyToPath {
+                    key: key.clone(),
+                    path,
+                    mode: None,
+                });
+                data.push((key, source));
+            }
+            self.deploy_project_dir_configmap(&configmap_name, namespace, user_id, data)
+                .await?;
+            project_sources.push(VolumeProjection {
+                config_map: Some(ConfigMapProjection {
+                    name: Some(configmap_name),
+                    items: Some(items),
+                    optional: None,
+                }),
+                ..Default::default()
+            });
+        }
 
-        self.deploy_deployment(StacksDevnetDeployment::BitcoindNode, namespace, user_id)
+        // contract sources are projected into the project root alongside the project
+        // manifest, so their paths match the locations in the deployment plan
+        let mut deployment =
+            self.get_deployment(StacksDevnetDeployment::BitcoindNode, namespace, user_id)?;
+        let project_volume = deployment
+            .spec
+            .as_mut()
+            .and_then(|spec| spec.template.spec.as_mut())
+            .and_then(|pod_spec| pod_spec.volumes.as_mut())
+            .and_then(|volumes| {
+                volumes
+                    .iter_mut()
+                    .find(|v| v.name == StacksDevnetConfigmap::ProjectManifest.to_string())
+            })
+            .and_then(|volume| volume.projected.as_mut());
+        match project_volume {
+            Some(projected) => projected
+                .sources
+                .get_or_insert_with(Vec::new)
+                .append(&mut project_sources),
+            None => {
+                let message = "bitcoind-chain-coordinator template is missing projected project-manifest volume".to_string();
+                self.ctx
+                    .try_log(|logger| slog::error!(logger, "{}", message));
+                return Err(DevNetError {
+                    message,
+                    code: 500,
+                    error_code: ErrorCode::InternalError,
+                });
+            }
+        }
+        self.deploy_resource(namespace, deployment, "deployment")
             .await?;
 
         self.deploy_service(StacksDevnetService::BitcoindNode, namespace, user_id)
@@ -1372,6 +2326,7 @@ impl StacksDevnetApiK8sManager {
             let epoch_conf_str = toml::to_string(&epoch_conf).map_err(|e| DevNetError {
                 message: format!("failed to serialize epoch config: {e}"),
                 code: 500,
+                error_code: ErrorCode::InternalError,
             })?;
             stacks_conf.push_str(&epoch_conf_str);
 
@@ -1542,37 +2497,53 @@ impl StacksDevnetApiK8sManager {
         K: std::fmt::Debug,
         K: kube::Resource<Scope = NamespaceResourceScope>,
     {
-        let api: Api<K> = Api::namespaced(self.client.to_owned(), namespace);
-        let dp = DeleteParams::default();
-
-        let resource_details = format!(
-            "RESOURCE: {}, NAME: {}, NAMESPACE: {}",
-            std::any::type_name::<K>(),
-            resource_name,
-            namespace
-        );
-        self.ctx
-            .try_log(|logger| slog::info!(logger, "deleting {}", resource_details));
-        match api.delete(resource_name, &dp).await {
-            Ok(_) => {
-                self.ctx.try_log(|logger| {
-                    slog::info!(logger, "successfully deleted {}", resource_details)
-                });
-                Ok(())
-            }
-            Err(e) => {
-                let e = match e {
-                    kube::Error::Api(api_error) => (api_error.message, api_error.code),
-                    e => (e.to_string(), 500),
-                };
-                let msg = format!("failed to delete {}, ERROR: {}", resource_details, e.0);
-                self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-                Err(DevNetError {
-                    message: msg,
-                    code: e.1,
-                })
-            }
-        }
+        with_span(
+            &self.ctx,
+            "delete_resource",
+            vec![
+                KeyValue::new("k8s.namespace.name", namespace.to_string()),
+                KeyValue::new(
+                    "k8s.resource.kind",
+                    K::kind(&Default::default()).to_string(),
+                ),
+                KeyValue::new("k8s.resource.name", resource_name.to_string()),
+            ],
+            async {
+                let api: Api<K> = Api::namespaced(self.client.to_owned(), namespace);
+                let dp = DeleteParams::default();
+
+                let resource_details = format!(
+                    "RESOURCE: {}, NAME: {}, NAMESPACE: {}",
+                    std::any::type_name::<K>(),
+                    resource_name,
+                    namespace
+                );
+                self.ctx
+                    .try_log(|logger| slog::info!(logger, "deleting {}", resource_details));
+                match api.delete(resource_name, &dp).await {
+                    Ok(_) => {
+                        self.ctx.try_log(|logger| {
+                            slog::info!(logger, "successfully deleted {}", resource_details)
+                        });
+                        Ok(())
+                    }
+                    Err(e) => {
+                        let e = match e {
+                            kube::Error::Api(api_error) => (api_error.message, api_error.code),
+                            e => (e.to_string(), 500),
+                        };
+                        let msg = format!("failed to delete {}, ERROR: {}", resource_details, e.0);
+                        self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+                        Err(DevNetError {
+                            message: msg,
+                            code: e.1,
+                            error_code: ErrorCode::KubernetesError,
+                        })
+                    }
+                }
+            },
+        )
+        .await
     }
 
     async fn delete_resource_by_label<K>(
@@ -1588,80 +2559,109 @@ impl StacksDevnetApiK8sManager {
         K: std::fmt::Debug,
         K: kube::Resource<Scope = NamespaceResourceScope>,
     {
-        let api: Api<K> = Api::namespaced(self.client.to_owned(), namespace);
-        let dp = DeleteParams::default();
-
-        let pod_label_selector = format!("{COMPONENT_SELECTOR}={resource_name}");
-        let user_label_selector = format!("{USER_SELECTOR}={user_id}");
-        let name_label_selector = format!("{NAME_SELECTOR}={resource_name}");
-        let label_selector =
-            format!("{pod_label_selector},{user_label_selector},{name_label_selector}");
-
-        let lp = ListParams::default()
-            .match_any()
-            .labels(&label_selector)
-            .limit(1);
-
-        let resource_details = format!(
-            "RESOURCE: {}, NAME: {}, NAMESPACE: {}",
-            std::any::type_name::<K>(),
-            resource_name,
-            namespace
-        );
-        self.ctx
-            .try_log(|logger| slog::info!(logger, "deleting {}", resource_details));
-        match api.delete_collection(&dp, &lp).await {
-            Ok(_) => {
-                self.ctx.try_log(|logger| {
-                    slog::info!(logger, "successfully deleted {}", resource_details)
-                });
-                Ok(())
-            }
-            Err(e) => {
-                let e = match e {
-                    kube::Error::Api(api_error) => (api_error.message, api_error.code),
-                    e => (e.to_string(), 500),
-                };
-                let msg = format!("failed to delete {}, ERROR: {}", resource_details, e.0);
-                self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-                Err(DevNetError {
-                    message: msg,
-                    code: e.1,
-                })
-            }
-        }
+        with_span(
+            &self.ctx,
+            "delete_resource_by_label",
+            vec![
+                KeyValue::new("k8s.namespace.name", namespace.to_string()),
+                KeyValue::new(
+                    "k8s.resource.kind",
+                    K::kind(&Default::default()).to_string(),
+                ),
+                KeyValue::new("k8s.resource.name", resource_name.to_string()),
+            ],
+            async {
+                let api: Api<K> = Api::namespaced(self.client.to_owned(), namespace);
+                let dp = DeleteParams::default();
+
+                let pod_label_selector = format!("{COMPONENT_SELECTOR}={resource_name}");
+                let user_label_selector = format!("{USER_SELECTOR}={user_id}");
+                let name_label_selector = format!("{NAME_SELECTOR}={resource_name}");
+                let label_selector =
+                    format!("{pod_label_selector},{user_label_selector},{name_label_selector}");
+
+                // every match is deleted, e.g. each shard of the `project-dir` configmap
+                let lp = ListParams::default().match_any().labels(&label_selector);
+
+                let resource_details = format!(
+                    "RESOURCE: {}, NAME: {}, NAMESPACE: {}",
+                    std::any::type_name::<K>(),
+                    resource_name,
+                    namespace
+                );
+                self.ctx
+                    .try_log(|logger| slog::info!(logger, "deleting {}", resource_details));
+                match api.delete_collection(&dp, &lp).await {
+                    Ok(_) => {
+                        self.ctx.try_log(|logger| {
+                            slog::info!(logger, "successfully deleted {}", resource_details)
+                        });
+                        Ok(())
+                    }
+                    Err(e) => {
+                        let e = match e {
+                            kube::Error::Api(api_error) => (api_error.message, api_error.code),
+                            e => (e.to_string(), 500),
+                        };
+                        let msg = format!("failed to delete {}, ERROR: {}", resource_details, e.0);
+                        self.ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+                        Err(DevNetError {
+                            message: msg,
+                            code: e.1,
+                            error_code: ErrorCode::KubernetesError,
+                        })
+                    }
+                }
+            },
+        )
+        .await
     }
 
     pub async fn delete_namespace(&self, namespace_str: &str) -> Result<(), DevNetError> {
-        if cfg!(debug_assertions) {
-            use kube::ResourceExt;
-            let api: Api<Namespace> = kube::Api::all(self.client.to_owned());
-
-            let dp = DeleteParams::default();
-            match api.delete(namespace_str, &dp).await {
-                Ok(namespace) => {
-                    namespace.map_left(|del| {
-                        assert_eq!(del.name_any(), namespace_str);
-                        self.ctx
-                            .try_log(|logger| slog::info!(logger, "Deleting namespace started"));
-                    });
-                    Ok(())
+        with_span(
+            &self.ctx,
+            "delete_namespace",
+            vec![KeyValue::new(
+                "k8s.namespace.name",
+                namespace_str.to_string(),
+            )],
+            async {
+                if cfg!(debug_assertions) {
+                    use kube::ResourceExt;
+                    let api: Api<Namespace> = kube::Api::all(self.client.to_owned());
+
+                    let dp = DeleteParams::default();
+                    match api.delete(namespace_str, &dp).await {
+                        Ok(namespace) => {
+                            namespace.map_left(|del| {
+                                assert_eq!(del.name_any(), namespace_str);
+                                self.ctx.try_log(|logger| {
+                                    slog::info!(logger, "Deleting namespace started")
+                                });
+                            });
+                            Ok(())
+                        }
+                        Err(kube::Error::Api(api_error)) => Err(DevNetError {
+                            message: format!("unable to delete namespace: {}", api_error.message),
+                            code: api_error.code,
+                            error_code: ErrorCode::KubernetesError,
+                        }),
+                        Err(e) => Err(DevNetError {
+                            message: format!("unable to delete namespace: {e}"),
+                            code: 500,
+                            error_code: ErrorCode::KubernetesError,
+                        }),
+                    }
+                } else {
+                    Err(DevNetError {
+                        message: "namespace deletion can only occur in debug mode".to_string(),
+                        code: 403,
+                        error_code: ErrorCode::Forbidden,
+                    })
                 }
-                Err(kube::Error::Api(api_error)) => Err(DevNetError {
-                    message: format!("unable to delete namespace: {}", api_error.message),
-                    code: api_error.code,
-                }),
-                Err(e) => Err(DevNetError {
-                    message: format!("unable to delete namespace: {e}"),
-                    code: 500,
-                }),
-            }
-        } else {
-            Err(DevNetError {
-                message: "namespace deletion can only occur in debug mode".to_string(),
-                code: 403,
-            })
-        }
+            },
+        )
+        .await
     }
 
     fn get_resource_from_file<K>(&self, template: StacksDevnetResource) -> Result<K, DevNetError>
@@ -1678,6 +2678,7 @@ impl StacksDevnetApiK8sManager {
                 Err(DevNetError {
                     message: msg,
                     code: 500,
+                    error_code: ErrorCode::InternalError,
                 })
             }
         }
@@ -1,57 +1,139 @@
 use hiro_system_kit::slog;
+use hyper::header::HeaderValue;
+use hyper::server::conn::{AddrStream, Http};
 use hyper::service::{make_service_fn, service_fn};
-use hyper::{Body, Method, Request, Response, Server};
-use stacks_devnet_api::api_config::ApiConfig;
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use opentelemetry::trace::{FutureExt, SpanKind, Status, TraceContextExt};
+use opentelemetry::KeyValue;
+use stacks_devnet_api::api_config::{
+    ApiConfig, ApiConfigReloader, ConfigError, ConfigSources, ServerConfig, TlsConfig,
+};
+use stacks_devnet_api::audit::{AuditAction, AuditLog, AuditOutcome, AuditRecord, PeerAddr};
+use stacks_devnet_api::auth::{authenticate, NetworkAction};
+use stacks_devnet_api::metrics::metrics;
+use stacks_devnet_api::operator::run_operator;
 use stacks_devnet_api::responder::Responder;
 use stacks_devnet_api::routes::{
-    get_standardized_path_parts, handle_check_devnet, handle_delete_devnet, handle_get_devnet,
-    handle_get_status, handle_new_devnet, handle_try_proxy_service, API_PATH,
+    get_request_id, get_route_template, get_standardized_path_parts, handle_check_devnet,
+    handle_delete_devnet, handle_get_audit_log, handle_get_devnet, handle_get_healthz,
+    handle_get_metrics, handle_get_openapi, handle_get_readyz, handle_get_status,
+    handle_list_devnets, handle_new_devnet, handle_try_proxy_service, handle_wait_for_devnet,
+    is_known_route, update_devnet_metrics, API_PATH, REQUEST_ID_HEADER,
+};
+use stacks_devnet_api::telemetry::{
+    extract_trace_context, init_tracing, shutdown_tracing, start_span,
 };
-use stacks_devnet_api::{Context, StacksDevnetApiK8sManager};
+use stacks_devnet_api::tls::{load_tls_acceptor, ClientCertificate, TlsAcceptorReloader};
+use stacks_devnet_api::webhooks::Webhooks;
+use stacks_devnet_api::{Context, DevNetError, ErrorCode, StacksDevnetApiK8sManager};
 use std::collections::HashMap;
 use std::env;
-use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{convert::Infallible, net::SocketAddr};
+use tokio::net::TcpListener;
+use tokio::signal::unix::{signal, SignalKind};
+
+const DEFAULT_HOST: &str = "0.0.0.0";
+const DEFAULT_PORT: u16 = 8477;
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(10);
+const DEVNET_METRICS_INTERVAL: Duration = Duration::from_secs(30);
 
 #[tokio::main]
 async fn main() {
-    const HOST: &str = "0.0.0.0";
-    let port: &str = &env::var("PORT").unwrap_or("8477".to_string());
-    let endpoint: String = HOST.to_owned() + ":" + port;
-    let addr: SocketAddr = endpoint.parse().expect("Could not parse ip:port.");
-
     let logger = hiro_system_kit::log::setup_logger();
     let _guard = hiro_system_kit::log::setup_global_logger(logger.clone());
-    let ctx = Context {
+    let mut ctx = Context {
         logger: Some(logger),
         tracer: false,
+        request_id: None,
     };
-    let k8s_manager = StacksDevnetApiK8sManager::new(&ctx).await;
-    let config_path = match env::var("CONFIG_PATH") {
-        Ok(path) => path,
-        Err(_) => {
-            if cfg!(debug_assertions) {
-                "./Config.toml".into()
-            } else {
-                "/etc/config/Config.toml".into()
-            }
+
+    // the test harness passes its own arguments
+    let args: Vec<String> = match cfg!(test) {
+        true => vec![],
+        false => env::args().skip(1).collect(),
+    };
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!(
+            "Usage: stacks-devnet-api [OPTIONS]\n\nOptions (environment variable):\n{}",
+            ConfigSources::usage()
+        );
+        return;
+    }
+    let (sources, config) = match load_config(&args) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("invalid configuration: {e}");
+            std::process::exit(1);
         }
     };
-    let config = ApiConfig::from_path(&config_path);
+    ctx.tracer = match init_tracing(&config.tracing_config) {
+        Ok(enabled) => enabled,
+        Err(e) => {
+            eprintln!("invalid configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+    let ServerConfig {
+        host,
+        port,
+        kube_context,
+        tls: config_tls,
+    } = config.server_config.clone();
+    let host = host.unwrap_or(DEFAULT_HOST.into());
+    let port = port.unwrap_or(DEFAULT_PORT);
+    let addr: SocketAddr = match format!("{host}:{port}").parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            eprintln!("invalid bind address {host}:{port}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let k8s_manager = StacksDevnetApiK8sManager::with_kube_context(&ctx, kube_context)
+        .await
+        .with_operator(config.operator_config.is_enabled());
+    if k8s_manager.uses_operator() {
+        tokio::spawn(run_operator(k8s_manager.clone()));
+    }
+    let config = Arc::new(RwLock::new(config));
     let request_store = Arc::new(Mutex::new(HashMap::new()));
+    tokio::spawn(watch_config(
+        ApiConfigReloader::new(sources, config.clone()),
+        ctx.clone(),
+    ));
+    tokio::spawn(refresh_devnet_metrics(
+        k8s_manager.clone(),
+        request_store.clone(),
+        ctx.clone(),
+    ));
+
+    if let Some(tls_config) = config_tls {
+        serve_tls(addr, tls_config, k8s_manager, config, request_store, ctx).await;
+        shutdown_tracing();
+        return;
+    }
 
-    let make_svc = make_service_fn(|_| {
+    let make_svc = make_service_fn(|conn: &AddrStream| {
+        let peer_addr = PeerAddr(conn.remote_addr());
         let k8s_manager = k8s_manager.clone();
         let ctx = ctx.clone();
         let config = config.clone();
         let request_store = request_store.clone();
         async move {
-            Ok::<_, Infallible>(service_fn(move |req| {
+            Ok::<_, Infallible>(service_fn(move |mut req: Request<Body>| {
+                req.extensions_mut().insert(peer_addr);
+                // each request uses a snapshot of the config, so a reload never affects in-flight requests
+                let config = match config.read() {
+                    Ok(config) => config.clone(),
+                    Err(e) => e.into_inner().clone(),
+                };
                 handle_request(
                     req,
                     k8s_manager.clone(),
-                    config.clone(),
+                    config,
                     request_store.clone(),
                     ctx.clone(),
                 )
@@ -66,16 +148,271 @@ async fn main() {
     if let Err(e) = server.await {
         ctx.try_log(|logger| slog::error!(logger, "server error: {}", e));
     }
+    shutdown_tracing();
+}
+
+/// Serves HTTPS, reloading the certificate and key for new connections when their files change.
+/// With a client CA configured, the common name of each client's certificate is attached
+/// to its requests so it can be used for authentication.
+async fn serve_tls(
+    addr: SocketAddr,
+    tls_config: TlsConfig,
+    k8s_manager: StacksDevnetApiK8sManager,
+    config: Arc<RwLock<ApiConfig>>,
+    request_store: Arc<Mutex<HashMap<String, u64>>>,
+    ctx: Context,
+) {
+    let acceptor = match load_tls_acceptor(&tls_config) {
+        Ok(acceptor) => Arc::new(RwLock::new(acceptor)),
+        Err(e) => {
+            eprintln!("invalid tls configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+    tokio::spawn(watch_tls(
+        TlsAcceptorReloader::new(tls_config, acceptor.clone()),
+        ctx.clone(),
+    ));
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            ctx.try_log(|logger| slog::error!(logger, "server error: {}", e));
+            return;
+        }
+    };
+
+    ctx.try_log(|logger| slog::info!(logger, "Running TLS server on {:?}", addr));
+
+    loop {
+        let (stream, peer_addr) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                ctx.try_log(|logger| slog::warn!(logger, "failed to accept connection: {}", e));
+                continue;
+            }
+        };
+        let acceptor = match acceptor.read() {
+            Ok(acceptor) => acceptor.clone(),
+            Err(e) => e.into_inner().clone(),
+        };
+        let k8s_manager = k8s_manager.clone();
+        let config = config.clone();
+        let request_store = request_store.clone();
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    ctx.try_log(|logger| {
+                        slog::warn!(logger, "tls handshake with {} failed: {}", peer_addr, e)
+                    });
+                    return;
+                }
+            };
+            let client_certificate = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(ClientCertificate::from_chain);
+            let service = service_fn(|mut req: Request<Body>| {
+                req.extensions_mut().insert(PeerAddr(peer_addr));
+                if let Some(client_certificate) = &client_certificate {
+                    req.extensions_mut().insert(client_certificate.clone());
+                }
+                let config = match config.read() {
+                    Ok(config) => config.clone(),
+                    Err(e) => e.into_inner().clone(),
+                };
+                handle_request(
+                    req,
+                    k8s_manager.clone(),
+                    config,
+                    request_store.clone(),
+                    ctx.clone(),
+                )
+            });
+            if let Err(e) = Http::new().serve_connection(stream, service).await {
+                ctx.try_log(|logger| slog::warn!(logger, "connection error: {}", e));
+            }
+        });
+    }
+}
+
+/// Periodically checks the TLS certificate, key and client CA files for changes,
+/// e.g. when cert-manager renews a mounted Secret.
+async fn watch_tls(mut reloader: TlsAcceptorReloader, ctx: Context) {
+    let mut interval = tokio::time::interval(CONFIG_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        match reloader.reload() {
+            Ok(true) => ctx.try_log(|logger| slog::info!(logger, "reloaded tls certificate")),
+            Ok(false) => {}
+            Err(e) => {
+                ctx.try_log(|logger| slog::error!(logger, "rejected tls certificate reload: {}", e))
+            }
+        }
+    }
+}
+
+/// Periodically refreshes the devnet gauges served by `/metrics`.
+async fn refresh_devnet_metrics(
+    k8s_manager: StacksDevnetApiK8sManager,
+    request_store: Arc<Mutex<HashMap<String, u64>>>,
+    ctx: Context,
+) {
+    let mut interval = tokio::time::interval(DEVNET_METRICS_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Could not get current time in secs")
+            .as_secs();
+        update_devnet_metrics(&k8s_manager, &request_store, now, &ctx).await;
+    }
+}
+
+/// Builds the config from the config file, environment variables and `args`. Without an
+/// explicit path, the default config file is only read if it exists.
+fn load_config(args: &[String]) -> Result<(ConfigSources, ApiConfig), ConfigError> {
+    let mut sources = ConfigSources::from_env_and_args(&env::vars().collect(), args)?;
+    if sources.config_path.is_none() {
+        let default_path = if cfg!(debug_assertions) {
+            "./Config.toml"
+        } else {
+            "/etc/config/Config.toml"
+        };
+        if Path::new(default_path).is_file() {
+            sources.config_path = Some(default_path.into());
+        }
+    }
+    let config = sources.load()?;
+    Ok((sources, config))
+}
+
+/// Reloads the config when the process receives a SIGHUP, and periodically checks the
+/// config file for changes, e.g. when a mounted ConfigMap is updated.
+async fn watch_config(mut reloader: ApiConfigReloader, ctx: Context) {
+    let mut sighup = match signal(SignalKind::hangup()) {
+        Ok(sighup) => Some(sighup),
+        Err(e) => {
+            ctx.try_log(|logger| slog::warn!(logger, "unable to listen for SIGHUP: {}", e));
+            None
+        }
+    };
+    let mut interval = tokio::time::interval(CONFIG_POLL_INTERVAL);
+    let mut last_error = None;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            Some(_) = async { sighup.as_mut()?.recv().await } => {
+                ctx.try_log(|logger| slog::info!(logger, "received SIGHUP, reloading config"));
+            }
+        }
+        let mut audit = AuditRecord::new(AuditAction::ReloadConfig, &ctx);
+        match reloader.reload() {
+            Ok(true) => {
+                ctx.try_log(|logger| slog::info!(logger, "reloaded config"));
+                last_error = None;
+            }
+            Ok(false) => continue,
+            Err(e) => {
+                ctx.try_log(|logger| slog::error!(logger, "rejected config reload: {}", e));
+                // an unreadable file fails every poll, so only the first failure is audited
+                if last_error.as_ref() == Some(&e) {
+                    continue;
+                }
+                audit.outcome = AuditOutcome::Failure;
+                audit.error_code = Some(ErrorCode::InvalidConfig);
+                audit.message = Some(e.to_string());
+                last_error = Some(e);
+            }
+        }
+        AuditLog::new(&reloader.config().audit_config).record(&audit, &ctx);
+    }
 }
 
 async fn handle_request(
+    mut request: Request<Body>,
+    k8s_manager: StacksDevnetApiK8sManager,
+    config: ApiConfig,
+    request_store: Arc<Mutex<HashMap<String, u64>>>,
+    ctx: Context,
+) -> Result<Response<Body>, Infallible> {
+    let start = Instant::now();
+    let route = get_route_template(request.uri().path());
+    let method = request.method().to_string();
+    let request_id = get_request_id(request.headers());
+    let request_id_header =
+        HeaderValue::from_str(&request_id).expect("request ids are valid header values");
+    // the header is kept on the request so that it's forwarded to proxied services
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, request_id_header.clone());
+    let ctx = ctx.with_request_id(&request_id);
+    let k8s_manager = k8s_manager.with_context(&ctx);
+    let mut audit = AuditRecord::from_request(&request, &ctx);
+    let audit_log = AuditLog::new(&config.audit_config);
+    let cx = ctx.tracer.then(|| {
+        start_span(
+            format!("{method} {route}"),
+            SpanKind::Server,
+            vec![
+                KeyValue::new("http.request.method", method.clone()),
+                KeyValue::new("http.route", route.clone()),
+                KeyValue::new("url.path", request.uri().path().to_string()),
+                KeyValue::new("request.id", request_id),
+            ],
+            &extract_trace_context(request.headers()),
+        )
+    });
+    let routed = route_request(
+        request,
+        k8s_manager,
+        config,
+        request_store,
+        &mut audit,
+        ctx.clone(),
+    );
+    let mut response = match &cx {
+        Some(cx) => routed.with_context(cx.clone()).await?,
+        None => routed.await?,
+    };
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, request_id_header);
+    let status = response.status();
+    if let Some(cx) = cx {
+        let span = cx.span();
+        span.set_attribute(KeyValue::new(
+            "http.response.status_code",
+            status.as_u16() as i64,
+        ));
+        if status.is_server_error() {
+            span.set_status(Status::error(status.to_string()));
+        }
+        span.end();
+    }
+    if let Some(mut audit) = audit {
+        audit.set_response(&response);
+        audit_log.record(&audit, &ctx);
+    }
+    metrics().observe_request(&route, &method, status.as_u16(), start.elapsed());
+    Ok(response)
+}
+
+async fn route_request(
     request: Request<Body>,
     k8s_manager: StacksDevnetApiK8sManager,
     ApiConfig {
         http_response_config,
         auth_config,
+        quota_config,
+        audit_config,
+        webhook_config,
+        ..
     }: ApiConfig,
     request_store: Arc<Mutex<HashMap<String, u64>>>,
+    audit: &mut Option<AuditRecord>,
     ctx: Context,
 ) -> Result<Response<Body>, Infallible> {
     let uri = request.uri();
@@ -90,57 +427,106 @@ async fn handle_request(
         )
     });
     let headers = request.headers().clone();
-    let responder = Responder::new(http_response_config, headers.clone(), ctx.clone()).unwrap();
+    let responder = match Responder::new(http_response_config, headers.clone(), ctx.clone()) {
+        Ok(responder) => responder,
+        // the config is validated when loaded, so this is only a safeguard
+        Err(e) => {
+            ctx.try_log(|logger| slog::error!(logger, "invalid http_response config: {}", e));
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            return Ok(response);
+        }
+    };
     if method == Method::OPTIONS {
-        return responder.ok();
+        if !responder.is_preflight() {
+            return responder.ok();
+        }
+        if !is_known_route(path) {
+            return responder.err_not_found("invalid request path".into());
+        }
+        return responder.preflight();
     }
+    let request_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Could not get current time in secs")
+        .as_secs();
     if method == Method::GET && (path == "/" || path == format!("{API_PATH}status")) {
         return handle_get_status(responder, ctx).await;
     }
-    let auth_header = auth_config
-        .auth_header
-        .unwrap_or("x-auth-request-user".to_string());
-    let user_id = match headers.get(auth_header) {
-        Some(auth_header_value) => match auth_header_value.to_str() {
-            Ok(user_id) => {
-                let user_id = user_id.replace("|", "-");
-                match auth_config.namespace_prefix {
-                    Some(mut prefix) => {
-                        prefix.push_str(&user_id);
-                        prefix
-                    }
-                    None => user_id,
-                }
-            }
-            Err(e) => {
-                let msg = format!("unable to parse auth header: {}", &e);
-                ctx.try_log(|logger| slog::warn!(logger, "{}", msg));
-                return responder.err_bad_request(msg);
-            }
-        },
-        None => return responder.err_bad_request("missing required auth header".into()),
+    if method == Method::GET && path == format!("{API_PATH}openapi.json") {
+        return handle_get_openapi(responder, &ctx).await;
+    }
+    if method == Method::GET && path == "/healthz" {
+        return handle_get_healthz(responder, &ctx).await;
+    }
+    if method == Method::GET && path == "/readyz" {
+        return handle_get_readyz(k8s_manager, &auth_config, responder, &ctx).await;
+    }
+    let client_certificate = request.extensions().get::<ClientCertificate>().cloned();
+    let user = match authenticate(&headers, client_certificate.as_ref(), &auth_config) {
+        Ok(user) => user,
+        Err(e) => {
+            ctx.try_log(|logger| {
+                slog::warn!(logger, "failed to authenticate request: {}", e.message)
+            });
+            return responder.respond_with_error(e);
+        }
     };
+    if let Some(audit) = audit.as_mut() {
+        audit.user_id = Some(user.user_id.clone());
+    }
+
+    if method == Method::GET && path == "/metrics" {
+        // the devnet gauges are labeled with user namespaces
+        if let Err(e) = user.authorize_admin() {
+            return responder.respond_with_error(e);
+        }
+        return handle_get_metrics(responder, &ctx).await;
+    }
 
-    let request_time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Could not get current time in secs")
-        .as_secs();
     if path == "/api/v1/networks" {
         return match *method {
             Method::POST => {
                 handle_new_devnet(
                     request,
-                    &user_id,
+                    &user,
+                    &quota_config,
                     k8s_manager,
                     responder,
                     request_store,
                     request_time,
+                    &Webhooks::new(&webhook_config),
+                    audit.get_or_insert_with(|| AuditRecord::new(AuditAction::CreateDevnet, &ctx)),
                     &ctx,
                 )
                 .await
             }
             _ => responder.err_method_not_allowed("network creation must be a POST request".into()),
         };
+    } else if path == "/api/v1/admin/networks" {
+        if let Err(e) = user.authorize_admin() {
+            ctx.try_log(|logger| {
+                slog::warn!(logger, "{} attempted to list all networks", user.user_id)
+            });
+            return responder.respond_with_error(e);
+        }
+        return match *method {
+            Method::GET => {
+                handle_list_devnets(uri, k8s_manager, responder, request_store, &ctx).await
+            }
+            _ => responder.err_method_not_allowed("network listing must be a GET request".into()),
+        };
+    } else if path == "/api/v1/admin/audit" {
+        if let Err(e) = user.authorize_admin() {
+            ctx.try_log(|logger| {
+                slog::warn!(logger, "{} attempted to query the audit log", user.user_id)
+            });
+            return responder.respond_with_error(e);
+        }
+        return match *method {
+            Method::GET => handle_get_audit_log(uri, &audit_config, responder, &ctx).await,
+            _ => responder.err_method_not_allowed("audit log queries must be GET requests".into()),
+        };
     } else if path.starts_with(API_PATH) {
         let path_parts = get_standardized_path_parts(uri.path());
 
@@ -152,21 +538,41 @@ async fn handle_request(
             return responder.err_bad_request("no network id provided".into());
         }
         let network = path_parts.network.unwrap();
-        if network != user_id {
-            return responder.err_bad_request("network id must match authenticated user id".into());
+        let action = match (&path_parts.subroute, method) {
+            (None, &Method::DELETE) => NetworkAction::Delete,
+            (None, _) => NetworkAction::Read,
+            (Some(subroute), _) if subroute == "wait" => NetworkAction::Wait,
+            (Some(_), _) => NetworkAction::Proxy,
+        };
+        if let Err(e) = user.authorize(&network, action) {
+            ctx.try_log(|logger| {
+                slog::warn!(
+                    logger,
+                    "{} is not authorized to access network {}",
+                    user.user_id,
+                    network
+                )
+            });
+            return responder.respond_with_error(e);
         }
+        // resources are labeled with the id of the user owning the network, which is its namespace
+        let user_id = network.clone();
 
         // verify that we have a valid namespace and the network actually exists
         let exists = match k8s_manager.check_namespace_exists(&network).await {
             Ok(exists) => exists,
             Err(e) => {
-                return responder.respond(e.code, e.message);
+                return responder.respond_with_error(e);
             }
         };
         if !exists {
-            let msg = format!("network {} does not exist", &network);
-            ctx.try_log(|logger| slog::info!(logger, "{}", msg));
-            return responder.err_not_found(msg);
+            let message = format!("network {} does not exist", &network);
+            ctx.try_log(|logger| slog::info!(logger, "{}", message));
+            return responder.respond_with_error(DevNetError {
+                message,
+                code: 404,
+                error_code: ErrorCode::DevnetNotFound,
+            });
         }
 
         // the path only contained the network path and network id,
@@ -177,10 +583,19 @@ async fn handle_request(
                     if let Ok(mut store) = request_store.lock() {
                         store.remove(&user_id);
                     }
-                    handle_delete_devnet(k8s_manager, &network, &user_id, responder).await
+                    handle_delete_devnet(
+                        k8s_manager,
+                        &network,
+                        &user_id,
+                        responder,
+                        &Webhooks::new(&webhook_config),
+                        &ctx,
+                    )
+                    .await
                 }
                 Method::GET => {
                     handle_get_devnet(
+                        uri,
                         k8s_manager,
                         &network,
                         &user_id,
@@ -208,6 +623,14 @@ async fn handle_request(
         let subroute = path_parts.subroute.unwrap();
         if subroute == "commands" {
             return responder.err_not_implemented("commands route in progress".into());
+        } else if subroute == "wait" {
+            return match *method {
+                Method::GET => {
+                    handle_wait_for_devnet(uri, k8s_manager, &network, &user_id, responder, &ctx)
+                        .await
+                }
+                _ => responder.err_method_not_allowed("can only GET at provided route".into()),
+            };
         } else {
             let remaining_path = path_parts.remainder.unwrap_or_default();
             return handle_try_proxy_service(
@@ -0,0 +1,356 @@
+use std::{
+    collections::HashMap,
+    sync::LazyLock,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use hyper::{Body, Request, Response};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+
+use crate::{DevNetError, StacksDevnetSummary};
+
+/// Buckets for the duration of API requests, in seconds.
+const REQUEST_DURATION_BUCKETS: &[f64] = &[0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+/// Buckets for the duration of deploying or deleting a devnet, in seconds.
+const DEVNET_OPERATION_DURATION_BUCKETS: &[f64] = &[0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0];
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// The process-wide metrics, served in the Prometheus exposition format at `/metrics`.
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    proxy_upstream_errors: IntCounterVec,
+    devnet_operation_duration: HistogramVec,
+    devnet_operation_failures: IntCounterVec,
+    kube_requests: IntCounterVec,
+    kube_request_errors: IntCounterVec,
+    active_devnets: IntGauge,
+    devnet_idle_seconds: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Metrics {
+        let registry = Registry::new_custom(Some("stacks_devnet_api".into()), None)
+            .expect("metrics registry prefix is valid");
+        let http_requests = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "Requests handled, by route and status",
+            ),
+            &["route", "method", "status"],
+        )
+        .expect("metric options are valid");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to handle requests, by route and status",
+            )
+            .buckets(REQUEST_DURATION_BUCKETS.to_vec()),
+            &["route", "method", "status"],
+        )
+        .expect("metric options are valid");
+        let proxy_upstream_errors = IntCounterVec::new(
+            Opts::new(
+                "proxy_upstream_errors_total",
+                "Proxied requests that failed to reach a devnet service",
+            ),
+            &["service"],
+        )
+        .expect("metric options are valid");
+        let devnet_operation_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "devnet_operation_duration_seconds",
+                "Time taken to deploy or delete a devnet",
+            )
+            .buckets(DEVNET_OPERATION_DURATION_BUCKETS.to_vec()),
+            &["operation"],
+        )
+        .expect("metric options are valid");
+        let devnet_operation_failures = IntCounterVec::new(
+            Opts::new(
+                "devnet_operation_failures_total",
+                "Failed devnet deployments and deletions, by error code",
+            ),
+            &["operation", "code"],
+        )
+        .expect("metric options are valid");
+        let kube_requests = IntCounterVec::new(
+            Opts::new(
+                "kube_requests_total",
+                "Requests made to the Kubernetes API, by verb and resource",
+            ),
+            &["verb", "resource"],
+        )
+        .expect("metric options are valid");
+        let kube_request_errors = IntCounterVec::new(
+            Opts::new(
+                "kube_request_errors_total",
+                "Requests to the Kubernetes API that failed or returned an error status",
+            ),
+            &["verb", "resource"],
+        )
+        .expect("metric options are valid");
+        let active_devnets = IntGauge::new("active_devnets", "Devnets managed by the API")
+            .expect("metric options are valid");
+        let devnet_idle_seconds = IntGaugeVec::new(
+            Opts::new(
+                "devnet_idle_seconds",
+                "Seconds since the owner of a devnet last made a request to it",
+            ),
+            &["namespace"],
+        )
+        .expect("metric options are valid");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(proxy_upstream_errors.clone()),
+            Box::new(devnet_operation_duration.clone()),
+            Box::new(devnet_operation_failures.clone()),
+            Box::new(kube_requests.clone()),
+            Box::new(kube_request_errors.clone()),
+            Box::new(active_devnets.clone()),
+            Box::new(devnet_idle_seconds.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metrics are only registered once");
+        }
+
+        Metrics {
+            registry,
+            http_requests,
+            http_request_duration,
+            proxy_upstream_errors,
+            devnet_operation_duration,
+            devnet_operation_failures,
+            kube_requests,
+            kube_request_errors,
+            active_devnets,
+            devnet_idle_seconds,
+        }
+    }
+
+    /// Encodes every metric in the Prometheus text exposition format.
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(|e| format!("failed to encode metrics: {e}"))?;
+        Ok(buffer)
+    }
+
+    /// Records a handled request. `route` must be a route template rather than the request path,
+    /// so that network ids don't end up in metric labels.
+    pub fn observe_request(&self, route: &str, method: &str, status: u16, duration: Duration) {
+        let status = status.to_string();
+        let labels = [route, get_method_label(method), status.as_str()];
+        self.http_requests.with_label_values(&labels).inc();
+        self.http_request_duration
+            .with_label_values(&labels)
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn observe_proxy_upstream_error(&self, service: &str) {
+        self.proxy_upstream_errors
+            .with_label_values(&[service])
+            .inc();
+    }
+
+    /// Records the duration of a devnet `operation` started at `start`, and its failure if any.
+    pub fn observe_devnet_operation<T>(
+        &self,
+        operation: &str,
+        start: Instant,
+        result: &Result<T, DevNetError>,
+    ) {
+        self.devnet_operation_duration
+            .with_label_values(&[operation])
+            .observe(start.elapsed().as_secs_f64());
+        if let Err(e) = result {
+            let code: &'static str = e.error_code.into();
+            self.devnet_operation_failures
+                .with_label_values(&[operation, code])
+                .inc();
+        }
+    }
+
+    fn observe_kube_request(&self, verb: &str, resource: &str, failed: bool) {
+        self.kube_requests
+            .with_label_values(&[verb, resource])
+            .inc();
+        if failed {
+            self.kube_request_errors
+                .with_label_values(&[verb, resource])
+                .inc();
+        }
+    }
+
+    /// Replaces the devnet gauges with the current devnets and the time of their owner's
+    /// last request, as tracked by the request store.
+    pub fn set_devnets(
+        &self,
+        devnets: &[StacksDevnetSummary],
+        request_store: &HashMap<String, u64>,
+        now: u64,
+    ) {
+        self.active_devnets.set(devnets.len() as i64);
+        self.devnet_idle_seconds.reset();
+        for devnet in devnets {
+            if let Some(last_request_time) = request_store.get(&devnet.namespace) {
+                self.devnet_idle_seconds
+                    .with_label_values(&[&devnet.namespace])
+                    .set(now.saturating_sub(*last_request_time) as i64);
+            }
+        }
+    }
+}
+
+/// Gets the `method` label of a request, grouping extension methods under `OTHER` so that
+/// clients can't add label values at will.
+fn get_method_label(method: &str) -> &str {
+    match method {
+        "GET" | "HEAD" | "POST" | "PUT" | "DELETE" | "CONNECT" | "OPTIONS" | "TRACE" | "PATCH" => {
+            method
+        }
+        _ => "OTHER",
+    }
+}
+
+/// Gets the kind of resource a Kubernetes API request is for from its path, e.g. `pods` for
+/// `/api/v1/namespaces/some-namespace/pods/some-pod`.
+fn get_kube_resource(path: &str) -> &str {
+    let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let parts = match parts.first() {
+        Some(&"api") => parts.get(2..),
+        Some(&"apis") => parts.get(3..),
+        _ => None,
+    }
+    .unwrap_or_default();
+    match parts {
+        ["namespaces", _, resource, ..] => *resource,
+        [resource, ..] => *resource,
+        [] => "unknown",
+    }
+}
+
+/// Wraps the service used by the kube client to count every request made to the
+/// Kubernetes API, and those that fail.
+#[derive(Clone)]
+pub struct KubeMetricsService<S> {
+    inner: S,
+}
+
+impl<S> KubeMetricsService<S> {
+    pub fn new(inner: S) -> KubeMetricsService<S> {
+        KubeMetricsService { inner }
+    }
+}
+
+impl<S, B> tower::Service<Request<Body>> for KubeMetricsService<S>
+where
+    S: tower::Service<Request<Body>, Response = Response<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let verb = request.method().as_str().to_lowercase();
+        let resource = get_kube_resource(request.uri().path()).to_string();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await;
+            let failed = match &response {
+                Ok(response) => !response.status().is_success(),
+                Err(_) => true,
+            };
+            metrics().observe_kube_request(&verb, &resource, failed);
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use super::{get_kube_resource, get_method_label, metrics};
+    use crate::StacksDevnetSummary;
+
+    #[test]
+    fn it_groups_unknown_methods() {
+        assert_eq!(get_method_label("GET"), "GET");
+        assert_eq!(get_method_label("PATCH"), "PATCH");
+        assert_eq!(get_method_label("PROPFIND"), "OTHER");
+        assert_eq!(get_method_label("get"), "OTHER");
+    }
+
+    #[test]
+    fn it_gets_kube_resource_from_path() {
+        assert_eq!(
+            get_kube_resource("/api/v1/namespaces/some-ns"),
+            "namespaces"
+        );
+        assert_eq!(
+            get_kube_resource("/api/v1/namespaces/some-ns/pods/some-pod"),
+            "pods"
+        );
+        assert_eq!(
+            get_kube_resource("/apis/apps/v1/namespaces/some-ns/deployments"),
+            "deployments"
+        );
+        assert_eq!(
+            get_kube_resource("/apis/apps/v1/statefulsets"),
+            "statefulsets"
+        );
+        assert_eq!(get_kube_resource("/version"), "unknown");
+    }
+
+    #[test]
+    fn it_encodes_metrics() {
+        metrics().observe_request(
+            "/api/v1/network/{network}",
+            "GET",
+            200,
+            Duration::from_millis(20),
+        );
+        let devnets = vec![
+            StacksDevnetSummary {
+                namespace: "metrics-ns-0".into(),
+                ..Default::default()
+            },
+            StacksDevnetSummary {
+                namespace: "metrics-ns-1".into(),
+                ..Default::default()
+            },
+        ];
+        let request_store = HashMap::from([("metrics-ns-0".to_string(), 100)]);
+        metrics().set_devnets(&devnets, &request_store, 130);
+
+        let encoded = String::from_utf8(metrics().encode().unwrap()).unwrap();
+        assert!(encoded.contains(
+            "stacks_devnet_api_http_requests_total{method=\"GET\",route=\"/api/v1/network/{network}\",status=\"200\"}"
+        ));
+        assert!(encoded.contains("stacks_devnet_api_active_devnets 2"));
+        assert!(encoded
+            .contains("stacks_devnet_api_devnet_idle_seconds{namespace=\"metrics-ns-0\"} 30"));
+        assert!(!encoded.contains("namespace=\"metrics-ns-1\""));
+    }
+}
@@ -0,0 +1,193 @@
+use utoipa::{
+    openapi::{
+        path::{Operation, PathItemType},
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+    Modify, OpenApi,
+};
+
+use crate::{
+    audit::{AuditAction, AuditOutcome, AuditRecord},
+    auth::{DEFAULT_API_KEY_HEADER, DEFAULT_AUTH_HEADER},
+    chain_status::{
+        DeploymentBatchProgress, DeploymentProgress, DeploymentTransactionProgress,
+        DeploymentTransactionStatus, DevnetChainStatus, DevnetSigner, PoxPhase, PoxStatus,
+    },
+    config::{ConfigValidationError, StacksDevnetConfig},
+    diagnostics::{DevnetComponentStatus, DevnetContainerStatus, DevnetEvent},
+    responder::{ErrorDetails, ErrorResponse},
+    routes::{
+        AuditLogResponse, DevnetMetadata, DevnetWaitResponse, HealthResponse, ReadinessCheck,
+        ReadinessChecks, ReadinessResponse, StacksDevnetInfoWithMetadata, StacksDevnetListResponse,
+        StatusResponse,
+    },
+    webhooks::{WebhookEvent, WebhookPayload},
+    ErrorCode, StacksDevnetInfoResponse, StacksDevnetSummary,
+};
+
+const PROXY_PATH: &str = "/api/v1/network/{network}/{service}/{path}";
+
+/// The OpenAPI description of every route served by `handle_request`.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::routes::handle_get_status,
+        crate::routes::handle_get_openapi,
+        crate::routes::handle_get_metrics,
+        crate::routes::handle_get_healthz,
+        crate::routes::handle_get_readyz,
+        crate::routes::handle_new_devnet,
+        crate::routes::handle_get_devnet,
+        crate::routes::handle_check_devnet,
+        crate::routes::handle_delete_devnet,
+        crate::routes::handle_wait_for_devnet,
+        crate::routes::handle_try_proxy_service,
+        crate::routes::handle_list_devnets,
+        crate::routes::handle_get_audit_log,
+    ),
+    components(schemas(
+        StatusResponse,
+        HealthResponse,
+        ReadinessResponse,
+        ReadinessChecks,
+        ReadinessCheck,
+        StacksDevnetConfig,
+        ConfigValidationError,
+        StacksDevnetInfoResponse,
+        StacksDevnetInfoWithMetadata,
+        DevnetComponentStatus,
+        DevnetContainerStatus,
+        DevnetEvent,
+        DevnetChainStatus,
+        PoxStatus,
+        PoxPhase,
+        DevnetSigner,
+        DeploymentProgress,
+        DeploymentBatchProgress,
+        DeploymentTransactionProgress,
+        DeploymentTransactionStatus,
+        DevnetMetadata,
+        DevnetWaitResponse,
+        StacksDevnetListResponse,
+        StacksDevnetSummary,
+        ErrorResponse,
+        ErrorDetails,
+        ErrorCode,
+        AuditLogResponse,
+        AuditRecord,
+        AuditAction,
+        AuditOutcome,
+        WebhookPayload,
+        WebhookEvent,
+    )),
+    modifiers(&SecuritySchemes, &ProxyOperations),
+    tags(
+        (name = "status", description = "The status of the API"),
+        (name = "networks", description = "Deploy, inspect and delete devnets, and make requests to their nodes"),
+        (name = "admin", description = "Manage the devnets of every user and query the audit log")
+    )
+)]
+pub struct ApiDoc;
+
+/// Documents the default headers used to authenticate requests. Deployments can rename them
+/// with the `auth_header` and `api_key_header` settings.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "auth_header",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(DEFAULT_AUTH_HEADER))),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(DEFAULT_API_KEY_HEADER))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Proxied requests are forwarded with their method, so the proxy route is
+/// documented for POST requests as well as GET requests.
+struct ProxyOperations;
+
+impl Modify for ProxyOperations {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let Some(path_item) = openapi.paths.paths.get_mut(PROXY_PATH) else {
+            return;
+        };
+        if let Some(operation) = path_item.operations.get(&PathItemType::Get).cloned() {
+            path_item.operations.insert(
+                PathItemType::Post,
+                Operation {
+                    operation_id: Some("proxy_post".into()),
+                    ..operation
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use super::ApiDoc;
+
+    #[test]
+    fn it_describes_every_route() {
+        let openapi = ApiDoc::openapi();
+        let mut routes = vec![];
+        for (path, path_item) in openapi.paths.paths.iter() {
+            for method in path_item.operations.keys() {
+                routes.push(format!("{path} {method:?}"));
+            }
+        }
+        routes.sort();
+        assert_eq!(
+            routes,
+            vec![
+                "/api/v1/admin/audit Get",
+                "/api/v1/admin/networks Get",
+                "/api/v1/network/{network} Delete",
+                "/api/v1/network/{network} Get",
+                "/api/v1/network/{network} Head",
+                "/api/v1/network/{network}/wait Get",
+                "/api/v1/network/{network}/{service}/{path} Get",
+                "/api/v1/network/{network}/{service}/{path} Post",
+                "/api/v1/networks Post",
+                "/api/v1/openapi.json Get",
+                "/api/v1/status Get",
+                "/healthz Get",
+                "/metrics Get",
+                "/readyz Get",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_describes_error_and_config_schemas() {
+        let openapi = ApiDoc::openapi();
+        let schemas = openapi.components.unwrap().schemas;
+        for schema in [
+            "StacksDevnetConfig",
+            "StacksDevnetInfoWithMetadata",
+            "ErrorResponse",
+            "ErrorCode",
+        ] {
+            assert!(schemas.contains_key(schema), "missing schema {schema}");
+        }
+        let error_code = serde_json::to_value(&schemas["ErrorCode"]).unwrap();
+        let codes = error_code["enum"].as_array().unwrap();
+        assert!(codes.contains(&"DEVNET_ALREADY_EXISTS".into()));
+    }
+}
@@ -0,0 +1,477 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use hiro_system_kit::slog;
+use k8s_openapi::{
+    api::apps::v1::{Deployment, StatefulSet},
+    apimachinery::pkg::apis::meta::v1::OwnerReference,
+};
+use kube::{
+    api::{Api, ListParams, Patch, PatchParams},
+    runtime::{
+        controller::{Action, Controller},
+        watcher,
+    },
+    Resource, ResourceExt,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::{
+    config::{StacksDevnet, StacksDevnetConfig},
+    routes::DevnetWaitParams,
+    DevNetError, DevnetInfoIncludes, ErrorCode, StacksDevnetApiK8sManager,
+    StacksDevnetInfoResponse, StacksDevnetSummary, MANAGED_BY, MANAGED_BY_SELECTOR, USER_SELECTOR,
+};
+
+const PHASE_PENDING: &str = "Pending";
+const PHASE_RUNNING: &str = "Running";
+const PHASE_FAILED: &str = "Failed";
+
+/// Running devnets are only checked on occasionally, to notice components that fail.
+const RUNNING_REQUEUE_INTERVAL: Duration = Duration::from_secs(300);
+const PENDING_REQUEUE_INTERVAL: Duration = Duration::from_secs(15);
+const ERROR_REQUEUE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The status of a `StacksDevnet`, written by the operator.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, ToSchema)]
+pub struct StacksDevnetStatus {
+    /// `Pending`, `Running` or `Failed`.
+    pub phase: Option<String>,
+    /// Why the devnet is pending or failed.
+    pub message: Option<String>,
+    /// The generation of the spec the devnet was last deployed from.
+    pub observed_generation: Option<i64>,
+    /// The phase of each component's pod, keyed by component.
+    #[serde(default)]
+    pub components: BTreeMap<String, String>,
+}
+
+impl StacksDevnetStatus {
+    /// A devnet that couldn't be deployed from the given generation of its spec, which
+    /// is only retried once the spec changes.
+    pub fn failed_to_deploy(generation: Option<i64>, message: String) -> StacksDevnetStatus {
+        StacksDevnetStatus {
+            phase: Some(PHASE_FAILED.into()),
+            message: Some(message),
+            observed_generation: generation,
+            ..Default::default()
+        }
+    }
+
+    /// A devnet that's deployed but whose components haven't all been created yet.
+    pub fn pending(generation: Option<i64>, message: String) -> StacksDevnetStatus {
+        StacksDevnetStatus {
+            phase: Some(PHASE_PENDING.into()),
+            message: Some(message),
+            observed_generation: generation,
+            ..Default::default()
+        }
+    }
+
+    /// The devnet is running once every component is running and ready, and failed as
+    /// soon as any component fails.
+    pub fn from_devnet_info(
+        generation: Option<i64>,
+        devnet_info: &StacksDevnetInfoResponse,
+    ) -> StacksDevnetStatus {
+        let components: BTreeMap<String, String> = devnet_info
+            .components
+            .iter()
+            .filter_map(|(component, status)| Some((component.clone(), status.phase.clone()?)))
+            .collect();
+        let failed: Vec<&str> = components
+            .iter()
+            .filter(|(_, phase)| phase.as_str() == PHASE_FAILED)
+            .map(|(component, _)| component.as_str())
+            .collect();
+        let (phase, message) = if !failed.is_empty() {
+            (PHASE_FAILED, Some(format!("{} failed", failed.join(", "))))
+        } else {
+            let params = DevnetWaitParams {
+                min_stacks_height: None,
+                min_burn_height: None,
+                timeout: Duration::ZERO,
+            };
+            let unmet = params.get_unmet_conditions(devnet_info);
+            match unmet.is_empty() {
+                true => (PHASE_RUNNING, None),
+                false => (PHASE_PENDING, Some(unmet.join(", "))),
+            }
+        };
+        StacksDevnetStatus {
+            phase: Some(phase.into()),
+            message,
+            observed_generation: generation,
+            components,
+        }
+    }
+
+    fn is_failed_to_deploy(&self, generation: Option<i64>) -> bool {
+        self.observed_generation == generation
+            && self.phase.as_deref() == Some(PHASE_FAILED)
+            && self.components.is_empty()
+    }
+
+    fn get_requeue_action(&self) -> Action {
+        match self.phase.as_deref() {
+            Some(PHASE_RUNNING) => Action::requeue(RUNNING_REQUEUE_INTERVAL),
+            _ => Action::requeue(PENDING_REQUEUE_INTERVAL),
+        }
+    }
+}
+
+impl StacksDevnetApiK8sManager {
+    /// Creates and deletes devnets through `StacksDevnet` resources, leaving their
+    /// deployment to the operator.
+    pub fn with_operator(&self, enabled: bool) -> StacksDevnetApiK8sManager {
+        StacksDevnetApiK8sManager {
+            operator: enabled,
+            ..self.clone()
+        }
+    }
+
+    pub fn uses_operator(&self) -> bool {
+        self.operator
+    }
+
+    /// Sets `owner` as the owner of every resource deployed.
+    pub fn with_owner(&self, owner: Option<OwnerReference>) -> StacksDevnetApiK8sManager {
+        StacksDevnetApiK8sManager {
+            owner_reference: owner,
+            ..self.clone()
+        }
+    }
+
+    /// Applies a `StacksDevnet` for `config`, named after its namespace. Applying the same
+    /// config again is a no-op, while a different config is a conflict, as for devnets
+    /// deployed directly.
+    pub async fn create_stacks_devnet(
+        &self,
+        config: StacksDevnetConfig,
+    ) -> Result<(), DevNetError> {
+        let namespace = config.namespace.clone();
+        self.ensure_namespace_exists(&namespace).await?;
+
+        let api: Api<StacksDevnet> = Api::namespaced(self.client.to_owned(), &namespace);
+        let existing = api
+            .get_opt(&namespace)
+            .await
+            .map_err(|e| get_kube_error(e, &format!("failed to get StacksDevnet {namespace}")))?;
+        let conflict = match &existing {
+            Some(existing) => {
+                serde_json::to_value(&existing.spec).ok() != serde_json::to_value(&config).ok()
+            }
+            // devnets deployed before operator mode was enabled have no `StacksDevnet`
+            None => {
+                self.check_any_devnet_assets_exist(&namespace, &namespace)
+                    .await?
+            }
+        };
+        if conflict {
+            let message = format!(
+                "cannot create devnet because assets of a different config already exist NAMESPACE: {namespace}"
+            );
+            self.ctx
+                .try_log(|logger| slog::warn!(logger, "{}", message));
+            return Err(DevNetError {
+                message,
+                code: 409,
+                error_code: ErrorCode::DevnetAlreadyExists,
+            });
+        }
+
+        let mut devnet = StacksDevnet::new(&namespace, config);
+        devnet.metadata.namespace = Some(namespace.clone());
+        devnet.metadata.labels = Some(BTreeMap::from([
+            (MANAGED_BY_SELECTOR.into(), MANAGED_BY.into()),
+            (USER_SELECTOR.into(), namespace.clone()),
+        ]));
+        self.deploy_resource(&namespace, devnet, "stacksdevnet")
+            .await
+    }
+
+    /// Deletes the `StacksDevnet` of a namespace, if there is one. Returns whether it
+    /// existed.
+    pub async fn delete_stacks_devnet(&self, namespace: &str) -> Result<bool, DevNetError> {
+        let api: Api<StacksDevnet> = Api::namespaced(self.client.to_owned(), namespace);
+        let existing = api
+            .get_opt(namespace)
+            .await
+            .map_err(|e| get_kube_error(e, &format!("failed to get StacksDevnet {namespace}")))?;
+        if existing.is_none() {
+            return Ok(false);
+        }
+        self.delete_resource::<StacksDevnet>(namespace, namespace)
+            .await?;
+        Ok(true)
+    }
+
+    /// Whether the namespace has a `StacksDevnet`, whose devnet may not be deployed yet.
+    /// Always false when the operator isn't enabled.
+    pub async fn stacks_devnet_exists(&self, namespace: &str) -> Result<bool, DevNetError> {
+        if !self.operator {
+            return Ok(false);
+        }
+        let api: Api<StacksDevnet> = Api::namespaced(self.client.to_owned(), namespace);
+        api.get_opt(namespace)
+            .await
+            .map(|devnet| devnet.is_some())
+            .map_err(|e| get_kube_error(e, &format!("failed to get StacksDevnet {namespace}")))
+    }
+
+    /// Summarizes every `StacksDevnet`, without the status of its pods, so that devnets the
+    /// operator hasn't deployed yet count towards quotas. Always empty when the operator
+    /// isn't enabled.
+    pub async fn get_stacks_devnet_summaries(
+        &self,
+    ) -> Result<Vec<StacksDevnetSummary>, DevNetError> {
+        if !self.operator {
+            return Ok(vec![]);
+        }
+        let api: Api<StacksDevnet> = Api::all(self.client.to_owned());
+        let devnets = api
+            .list(&ListParams::default())
+            .await
+            .map_err(|e| get_kube_error(e, "failed to list StacksDevnets"))?;
+        Ok(devnets
+            .into_iter()
+            .map(|devnet| StacksDevnetSummary {
+                namespace: devnet.namespace().unwrap_or_default(),
+                owner: devnet.labels().get(USER_SELECTOR).cloned(),
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    async fn patch_stacks_devnet_status(
+        &self,
+        devnet: &StacksDevnet,
+        status: StacksDevnetStatus,
+    ) -> Result<(), DevNetError> {
+        if devnet.status.as_ref() == Some(&status) {
+            return Ok(());
+        }
+        let name = devnet.name_any();
+        let namespace = devnet.namespace().unwrap_or_default();
+        let api: Api<StacksDevnet> = Api::namespaced(self.client.to_owned(), &namespace);
+        api.patch_status(
+            &name,
+            &PatchParams::default(),
+            &Patch::Merge(json!({ "status": status })),
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            get_kube_error(
+                e,
+                &format!("failed to update status of StacksDevnet {name}, NAMESPACE: {namespace}"),
+            )
+        })
+    }
+}
+
+fn get_kube_error(e: kube::Error, context: &str) -> DevNetError {
+    let (message, code) = match e {
+        kube::Error::Api(api_error) => (api_error.message, api_error.code),
+        e => (e.to_string(), 500),
+    };
+    DevNetError {
+        message: format!("{context}, ERROR: {message}"),
+        code,
+        error_code: ErrorCode::KubernetesError,
+    }
+}
+
+/// Reconciles `StacksDevnet` resources in every namespace until the watch stream ends:
+/// each devnet is deployed when its spec changes, owned by its `StacksDevnet` so deleting
+/// it deletes the devnet, and its status tracks the devnet's components.
+pub async fn run_operator(k8s_manager: StacksDevnetApiK8sManager) {
+    let client = k8s_manager.client.clone();
+    let ctx = k8s_manager.ctx.clone();
+    ctx.try_log(|logger| slog::info!(logger, "starting StacksDevnet operator"));
+    Controller::new(
+        Api::<StacksDevnet>::all(client.clone()),
+        watcher::Config::default(),
+    )
+    .owns(
+        Api::<Deployment>::all(client.clone()),
+        watcher::Config::default(),
+    )
+    .owns(Api::<StatefulSet>::all(client), watcher::Config::default())
+    .run(reconcile, error_policy, Arc::new(k8s_manager))
+    .for_each(|result| {
+        if let Err(e) = result {
+            ctx.try_log(|logger| slog::warn!(logger, "failed to reconcile StacksDevnet: {}", e));
+        }
+        futures::future::ready(())
+    })
+    .await;
+}
+
+async fn reconcile(
+    devnet: Arc<StacksDevnet>,
+    k8s_manager: Arc<StacksDevnetApiK8sManager>,
+) -> Result<Action, DevNetError> {
+    let namespace = devnet.namespace().unwrap_or_default();
+    let generation = devnet.metadata.generation;
+    let current_status = devnet.status.clone().unwrap_or_default();
+    if current_status.is_failed_to_deploy(generation) {
+        return Ok(Action::await_change());
+    }
+
+    if current_status.observed_generation != generation {
+        k8s_manager.ctx.try_log(|logger| {
+            slog::info!(
+                logger,
+                "deploying StacksDevnet {}, NAMESPACE: {}, GENERATION: {:?}",
+                devnet.name_any(),
+                namespace,
+                generation
+            )
+        });
+        // the devnet belongs to the namespace of its `StacksDevnet`, which the spec must name
+        let deployed = match devnet
+            .spec
+            .clone()
+            .to_validated_config(&namespace, &k8s_manager.ctx)
+        {
+            Ok(config) => {
+                k8s_manager
+                    .with_owner(devnet.controller_owner_ref(&()))
+                    .deploy_devnet(config)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match deployed {
+            Ok(_) => {}
+            // errors of the cluster are retried, while errors of the spec wait for it to change
+            Err(e) if e.code >= 500 => return Err(e),
+            Err(e) => {
+                let status = StacksDevnetStatus::failed_to_deploy(generation, e.message);
+                k8s_manager
+                    .patch_stacks_devnet_status(&devnet, status)
+                    .await?;
+                return Ok(Action::await_change());
+            }
+        }
+    }
+
+    let status = match k8s_manager
+        .get_devnet_info(&namespace, &namespace, DevnetInfoIncludes::default())
+        .await
+    {
+        Ok(devnet_info) => StacksDevnetStatus::from_devnet_info(generation, &devnet_info),
+        Err(e) if e.code == 404 => StacksDevnetStatus::pending(generation, e.message),
+        Err(e) => return Err(e),
+    };
+    let action = status.get_requeue_action();
+    k8s_manager
+        .patch_stacks_devnet_status(&devnet, status)
+        .await?;
+    Ok(action)
+}
+
+fn error_policy(
+    devnet: Arc<StacksDevnet>,
+    error: &DevNetError,
+    k8s_manager: Arc<StacksDevnetApiK8sManager>,
+) -> Action {
+    k8s_manager.ctx.try_log(|logger| {
+        slog::warn!(
+            logger,
+            "failed to reconcile StacksDevnet {}, NAMESPACE: {}, ERROR: {}",
+            devnet.name_any(),
+            devnet.namespace().unwrap_or_default(),
+            error
+        )
+    });
+    Action::requeue(ERROR_REQUEUE_INTERVAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::DevnetComponentStatus;
+
+    use super::*;
+
+    fn get_devnet_info(phases: &[(&str, Option<&str>, bool)]) -> StacksDevnetInfoResponse {
+        StacksDevnetInfoResponse {
+            components: phases
+                .iter()
+                .map(|(component, phase, ready)| {
+                    (
+                        component.to_string(),
+                        DevnetComponentStatus {
+                            phase: phase.map(|p| p.to_string()),
+                            ready: *ready,
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            stacks_chain_tip: None,
+            bitcoin_chain_tip: None,
+            chain: None,
+            deployment: None,
+        }
+    }
+
+    #[test]
+    fn it_reports_running_devnets() {
+        let devnet_info = get_devnet_info(&[
+            ("bitcoind-chain-coordinator", Some("Running"), true),
+            ("stacks-blockchain", Some("Running"), true),
+        ]);
+        let status = StacksDevnetStatus::from_devnet_info(Some(2), &devnet_info);
+        assert_eq!(status.phase.as_deref(), Some("Running"));
+        assert_eq!(status.message, None);
+        assert_eq!(status.observed_generation, Some(2));
+        assert_eq!(
+            status
+                .components
+                .get("stacks-blockchain")
+                .map(String::as_str),
+            Some("Running")
+        );
+    }
+
+    #[test]
+    fn it_reports_pending_devnets() {
+        let devnet_info = get_devnet_info(&[
+            ("bitcoind-chain-coordinator", Some("Running"), true),
+            ("stacks-blockchain", Some("Running"), false),
+            ("stacks-signer-0", None, false),
+        ]);
+        let status = StacksDevnetStatus::from_devnet_info(Some(1), &devnet_info);
+        assert_eq!(status.phase.as_deref(), Some("Pending"));
+        assert_eq!(
+            status.message.as_deref(),
+            Some("stacks-blockchain is not ready, stacks-signer-0 has not been created")
+        );
+        // components without a pod have no phase to report
+        assert!(!status.components.contains_key("stacks-signer-0"));
+    }
+
+    #[test]
+    fn it_reports_failed_devnets() {
+        let devnet_info = get_devnet_info(&[
+            ("bitcoind-chain-coordinator", Some("Running"), true),
+            ("stacks-blockchain", Some("Failed"), false),
+        ]);
+        let status = StacksDevnetStatus::from_devnet_info(Some(1), &devnet_info);
+        assert_eq!(status.phase.as_deref(), Some("Failed"));
+        assert_eq!(status.message.as_deref(), Some("stacks-blockchain failed"));
+        // a failed component isn't a failed deployment, so the devnet keeps being checked on
+        assert!(!status.is_failed_to_deploy(Some(1)));
+    }
+
+    #[test]
+    fn it_waits_for_spec_changes_after_failed_deployments() {
+        let status = StacksDevnetStatus::failed_to_deploy(Some(3), "invalid config".into());
+        assert!(status.is_failed_to_deploy(Some(3)));
+        assert!(!status.is_failed_to_deploy(Some(4)));
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    fs::{self, File},
+    io::{self, Cursor, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+
+const PROJECT_MANIFEST_FILE: &str = "Clarinet.toml";
+/// Upper bound on the total number of bytes written while unpacking a project archive,
+/// to avoid filling the pod's disk with a maliciously compressed payload.
+const MAX_UNPACKED_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectArchiveFormat {
+    TarGz,
+    Zip,
+}
+
+impl ProjectArchiveFormat {
+    pub fn from_content_type(content_type: &str) -> Option<ProjectArchiveFormat> {
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+        match mime {
+            "application/gzip"
+            | "application/x-gzip"
+            | "application/x-tar+gzip"
+            | "application/x-compressed-tar" => Some(ProjectArchiveFormat::TarGz),
+            "application/zip" | "application/x-zip-compressed" => Some(ProjectArchiveFormat::Zip),
+            _ => None,
+        }
+    }
+}
+
+/// Unpacks a Clarinet project archive into `destination` and returns the path of the
+/// project root, i.e. the directory containing `Clarinet.toml`. The manifest may be at the
+/// root of the archive or inside a single top-level folder.
+pub fn unpack_project_archive(
+    archive: &[u8],
+    format: ProjectArchiveFormat,
+    destination: &Path,
+) -> Result<PathBuf, String> {
+    let mut remaining = MAX_UNPACKED_SIZE;
+    match format {
+        ProjectArchiveFormat::TarGz => unpack_tar_gz(archive, destination, &mut remaining)?,
+        ProjectArchiveFormat::Zip => unpack_zip(archive, destination, &mut remaining)?,
+    };
+    find_project_root(destination)
+}
+
+fn unpack_tar_gz(archive: &[u8], destination: &Path, remaining: &mut u64) -> Result<(), String> {
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("failed to read project archive: {e}"))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("failed to read project archive: {e}"))?;
+        let entry_type = entry.header().entry_type();
+        if !(entry_type.is_file() || entry_type.is_dir()) {
+            // links and special files have no place in a Clarinet project
+            continue;
+        }
+        let path = entry
+            .path()
+            .map_err(|e| format!("failed to read project archive: {e}"))?
+            .into_owned();
+        let target = get_safe_target_path(destination, &path)?;
+        if entry_type.is_dir() {
+            create_dir(&target)?;
+            continue;
+        }
+        write_file(&target, &mut entry, remaining)?;
+    }
+    Ok(())
+}
+
+fn unpack_zip(archive: &[u8], destination: &Path, remaining: &mut u64) -> Result<(), String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive))
+        .map_err(|e| format!("failed to read project archive: {e}"))?;
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| format!("failed to read project archive: {e}"))?;
+        let path = PathBuf::from(file.name());
+        let target = get_safe_target_path(destination, &path)?;
+        if file.is_dir() {
+            create_dir(&target)?;
+            continue;
+        }
+        write_file(&target, &mut file, remaining)?;
+    }
+    Ok(())
+}
+
+fn get_safe_target_path(destination: &Path, path: &Path) -> Result<PathBuf, String> {
+    let mut target = destination.to_path_buf();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => target.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(format!(
+                    "project archive contains invalid path {}",
+                    path.display()
+                ))
+            }
+        }
+    }
+    Ok(target)
+}
+
+fn create_dir(target: &Path) -> Result<(), String> {
+    fs::create_dir_all(target)
+        .map_err(|e| format!("failed to unpack project archive {}: {e}", target.display()))
+}
+
+/// Writes an entry, counting the bytes actually written against the `remaining` budget,
+/// since the sizes declared by an archive's headers can't be trusted.
+fn write_file<R: Read>(target: &Path, reader: &mut R, remaining: &mut u64) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        create_dir(parent)?;
+    }
+    let mut file = File::create(target)
+        .map_err(|e| format!("failed to unpack project archive {}: {e}", target.display()))?;
+    let written = io::copy(&mut reader.take(*remaining + 1), &mut file)
+        .map_err(|e| format!("failed to unpack project archive {}: {e}", target.display()))?;
+    if written > *remaining {
+        return Err(format!(
+            "project archive exceeds the maximum unpacked size of {MAX_UNPACKED_SIZE} bytes"
+        ));
+    }
+    *remaining -= written;
+    Ok(())
+}
+
+fn find_project_root(destination: &Path) -> Result<PathBuf, String> {
+    if destination.join(PROJECT_MANIFEST_FILE).is_file() {
+        return Ok(destination.to_path_buf());
+    }
+    let read_dir = fs::read_dir(destination)
+        .map_err(|e| format!("failed to read unpacked project archive: {e}"))?;
+    let candidates: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.join(PROJECT_MANIFEST_FILE).is_file())
+        .collect();
+    match candidates.len() {
+        1 => Ok(candidates[0].to_owned()),
+        0 => Err(format!(
+            "project archive does not contain a {PROJECT_MANIFEST_FILE}"
+        )),
+        _ => Err(format!(
+            "project archive contains more than one {PROJECT_MANIFEST_FILE}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, path::Path};
+
+    use flate2::{write::GzEncoder, Compression};
+    use test_case::test_case;
+
+    use super::{unpack_project_archive, unpack_tar_gz, unpack_zip, ProjectArchiveFormat};
+
+    fn build_tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            // `set_path` rejects `..`, so write the raw name to emulate a hostile archive
+            let name = &mut header.as_old_mut().name;
+            name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_cksum();
+            builder.append(&header, contents.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn build_zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+        for (path, contents) in files {
+            writer
+                .start_file(*path, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test_case("application/gzip" => Some(ProjectArchiveFormat::TarGz); "for application/gzip")]
+    #[test_case("application/x-gzip" => Some(ProjectArchiveFormat::TarGz); "for application/x-gzip")]
+    #[test_case("application/zip" => Some(ProjectArchiveFormat::Zip); "for application/zip")]
+    #[test_case("application/zip; charset=binary" => Some(ProjectArchiveFormat::Zip); "ignoring parameters")]
+    #[test_case("application/json" => None; "for application/json")]
+    fn it_parses_archive_content_type(content_type: &str) -> Option<ProjectArchiveFormat> {
+        ProjectArchiveFormat::from_content_type(content_type)
+    }
+
+    #[test_case(ProjectArchiveFormat::TarGz; "for tar.gz archives")]
+    #[test_case(ProjectArchiveFormat::Zip; "for zip archives")]
+    fn it_finds_project_root_in_top_level_folder(format: ProjectArchiveFormat) {
+        let files = [
+            ("my-project/Clarinet.toml", "[project]"),
+            ("my-project/contracts/nested/px.clar", "(ok true)"),
+        ];
+        let archive = match format {
+            ProjectArchiveFormat::TarGz => build_tar_gz(&files),
+            ProjectArchiveFormat::Zip => build_zip(&files),
+        };
+        let destination = tempfile::tempdir().unwrap();
+        let root = unpack_project_archive(&archive, format, destination.path()).unwrap();
+        assert_eq!(root, destination.path().join("my-project"));
+        assert!(Path::new(&root.join("contracts/nested/px.clar")).is_file());
+    }
+
+    #[test]
+    fn it_rejects_archive_paths_outside_destination() {
+        let archive = build_tar_gz(&[("../Clarinet.toml", "[project]")]);
+        let destination = tempfile::tempdir().unwrap();
+        let err = unpack_project_archive(&archive, ProjectArchiveFormat::TarGz, destination.path())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "project archive contains invalid path ../Clarinet.toml"
+        );
+    }
+
+    #[test_case(ProjectArchiveFormat::TarGz; "for tar.gz archives")]
+    #[test_case(ProjectArchiveFormat::Zip; "for zip archives")]
+    fn it_limits_the_total_unpacked_size(format: ProjectArchiveFormat) {
+        let contents = "(ok true)".repeat(10);
+        let files = [
+            ("Clarinet.toml", "[project]"),
+            ("contracts/a.clar", contents.as_str()),
+            ("contracts/b.clar", contents.as_str()),
+        ];
+        let archive = match format {
+            ProjectArchiveFormat::TarGz => build_tar_gz(&files),
+            ProjectArchiveFormat::Zip => build_zip(&files),
+        };
+        let unpack = |budget: u64| {
+            let destination = tempfile::tempdir().unwrap();
+            let mut remaining = budget;
+            let result = match format {
+                ProjectArchiveFormat::TarGz => {
+                    unpack_tar_gz(&archive, destination.path(), &mut remaining)
+                }
+                ProjectArchiveFormat::Zip => {
+                    unpack_zip(&archive, destination.path(), &mut remaining)
+                }
+            };
+            result.map(|_| remaining)
+        };
+        let total = 9 + 2 * contents.len() as u64;
+        assert_eq!(unpack(total), Ok(0));
+        assert_eq!(
+            unpack(total - 1),
+            Err("project archive exceeds the maximum unpacked size of 67108864 bytes".to_string())
+        );
+    }
+
+    #[test]
+    fn it_requires_project_manifest() {
+        let archive = build_zip(&[("contracts/px.clar", "(ok true)")]);
+        let destination = tempfile::tempdir().unwrap();
+        let err = unpack_project_archive(&archive, ProjectArchiveFormat::Zip, destination.path())
+            .unwrap_err();
+        assert_eq!(err, "project archive does not contain a Clarinet.toml");
+    }
+}
@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::{
+    api_config::{ApiKeyRole, QuotaConfig},
+    auth::AuthenticatedUser,
+    StacksDevnetSummary,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuotaExceeded {
+    pub message: String,
+    /// `cluster`, `user` or the `namespace_prefix` of the exceeded group quota.
+    pub quota: String,
+    pub limit: usize,
+    pub usage: usize,
+    #[serde(skip)]
+    pub code: u16,
+}
+
+/// One lock per user, held from a user's quota check until their devnet is created.
+static USER_LOCKS: LazyLock<Mutex<HashMap<String, Arc<AsyncMutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+/// Waits for the other devnet creations of `user_id` to finish, so that concurrent requests
+/// can't all pass the per-user quota before any of their devnets exist. Devnets created by
+/// different users still race for the cluster and group quotas.
+pub async fn lock_user_quota(user_id: &str) -> OwnedMutexGuard<()> {
+    let lock = {
+        let mut locks = match USER_LOCKS.lock() {
+            Ok(locks) => locks,
+            Err(e) => e.into_inner(),
+        };
+        // locks nobody holds or waits for are dropped
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(user_id.to_string()).or_default().clone()
+    };
+    lock.lock_owned().await
+}
+
+impl QuotaConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.max_devnets.is_some()
+            || self.max_devnets_per_user.is_some()
+            || self
+                .groups
+                .as_ref()
+                .is_some_and(|groups| !groups.is_empty())
+    }
+
+    /// Checks whether `user` may create one more devnet in `namespace`, given the devnets
+    /// currently running. Running out of cluster capacity is reported with a 429, since it
+    /// may resolve on its own, while exceeding a user or group quota is reported with a 403.
+    pub fn check(
+        &self,
+        user: &AuthenticatedUser,
+        namespace: &str,
+        devnets: &[StacksDevnetSummary],
+    ) -> Result<(), QuotaExceeded> {
+        if let Some(limit) = self.max_devnets {
+            check_limit(
+                "cluster",
+                "devnets in the cluster",
+                limit,
+                devnets.len(),
+                429,
+            )?;
+        }
+
+        if let Some(limit) = self.max_devnets_per_user {
+            let usage = match user.role {
+                ApiKeyRole::User => Some(
+                    devnets
+                        .iter()
+                        .filter(|d| d.owner.as_deref() == Some(user.user_id.as_str()))
+                        .count(),
+                ),
+                ApiKeyRole::Ci => {
+                    let prefix = user.network_prefix.as_deref().unwrap_or_default();
+                    Some(
+                        devnets
+                            .iter()
+                            .filter(|d| d.namespace.starts_with(prefix))
+                            .count(),
+                    )
+                }
+                ApiKeyRole::Admin => None,
+            };
+            if let Some(usage) = usage {
+                let description = format!("devnets for {}", user.user_id);
+                check_limit("user", &description, limit, usage, 403)?;
+            }
+        }
+
+        for group in self.groups.iter().flatten() {
+            if !namespace.starts_with(&group.namespace_prefix) {
+                continue;
+            }
+            let usage = devnets
+                .iter()
+                .filter(|d| d.namespace.starts_with(&group.namespace_prefix))
+                .count();
+            let description = format!("devnets in namespaces prefixed {}", group.namespace_prefix);
+            check_limit(
+                &group.namespace_prefix,
+                &description,
+                group.max_devnets,
+                usage,
+                403,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn check_limit(
+    quota: &str,
+    description: &str,
+    limit: usize,
+    usage: usize,
+    code: u16,
+) -> Result<(), QuotaExceeded> {
+    if usage < limit {
+        return Ok(());
+    }
+    Err(QuotaExceeded {
+        message: format!(
+            "cannot create devnet: {usage} of {limit} allowed {description} are running"
+        ),
+        quota: quota.to_string(),
+        limit,
+        usage,
+        code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{lock_user_quota, QuotaExceeded};
+    use crate::{
+        api_config::{ApiKeyRole, QuotaConfig, QuotaGroupConfig},
+        auth::AuthenticatedUser,
+        StacksDevnetSummary,
+    };
+
+    fn get_devnets(namespaces: &[&str]) -> Vec<StacksDevnetSummary> {
+        namespaces
+            .iter()
+            .map(|namespace| StacksDevnetSummary {
+                namespace: namespace.to_string(),
+                owner: Some(namespace.to_string()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn get_user(user_id: &str, role: ApiKeyRole) -> AuthenticatedUser {
+        AuthenticatedUser {
+            user_id: user_id.into(),
+            role,
+            network_prefix: Some("ci-".into()),
+        }
+    }
+
+    #[test]
+    fn it_enforces_cluster_quota() {
+        let quota_config = QuotaConfig {
+            max_devnets: Some(2),
+            ..Default::default()
+        };
+        let user = get_user("platform-a", ApiKeyRole::User);
+        assert!(quota_config
+            .check(&user, "platform-a", &get_devnets(&["platform-b"]))
+            .is_ok());
+        let err = quota_config
+            .check(&user, "platform-a", &get_devnets(&["platform-b", "ci-1"]))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            QuotaExceeded {
+                message: "cannot create devnet: 2 of 2 allowed devnets in the cluster are running"
+                    .into(),
+                quota: "cluster".into(),
+                limit: 2,
+                usage: 2,
+                code: 429,
+            }
+        );
+    }
+
+    #[test]
+    fn it_enforces_user_quota_by_role() {
+        let quota_config = QuotaConfig {
+            max_devnets_per_user: Some(2),
+            ..Default::default()
+        };
+        let devnets = get_devnets(&["ci-1", "ci-2", "platform-a"]);
+
+        let ci = get_user("pipeline", ApiKeyRole::Ci);
+        let err = quota_config.check(&ci, "ci-3", &devnets).unwrap_err();
+        assert_eq!((err.quota.as_str(), err.usage, err.code), ("user", 2, 403));
+
+        let user = get_user("platform-b", ApiKeyRole::User);
+        assert!(quota_config.check(&user, "platform-b", &devnets).is_ok());
+
+        let admin = get_user("support", ApiKeyRole::Admin);
+        assert!(quota_config.check(&admin, "ci-3", &devnets).is_ok());
+    }
+
+    #[test]
+    fn it_counts_the_existing_devnet_of_a_user() {
+        let quota_config = QuotaConfig {
+            max_devnets_per_user: Some(1),
+            ..Default::default()
+        };
+        let user = get_user("platform-a", ApiKeyRole::User);
+        let err = quota_config
+            .check(&user, "platform-a", &get_devnets(&["platform-a", "ci-1"]))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            QuotaExceeded {
+                message: "cannot create devnet: 1 of 1 allowed devnets for platform-a are running"
+                    .into(),
+                quota: "user".into(),
+                limit: 1,
+                usage: 1,
+                code: 403,
+            }
+        );
+        assert!(quota_config
+            .check(&user, "platform-a", &get_devnets(&["ci-1"]))
+            .is_ok());
+    }
+
+    #[test]
+    fn it_enforces_matching_group_quotas() {
+        let quota_config = QuotaConfig {
+            groups: Some(vec![QuotaGroupConfig {
+                namespace_prefix: "platform-acme-".into(),
+                max_devnets: 1,
+            }]),
+            ..Default::default()
+        };
+        let devnets = get_devnets(&["platform-acme-a", "platform-b"]);
+        let user = get_user("platform-acme-c", ApiKeyRole::User);
+        let err = quota_config
+            .check(&user, "platform-acme-c", &devnets)
+            .unwrap_err();
+        assert_eq!(
+            (err.quota.as_str(), err.limit, err.usage),
+            ("platform-acme-", 1, 1)
+        );
+
+        let user = get_user("platform-c", ApiKeyRole::User);
+        assert!(quota_config.check(&user, "platform-c", &devnets).is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_locks_quota_checks_per_user() {
+        let guard = lock_user_quota("platform-lock-a").await;
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(50),
+            lock_user_quota("platform-lock-a"),
+        )
+        .await;
+        assert!(waiting.is_err());
+        let _other = lock_user_quota("platform-lock-b").await;
+
+        drop(guard);
+        let _guard = lock_user_quota("platform-lock-a").await;
+    }
+}
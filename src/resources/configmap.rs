@@ -31,3 +31,41 @@ impl fmt::Display for StacksDevnetConfigmap {
         }
     }
 }
+
+/// ConfigMaps are capped at 1MiB including metadata, so contract data is split across
+/// several ConfigMaps that each stay under this size.
+pub const MAX_CONFIGMAP_DATA_BYTES: usize = 900 * 1024;
+
+/// The first shard keeps the `project-dir` name so existence checks keep working;
+/// any further shards are suffixed with their index.
+pub fn get_project_dir_configmap_name(shard_idx: usize) -> String {
+    match shard_idx {
+        0 => StacksDevnetConfigmap::ProjectDir.to_string(),
+        idx => format!("{}-{idx}", StacksDevnetConfigmap::ProjectDir),
+    }
+}
+
+/// Splits `(path, source)` pairs into groups whose combined size fits in a single ConfigMap.
+/// Always returns at least one (possibly empty) shard.
+pub fn shard_configmap_data(
+    data: Vec<(String, String)>,
+    max_bytes: usize,
+) -> Result<Vec<Vec<(String, String)>>, String> {
+    let mut shards: Vec<Vec<(String, String)>> = vec![vec![]];
+    let mut shard_size = 0;
+    for (path, source) in data {
+        let entry_size = path.len() + source.len();
+        if entry_size > max_bytes {
+            return Err(format!(
+                "contract {path} is {entry_size} bytes, which exceeds the maximum of {max_bytes} bytes"
+            ));
+        }
+        if shard_size + entry_size > max_bytes {
+            shards.push(vec![]);
+            shard_size = 0;
+        }
+        shard_size += entry_size;
+        shards.last_mut().unwrap().push((path, source));
+    }
+    Ok(shards)
+}
@@ -1,4 +1,5 @@
 use super::{
+    configmap::{get_project_dir_configmap_name, shard_configmap_data},
     deployment::StacksDevnetDeployment,
     service::{get_service_from_path_part, get_service_port, get_user_facing_port, ServicePort},
     stateful_set::StacksDevnetStatefulSet,
@@ -86,3 +87,33 @@ fn it_prints_service_from_path_part(path_part: &str) -> Option<StacksDevnetServi
 fn it_gets_user_facing_port_for_service(service: StacksDevnetService) -> Option<String> {
     get_user_facing_port(service)
 }
+
+#[test_case(0 => is equal_to "project-dir"; "for first shard")]
+#[test_case(2 => is equal_to "project-dir-2"; "for later shards")]
+fn it_prints_correct_name_for_project_dir_shard(shard_idx: usize) -> String {
+    get_project_dir_configmap_name(shard_idx)
+}
+
+#[test]
+fn it_shards_configmap_data_by_size() {
+    let data = vec![
+        ("contracts/a.clar".to_string(), "a".repeat(40)),
+        ("contracts/nested/b.clar".to_string(), "b".repeat(40)),
+        ("contracts/c.clar".to_string(), "c".repeat(10)),
+    ];
+    let shards = shard_configmap_data(data, 100).unwrap();
+    let paths: Vec<Vec<&str>> = shards
+        .iter()
+        .map(|shard| shard.iter().map(|(path, _)| path.as_str()).collect())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            vec!["contracts/a.clar"],
+            vec!["contracts/nested/b.clar", "contracts/c.clar"]
+        ]
+    );
+
+    assert_eq!(shard_configmap_data(vec![], 100).unwrap().len(), 1);
+    assert!(shard_configmap_data(vec![("big.clar".into(), "x".repeat(100))], 100).is_err());
+}
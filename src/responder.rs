@@ -2,19 +2,44 @@ use hiro_system_kit::slog;
 use hyper::{
     header::{
         ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
-        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+        ORIGIN, VARY,
     },
     http::{response::Builder, HeaderValue},
     Body, HeaderMap, Response, StatusCode,
 };
+use serde::Serialize;
 use std::convert::Infallible;
+use utoipa::ToSchema;
 
-use crate::{api_config::ResponderConfig, Context};
+use crate::{api_config::ResponderConfig, Context, DevNetError, ErrorCode};
+
+/// How long browsers may cache a preflight response, unless configured otherwise.
+const DEFAULT_MAX_AGE_SECS: u64 = 600;
+
+/// The body of every error response: `{"error": {"code": ..., "message": ..., "details": ...}}`.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ErrorResponse {
+    pub error: ErrorDetails,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ErrorDetails {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Extra information for some errors, such as the validation errors of a devnet config.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<serde_json::Value>,
+}
 
 pub struct Responder {
     allowed_origins: Vec<String>,
     allowed_methods: Vec<String>,
     allowed_headers: String,
+    exposed_headers: Vec<String>,
+    max_age: u64,
     headers: HeaderMap<HeaderValue>,
     ctx: Context,
 }
@@ -25,51 +50,163 @@ impl Default for Responder {
             allowed_origins: Vec::default(),
             allowed_methods: Vec::default(),
             allowed_headers: String::default(),
+            exposed_headers: Vec::default(),
+            max_age: DEFAULT_MAX_AGE_SECS,
             headers: HeaderMap::default(),
             ctx: Context::empty(),
         }
     }
 }
+
+/// Checks an `Origin` header against an allowed origin pattern. Patterns are `*`, an exact
+/// origin, or an origin with a wildcard subdomain, such as `https://*.example.com`.
+/// Patterns without a scheme, like `localhost:3002`, match any scheme.
+pub fn origin_matches(pattern: &str, origin: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    let origin = match pattern.contains("://") {
+        true => origin,
+        false => origin.split_once("://").map_or(origin, |(_, host)| host),
+    };
+    match pattern.split_once("*.") {
+        Some((prefix, domain)) => origin
+            .strip_prefix(prefix)
+            .and_then(|origin| origin.strip_suffix(domain))
+            .and_then(|subdomain| subdomain.strip_suffix('.'))
+            .is_some_and(|subdomain| {
+                !subdomain.is_empty()
+                    && subdomain
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+            }),
+        None => pattern.eq_ignore_ascii_case(origin),
+    }
+}
+
 impl Responder {
     pub fn new(
         config: ResponderConfig,
         headers: HeaderMap<HeaderValue>,
         ctx: Context,
     ) -> Result<Responder, String> {
+        config.validate()?;
         Ok(Responder {
             allowed_origins: config.allowed_origins.unwrap_or_default(),
             allowed_methods: config.allowed_methods.unwrap_or_default(),
             allowed_headers: config.allowed_headers.unwrap_or("*".to_string()),
+            exposed_headers: config.exposed_headers.unwrap_or_default(),
+            max_age: config.max_age.unwrap_or(DEFAULT_MAX_AGE_SECS),
             headers,
             ctx,
         })
     }
 
-    pub fn response_builder(&self) -> Builder {
-        let mut builder = Response::builder()
-            .header(ACCESS_CONTROL_ALLOW_CREDENTIALS, "true")
-            .header(ACCESS_CONTROL_ALLOW_HEADERS, &self.allowed_headers);
-
-        for method in &self.allowed_methods {
-            builder = builder.header(ACCESS_CONTROL_ALLOW_METHODS, method);
+    /// Gets the `Access-Control-Allow-Origin` of the request's origin, if it is allowed to
+    /// make cross-origin requests, along with whether it may send credentials. Origins listed
+    /// explicitly or matching a subdomain pattern are echoed and may send credentials, while
+    /// other origins allowed by `*` only get a literal `*`.
+    fn get_allowed_origin(&self) -> Option<(HeaderValue, bool)> {
+        let origin = self.headers.get(ORIGIN)?;
+        let origin_str = origin.to_str().ok()?;
+        let (any, patterns): (Vec<_>, Vec<_>) = self
+            .allowed_origins
+            .iter()
+            .partition(|pattern| pattern.as_str() == "*");
+        if patterns
+            .iter()
+            .any(|pattern| origin_matches(pattern, origin_str))
+        {
+            return Some((origin.clone(), true));
         }
+        (!any.is_empty()).then(|| (HeaderValue::from_static("*"), false))
+    }
 
-        match self.headers.get(ORIGIN) {
-            Some(header_value) => {
-                if self.allowed_origins.clone().into_iter().any(|h| h == "*") {
-                    builder = builder.header(ACCESS_CONTROL_ALLOW_ORIGIN, "*");
-                    return builder;
-                }
-                for allowed_host in &self.allowed_origins {
-                    if header_value == allowed_host {
-                        builder = builder.header(ACCESS_CONTROL_ALLOW_ORIGIN, allowed_host);
-                        break;
-                    }
-                }
-                builder
+    pub fn response_builder(&self) -> Builder {
+        // listed origins are echoed rather than `*`, since `*` can't be used with credentials,
+        // so caches must key responses by origin
+        let mut builder = Response::builder().header(VARY, "Origin");
+        if let Some((origin, allow_credentials)) = self.get_allowed_origin() {
+            builder = builder.header(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+            if allow_credentials {
+                builder = builder.header(ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+            }
+            if !self.exposed_headers.is_empty() {
+                builder = builder.header(
+                    ACCESS_CONTROL_EXPOSE_HEADERS,
+                    self.exposed_headers.join(", "),
+                );
             }
-            None => builder,
         }
+        builder
+    }
+
+    /// Whether the request is a CORS preflight rather than a plain OPTIONS request.
+    pub fn is_preflight(&self) -> bool {
+        self.headers.contains_key(ORIGIN)
+            && self.headers.contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+    }
+
+    /// Answers a CORS preflight, rejecting disallowed origins and methods with a 403.
+    pub fn preflight(&self) -> Result<Response<Body>, Infallible> {
+        if self.get_allowed_origin().is_none() {
+            return self.respond(403, "origin not allowed".into());
+        }
+        let requested_method = self
+            .headers
+            .get(ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|method| method.to_str().ok())
+            .unwrap_or_default();
+        if !self
+            .allowed_methods
+            .iter()
+            .any(|method| method.eq_ignore_ascii_case(requested_method))
+        {
+            return self.respond(403, format!("method {requested_method} not allowed"));
+        }
+        // browsers don't treat `*` as a wildcard for credentialed requests, so echo the request
+        let allowed_headers = match self.allowed_headers.as_str() {
+            "*" => self
+                .headers
+                .get(ACCESS_CONTROL_REQUEST_HEADERS)
+                .and_then(|headers| headers.to_str().ok())
+                .unwrap_or_default(),
+            allowed_headers => allowed_headers,
+        };
+        let mut builder = self
+            .response_builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(
+                VARY,
+                "Access-Control-Request-Method, Access-Control-Request-Headers",
+            )
+            .header(
+                ACCESS_CONTROL_ALLOW_METHODS,
+                self.allowed_methods.join(", "),
+            )
+            .header(ACCESS_CONTROL_MAX_AGE, self.max_age);
+        if !allowed_headers.is_empty() {
+            builder = builder.header(ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        }
+        match builder.body(Body::empty()) {
+            Ok(r) => Ok(r),
+            Err(e) => self.fallback_response(e),
+        }
+    }
+
+    /// Responds with a bare 500 when a response can't be built. It has no CORS headers,
+    /// since they may be what failed, so it can't fail itself.
+    fn fallback_response(&self, e: hyper::http::Error) -> Result<Response<Body>, Infallible> {
+        self.ctx.try_log(|logger| {
+            slog::error!(
+                logger,
+                "responder failed to send response: {}",
+                e.to_string()
+            )
+        });
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        Ok(response)
     }
 
     fn _respond(&self, code: StatusCode, body: String) -> Result<Response<Body>, Infallible> {
@@ -77,30 +214,89 @@ impl Responder {
         let body = Body::from(body);
         match builder.status(code).body(body) {
             Ok(r) => Ok(r),
-            Err(e) => {
-                self.ctx.try_log(|logger| {
-                    slog::error!(
-                        logger,
-                        "responder failed to send response: {}",
-                        e.to_string()
-                    )
-                });
-                Ok(self
-                    .response_builder()
-                    .status(500)
-                    .body(Body::empty())
-                    .unwrap())
-            }
+            Err(e) => self.fallback_response(e),
         }
     }
 
+    /// Responds with `body` as plain text, or as a JSON error whose code is derived from the
+    /// status when `code` is an error status.
     pub fn respond(&self, code: u16, body: String) -> Result<Response<Body>, Infallible> {
-        self._respond(
+        let status = StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        if status.is_client_error() || status.is_server_error() {
+            return self._respond_with_error(
+                status,
+                ErrorCode::from_status(status.as_u16()),
+                body,
+                None,
+            );
+        }
+        self._respond(status, body)
+    }
+
+    pub fn respond_with_error(&self, error: DevNetError) -> Result<Response<Body>, Infallible> {
+        self._respond_with_error(
+            StatusCode::from_u16(error.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            error.error_code,
+            error.message,
+            None,
+        )
+    }
+
+    pub fn respond_with_error_details(
+        &self,
+        code: u16,
+        error_code: ErrorCode,
+        message: String,
+        details: serde_json::Value,
+    ) -> Result<Response<Body>, Infallible> {
+        self._respond_with_error(
             StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            body,
+            error_code,
+            message,
+            Some(details),
         )
     }
 
+    fn _respond_with_error(
+        &self,
+        code: StatusCode,
+        error_code: ErrorCode,
+        message: String,
+        details: Option<serde_json::Value>,
+    ) -> Result<Response<Body>, Infallible> {
+        let body = ErrorResponse {
+            error: ErrorDetails {
+                code: error_code,
+                message,
+                details,
+            },
+        };
+        match serde_json::to_vec(&body) {
+            Ok(body) => {
+                let mut response = self.respond_with_json(code.as_u16(), Body::from(body))?;
+                // lets the audit log record the code without parsing the body
+                response.extensions_mut().insert(error_code);
+                Ok(response)
+            }
+            Err(e) => self._respond(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("failed to serialize error: {e}"),
+            ),
+        }
+    }
+
+    pub fn respond_with_json(&self, code: u16, body: Body) -> Result<Response<Body>, Infallible> {
+        match self
+            .response_builder()
+            .status(StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR))
+            .header("Content-Type", "application/json")
+            .body(body)
+        {
+            Ok(r) => Ok(r),
+            Err(e) => self.fallback_response(e),
+        }
+    }
+
     pub fn ok(&self) -> Result<Response<Body>, Infallible> {
         self._respond(StatusCode::OK, "Ok".into())
     }
@@ -113,27 +309,42 @@ impl Responder {
             .body(body)
         {
             Ok(r) => Ok(r),
-            Err(e) => self.err_internal(format!("failed to send response: {e}")),
+            Err(e) => self.fallback_response(e),
         }
     }
 
     pub fn err_method_not_allowed(&self, body: String) -> Result<Response<Body>, Infallible> {
-        self._respond(StatusCode::METHOD_NOT_ALLOWED, body)
+        self._respond_with_error(
+            StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::MethodNotAllowed,
+            body,
+            None,
+        )
     }
 
     pub fn err_bad_request(&self, body: String) -> Result<Response<Body>, Infallible> {
-        self._respond(StatusCode::BAD_REQUEST, body)
+        self._respond_with_error(StatusCode::BAD_REQUEST, ErrorCode::BadRequest, body, None)
     }
 
     pub fn err_not_found(&self, body: String) -> Result<Response<Body>, Infallible> {
-        self._respond(StatusCode::NOT_FOUND, body)
+        self._respond_with_error(StatusCode::NOT_FOUND, ErrorCode::NotFound, body, None)
     }
 
     pub fn err_not_implemented(&self, body: String) -> Result<Response<Body>, Infallible> {
-        self._respond(StatusCode::NOT_FOUND, body)
+        self._respond_with_error(
+            StatusCode::NOT_IMPLEMENTED,
+            ErrorCode::NotImplemented,
+            body,
+            None,
+        )
     }
 
     pub fn err_internal(&self, body: String) -> Result<Response<Body>, Infallible> {
-        self._respond(StatusCode::INTERNAL_SERVER_ERROR, body)
+        self._respond_with_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::InternalError,
+            body,
+            None,
+        )
     }
 }
@@ -1,5 +1,14 @@
 use hiro_system_kit::slog;
-use hyper::{Body, Client, Request, Response, Uri};
+use hyper::{
+    body::HttpBody,
+    header::{CONTENT_LENGTH, CONTENT_TYPE},
+    Body, Client, HeaderMap, Request, Response, Uri,
+};
+use opentelemetry::{
+    trace::{SpanKind, Status, TraceContextExt},
+    Context as TraceContext, KeyValue,
+};
+use prometheus::{Encoder, TextEncoder};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::{
@@ -7,24 +16,50 @@ use std::{
     convert::Infallible,
     str::FromStr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
+use tokio::time::{sleep, Instant};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
 
 use crate::{
-    config::StacksDevnetConfig,
+    api_config::{AuditConfig, AuthConfig, QuotaConfig},
+    audit::{AuditAction, AuditFilter, AuditLog, AuditRecord},
+    auth::{check_auth_config, AuthenticatedUser, NetworkAction},
+    config::{StacksDevnetConfig, ValidatedStacksDevnetConfig},
+    metrics::metrics,
+    openapi::ApiDoc,
+    project_archive::ProjectArchiveFormat,
+    quota::lock_user_quota,
     resources::service::{get_service_from_path_part, get_service_url, get_user_facing_port},
-    responder::Responder,
-    Context, StacksDevnetApiK8sManager, StacksDevnetInfoResponse,
+    responder::{ErrorResponse, Responder},
+    telemetry::{inject_trace_context, start_span},
+    webhooks::{WebhookEvent, WebhookPayload, Webhooks},
+    Context, DevNetError, DevnetInfoIncludes, DevnetListFilter, ErrorCode,
+    StacksDevnetApiK8sManager, StacksDevnetInfoResponse, StacksDevnetSummary,
 };
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const PRJ_NAME: &str = env!("CARGO_PKG_NAME");
 
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct StatusResponse {
+    pub version: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/status",
+    tag = "status",
+    responses((status = 200, description = "The name and version of the API", body = StatusResponse))
+)]
 pub async fn handle_get_status(
     responder: Responder,
     ctx: Context,
 ) -> Result<Response<Body>, Infallible> {
-    let version_info = format!("{PRJ_NAME} v{VERSION}");
-    let version_info = json!({ "version": version_info });
+    let version_info = StatusResponse {
+        version: format!("{PRJ_NAME} v{VERSION}"),
+    };
     let version_info = match serde_json::to_vec(&version_info) {
         Ok(v) => v,
         Err(e) => {
@@ -37,70 +72,678 @@ pub async fn handle_get_status(
     responder.ok_with_json(body)
 }
 
+/// Serves the OpenAPI description of the API, generated from the route handlers and their types.
+#[utoipa::path(
+    get,
+    path = "/api/v1/openapi.json",
+    tag = "status",
+    responses((status = 200, description = "The OpenAPI description of the API", body = Object))
+)]
+pub async fn handle_get_openapi(
+    responder: Responder,
+    ctx: &Context,
+) -> Result<Response<Body>, Infallible> {
+    match ApiDoc::openapi().to_json() {
+        Ok(body) => responder.ok_with_json(Body::from(body)),
+        Err(e) => {
+            let msg = format!("failed to form openapi description: {e}");
+            ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+            responder.err_internal(msg)
+        }
+    }
+}
+
+/// Replaces the devnet gauges with the devnets currently running. The gauges are refreshed
+/// on a timer, so scrapes don't list the devnets of the whole cluster.
+pub async fn update_devnet_metrics(
+    k8s_manager: &StacksDevnetApiK8sManager,
+    request_store: &Arc<Mutex<HashMap<String, u64>>>,
+    now: u64,
+    ctx: &Context,
+) {
+    match k8s_manager.get_devnet_summaries().await {
+        Ok(devnets) => {
+            if let Ok(store) = request_store.lock() {
+                metrics().set_devnets(&devnets, &store, now);
+            }
+        }
+        Err(e) => ctx.try_log(|logger| {
+            slog::warn!(logger, "failed to update devnet metrics: {}", e.message)
+        }),
+    }
+}
+
+/// Serves the API's metrics in the Prometheus exposition format. Requires an admin api key.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "status",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text exposition format", content_type = "text/plain; version=0.0.4"),
+        (status = 403, description = "The request was not made with an admin api key", body = ErrorResponse)
+    ),
+    security(("api_key" = []))
+)]
+pub async fn handle_get_metrics(
+    responder: Responder,
+    ctx: &Context,
+) -> Result<Response<Body>, Infallible> {
+    let body = match metrics().encode() {
+        Ok(body) => body,
+        Err(e) => {
+            ctx.try_log(|logger| slog::error!(logger, "{}", e));
+            return responder.err_internal(e);
+        }
+    };
+    match responder
+        .response_builder()
+        .status(200)
+        .header(CONTENT_TYPE, TextEncoder::new().format_type())
+        .body(Body::from(body))
+    {
+        Ok(r) => Ok(r),
+        Err(e) => responder.err_internal(format!("failed to send response: {e}")),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct HealthResponse {
+    pub status: String,
+}
+
+/// Reports that the process is alive, for liveness probes.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "status",
+    responses((status = 200, description = "The API is alive", body = HealthResponse))
+)]
+pub async fn handle_get_healthz(
+    responder: Responder,
+    ctx: &Context,
+) -> Result<Response<Body>, Infallible> {
+    match serde_json::to_vec(&HealthResponse {
+        status: "ok".into(),
+    }) {
+        Ok(body) => responder.ok_with_json(Body::from(body)),
+        Err(e) => {
+            let msg = format!("failed to form response body: {e}");
+            ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+            responder.err_internal(msg)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ReadinessCheck {
+    pub ok: bool,
+    /// Why the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ReadinessCheck {
+    fn from_result(result: Result<(), DevNetError>) -> ReadinessCheck {
+        match result {
+            Ok(()) => ReadinessCheck {
+                ok: true,
+                message: None,
+            },
+            Err(e) => ReadinessCheck {
+                ok: false,
+                message: Some(e.message),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ReadinessChecks {
+    /// Whether the Kubernetes API can be reached and namespaces can be read.
+    pub kubernetes: ReadinessCheck,
+    /// Whether the files the auth config refers to can be loaded.
+    pub config: ReadinessCheck,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: String,
+    pub checks: ReadinessChecks,
+}
+
+/// Reports whether the API can serve requests, for readiness probes. When a check fails,
+/// the error's `details` hold the result of every check.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "status",
+    responses(
+        (status = 200, description = "The API's dependencies are available", body = ReadinessResponse),
+        (status = 503, description = "One of the API's dependencies is unavailable", body = ErrorResponse)
+    )
+)]
+pub async fn handle_get_readyz(
+    k8s_manager: StacksDevnetApiK8sManager,
+    auth_config: &AuthConfig,
+    responder: Responder,
+    ctx: &Context,
+) -> Result<Response<Body>, Infallible> {
+    let checks = ReadinessChecks {
+        kubernetes: ReadinessCheck::from_result(k8s_manager.check_kube_access().await),
+        config: ReadinessCheck::from_result(check_auth_config(auth_config)),
+    };
+    if !checks.kubernetes.ok || !checks.config.ok {
+        let failed = [
+            ("kubernetes", &checks.kubernetes),
+            ("config", &checks.config),
+        ]
+        .into_iter()
+        .filter(|(_, check)| !check.ok)
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>()
+        .join(", ");
+        let msg = format!("readiness checks failed: {failed}");
+        ctx.try_log(|logger| slog::warn!(logger, "{}", msg));
+        return responder.respond_with_error_details(
+            503,
+            ErrorCode::ServiceUnavailable,
+            msg,
+            json!({ "checks": checks }),
+        );
+    }
+    match serde_json::to_vec(&ReadinessResponse {
+        status: "ready".into(),
+        checks,
+    }) {
+        Ok(body) => responder.ok_with_json(Body::from(body)),
+        Err(e) => {
+            let msg = format!("failed to form response body: {e}");
+            ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+            responder.err_internal(msg)
+        }
+    }
+}
+
+/// Upper bound on the size of a devnet creation request, which may hold a project archive.
+const MAX_REQUEST_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// Reads the body of `request`, rejecting it with a 413 as soon as it's known to be larger
+/// than `max_size` bytes, whether from its `Content-Length` or the bytes received so far.
+async fn read_body(request: Request<Body>, max_size: usize) -> Result<Vec<u8>, DevNetError> {
+    let too_large = || DevNetError {
+        message: format!("request body exceeds the maximum size of {max_size} bytes"),
+        code: 413,
+        error_code: ErrorCode::PayloadTooLarge,
+    };
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok())
+        .and_then(|length| length.parse::<usize>().ok());
+    if content_length.is_some_and(|length| length > max_size) {
+        return Err(too_large());
+    }
+    let mut body = request.into_body();
+    let mut buffer = Vec::with_capacity(content_length.unwrap_or_default());
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| DevNetError {
+            message: format!("failed to parse request body: {e}"),
+            code: 500,
+            error_code: ErrorCode::InternalError,
+        })?;
+        if buffer.len() + chunk.len() > max_size {
+            return Err(too_large());
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/networks",
+    tag = "networks",
+    request_body(
+        content = StacksDevnetConfig,
+        description = "The devnet config. A `.tar.gz` or `.zip` of a Clarinet project can be sent instead, with a `Content-Type` of `application/gzip` or `application/zip`"
+    ),
+    params(
+        ("namespace" = Option<String>, Query, description = "The namespace of a devnet created from a project archive with an admin or ci api key"),
+        ("disable_stacks_api" = Option<bool>, Query, description = "Skips deploying the stacks API for a devnet created from a project archive")
+    ),
+    responses(
+        (status = 200, description = "The devnet was deployed"),
+        (status = 400, description = "The devnet config is invalid", body = ErrorResponse),
+        (status = 403, description = "The api key can't create this devnet, the config names another namespace, or a user or group quota was reached", body = ErrorResponse),
+        (status = 409, description = "Devnet assets deployed from a different config already exist in the namespace. Retrying the same config converges on it, but a different config is not applied over a deployed devnet, which must be deleted first", body = ErrorResponse),
+        (status = 413, description = "The request body is larger than 16 MiB", body = ErrorResponse),
+        (status = 429, description = "The cluster quota was reached", body = ErrorResponse)
+    ),
+    security(("auth_header" = []), ("api_key" = []), ("bearer" = []))
+)]
 pub async fn handle_new_devnet(
     request: Request<Body>,
-    user_id: &str,
+    user: &AuthenticatedUser,
+    quota_config: &QuotaConfig,
     k8s_manager: StacksDevnetApiK8sManager,
     responder: Responder,
     request_store: Arc<Mutex<HashMap<String, u64>>>,
     request_time: u64,
+    webhooks: &Webhooks,
+    audit: &mut AuditRecord,
     ctx: &Context,
 ) -> Result<Response<Body>, Infallible> {
-    let body = hyper::body::to_bytes(request.into_body()).await;
-    if body.is_err() {
-        let msg = "failed to parse request body";
-        ctx.try_log(|logger| slog::error!(logger, "{}", msg));
-        return responder.err_internal(msg.into());
-    }
-    let body = body.unwrap();
-    let config: Result<StacksDevnetConfig, _> = serde_json::from_slice(&body);
+    let archive_format = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(ProjectArchiveFormat::from_content_type);
+    let disable_stacks_api = matches!(
+        get_query_param(request.uri(), "disable_stacks_api").as_deref(),
+        Some("") | Some("true")
+    );
+    // privileged callers aren't bound to a namespace, so archive uploads name it explicitly
+    let requested_namespace = get_query_param(request.uri(), "namespace");
+    let body = match read_body(request, MAX_REQUEST_BODY_SIZE).await {
+        Ok(body) => body,
+        Err(e) => {
+            ctx.try_log(|logger| slog::error!(logger, "{}", e.message));
+            return responder.respond_with_error(e);
+        }
+    };
+    audit.set_config(&body);
+    let config: Result<StacksDevnetConfig, String> = match archive_format {
+        Some(format) => {
+            let namespace = match (user.is_privileged(), requested_namespace) {
+                (false, _) => user.user_id.clone(),
+                (true, Some(namespace)) => namespace,
+                (true, None) => {
+                    return responder.err_bad_request(
+                        "the namespace query parameter is required for project archives".into(),
+                    )
+                }
+            };
+            StacksDevnetConfig::from_project_archive(&namespace, &body, format, disable_stacks_api)
+        }
+        None => StacksDevnetConfig::from_json(&body),
+    };
+    if let Ok(config) = &config {
+        audit.network = Some(config.namespace.clone());
+    }
+    let user_id = match (&config, user.is_privileged()) {
+        (Ok(config), true) => {
+            if let Err(e) = user.authorize(&config.namespace, NetworkAction::Create) {
+                return responder.respond_with_error(e);
+            }
+            config.namespace.clone()
+        }
+        _ => user.user_id.clone(),
+    };
+    let user_id = user_id.as_str();
+    if let Ok(config) = &config {
+        if let Err(e) = config.check_namespace(user_id, ctx) {
+            return responder.respond_with_error(e);
+        }
+        let mut validation_errors = config.validate();
+        validation_errors.extend(
+            webhooks.validate_devnet_webhooks(config.webhooks.as_deref().unwrap_or_default()),
+        );
+        if !validation_errors.is_empty() {
+            ctx.try_log(|logger| {
+                slog::warn!(
+                    logger,
+                    "failed to validate config for NAMESPACE: {}, found {} errors",
+                    config.namespace,
+                    validation_errors.len()
+                )
+            });
+            return responder.respond_with_error_details(
+                400,
+                ErrorCode::InvalidConfig,
+                format!("found {} errors in devnet config", validation_errors.len()),
+                json!(validation_errors),
+            );
+        }
+    }
     match config {
-        Ok(config) => match config.to_validated_config(user_id, ctx) {
-            Ok(config) => match k8s_manager.deploy_devnet(config).await {
-                Ok(_) => {
-                    if let Ok(mut store) = request_store.lock() {
-                        store.insert(user_id.to_string(), request_time);
+        Ok(config) => match config.clone().to_validated_config(user_id, ctx) {
+            Ok(validated_config) => {
+                // held until the devnet is created, so it counts towards the user's next check
+                let _quota_guard = match quota_config.is_enabled() {
+                    true => Some(lock_user_quota(&user.user_id).await),
+                    false => None,
+                };
+                if quota_config.is_enabled() {
+                    if let Err(response) = check_devnet_quota(
+                        &validated_config,
+                        user,
+                        quota_config,
+                        &k8s_manager,
+                        &responder,
+                        ctx,
+                    )
+                    .await
+                    {
+                        return response;
                     }
-                    responder.ok()
                 }
-                Err(e) => responder.respond(e.code, e.message),
-            },
-            Err(e) => responder.respond(e.code, e.message),
+                let devnet_webhooks = validated_config.webhooks.clone();
+                // in operator mode the devnet is deployed by the operator from its `StacksDevnet`
+                let deployed = match k8s_manager.uses_operator() {
+                    true => k8s_manager.create_stacks_devnet(config).await,
+                    false => k8s_manager.deploy_devnet(validated_config).await,
+                };
+                match deployed {
+                    Ok(_) => {
+                        if let Ok(mut store) = request_store.lock() {
+                            store.insert(user_id.to_string(), request_time);
+                        }
+                        webhooks.watch_new_devnet(
+                            k8s_manager,
+                            user_id,
+                            user_id,
+                            devnet_webhooks,
+                            ctx,
+                        );
+                        responder.ok()
+                    }
+                    Err(e) => {
+                        // nothing was deployed when the namespace is missing or already in use
+                        if !matches!(
+                            e.error_code,
+                            ErrorCode::DevnetAlreadyExists | ErrorCode::NamespaceNotFound
+                        ) {
+                            let payload = WebhookPayload::failed(user_id, e.message.clone(), ctx);
+                            webhooks.send(payload, &devnet_webhooks, ctx);
+                        }
+                        responder.respond_with_error(e)
+                    }
+                }
+            }
+            Err(e) => responder.respond_with_error(e),
         },
-        Err(e) => {
-            responder.err_bad_request(format!("invalid configuration to create network: {e}"))
+        Err(e) => responder.respond_with_error(DevNetError {
+            message: format!("invalid configuration to create network: {e}"),
+            code: 400,
+            error_code: ErrorCode::InvalidConfig,
+        }),
+    }
+}
+
+/// Checks the quotas of `user` for the devnet of `config`, counting the `StacksDevnet`s
+/// the operator hasn't deployed yet. A devnet already in its namespace only counts when
+/// the request isn't reapplying the same config or `StacksDevnet`, since retrying a
+/// creation doesn't run another devnet.
+async fn check_devnet_quota(
+    config: &ValidatedStacksDevnetConfig,
+    user: &AuthenticatedUser,
+    quota_config: &QuotaConfig,
+    k8s_manager: &StacksDevnetApiK8sManager,
+    responder: &Responder,
+    ctx: &Context,
+) -> Result<(), Result<Response<Body>, Infallible>> {
+    let mut devnets = k8s_manager
+        .get_devnet_summaries()
+        .await
+        .map_err(|e| responder.respond_with_error(e))?;
+    let stacks_devnets = k8s_manager
+        .get_stacks_devnet_summaries()
+        .await
+        .map_err(|e| responder.respond_with_error(e))?;
+    // an existing `StacksDevnet` is either applied again as is or a conflict
+    let reapply = stacks_devnets
+        .iter()
+        .any(|devnet| devnet.namespace == config.namespace)
+        || k8s_manager
+            .is_devnet_reapply(config)
+            .await
+            .map_err(|e| responder.respond_with_error(e))?;
+    for stacks_devnet in stacks_devnets {
+        if !devnets
+            .iter()
+            .any(|d| d.namespace == stacks_devnet.namespace)
+        {
+            devnets.push(stacks_devnet);
         }
     }
+    if reapply {
+        devnets.retain(|devnet| devnet.namespace != config.namespace);
+    }
+    quota_config
+        .check(user, &config.namespace, &devnets)
+        .map_err(|e| {
+            ctx.try_log(|logger| {
+                slog::warn!(logger, "{}, NAMESPACE: {}", e.message, config.namespace)
+            });
+            responder.respond_with_error_details(
+                e.code,
+                ErrorCode::QuotaExceeded,
+                e.message,
+                json!({ "quota": e.quota, "limit": e.limit, "usage": e.usage }),
+            )
+        })
 }
 
+const DEFAULT_LIST_LIMIT: usize = 50;
+const MAX_LIST_LIMIT: usize = 500;
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct StacksDevnetListResponse {
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub networks: Vec<StacksDevnetSummary>,
+}
+
+/// Parses the `offset`, `limit`, `owner`, `namespace_prefix` and `phase` query parameters
+/// of the admin network listing.
+pub fn get_devnet_list_params(uri: &Uri) -> Result<(DevnetListFilter, usize, usize), String> {
+    let parse_number = |name: &str, default: usize| match get_query_param(uri, name) {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| format!("invalid {name} query parameter: {value}")),
+        None => Ok(default),
+    };
+    let offset = parse_number("offset", 0)?;
+    let limit = parse_number("limit", DEFAULT_LIST_LIMIT)?;
+    if limit == 0 || limit > MAX_LIST_LIMIT {
+        return Err(format!(
+            "limit query parameter must be between 1 and {MAX_LIST_LIMIT}"
+        ));
+    }
+    let filter = DevnetListFilter {
+        owner: get_query_param(uri, "owner"),
+        namespace_prefix: get_query_param(uri, "namespace_prefix"),
+        phase: get_query_param(uri, "phase"),
+    };
+    Ok((filter, offset, limit))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/networks",
+    tag = "admin",
+    params(
+        ("offset" = Option<usize>, Query, description = "The number of devnets to skip, defaults to 0"),
+        ("limit" = Option<usize>, Query, description = "The number of devnets to return, defaults to 50 with a maximum of 500"),
+        ("owner" = Option<String>, Query, description = "Only include devnets owned by this user"),
+        ("namespace_prefix" = Option<String>, Query, description = "Only include devnets in namespaces with this prefix"),
+        ("phase" = Option<String>, Query, description = "Only include devnets with at least one pod in this phase")
+    ),
+    responses(
+        (status = 200, description = "Every devnet managed by the API, sorted by namespace", body = StacksDevnetListResponse),
+        (status = 400, description = "A query parameter is invalid", body = ErrorResponse),
+        (status = 403, description = "The request was not made with an admin api key", body = ErrorResponse)
+    ),
+    security(("api_key" = []))
+)]
+pub async fn handle_list_devnets(
+    uri: &Uri,
+    k8s_manager: StacksDevnetApiK8sManager,
+    responder: Responder,
+    request_store: Arc<Mutex<HashMap<String, u64>>>,
+    ctx: &Context,
+) -> Result<Response<Body>, Infallible> {
+    let (filter, offset, limit) = match get_devnet_list_params(uri) {
+        Ok(params) => params,
+        Err(e) => return responder.err_bad_request(e),
+    };
+    match k8s_manager.list_devnets(&filter, offset, limit).await {
+        Ok((total, mut networks)) => {
+            if let Ok(store) = request_store.lock() {
+                for network in networks.iter_mut() {
+                    network.last_request_time = store.get(&network.namespace).copied();
+                }
+            }
+            let response = StacksDevnetListResponse {
+                total,
+                offset,
+                limit,
+                networks,
+            };
+            match serde_json::to_vec(&response) {
+                Ok(body) => responder.ok_with_json(Body::from(body)),
+                Err(e) => {
+                    let msg = format!("failed to form response body: {e}");
+                    ctx.try_log(|logger: &hiro_system_kit::Logger| slog::error!(logger, "{}", msg));
+                    responder.err_internal(msg)
+                }
+            }
+        }
+        Err(e) => responder.respond_with_error(e),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct AuditLogResponse {
+    /// The matching records, newest first.
+    pub records: Vec<AuditRecord>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit",
+    tag = "admin",
+    params(
+        ("user_id" = Option<String>, Query, description = "Only include actions of this user or api key"),
+        ("network" = Option<String>, Query, description = "Only include actions on this network"),
+        ("action" = Option<AuditAction>, Query, description = "Only include this action"),
+        ("since" = Option<u64>, Query, description = "Only include actions at or after this unix timestamp, in seconds"),
+        ("limit" = Option<usize>, Query, description = "The number of records to return, defaults to 100 with a maximum of 1000")
+    ),
+    responses(
+        (status = 200, description = "The most recent matching audit records", body = AuditLogResponse),
+        (status = 400, description = "A query parameter is invalid", body = ErrorResponse),
+        (status = 403, description = "The request was not made with an admin api key", body = ErrorResponse),
+        (status = 501, description = "Audit records are written to stdout", body = ErrorResponse)
+    ),
+    security(("api_key" = []))
+)]
+pub async fn handle_get_audit_log(
+    uri: &Uri,
+    audit_config: &AuditConfig,
+    responder: Responder,
+    ctx: &Context,
+) -> Result<Response<Body>, Infallible> {
+    let filter = match AuditFilter::from_uri(uri) {
+        Ok(filter) => filter,
+        Err(e) => return responder.err_bad_request(e),
+    };
+    match AuditLog::new(audit_config).query(&filter, ctx) {
+        Ok(records) => match serde_json::to_vec(&AuditLogResponse { records }) {
+            Ok(body) => responder.ok_with_json(Body::from(body)),
+            Err(e) => {
+                let msg = format!("failed to form response body: {e}");
+                ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+                responder.err_internal(msg)
+            }
+        },
+        Err(e) => responder.respond_with_error(e),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/network/{network}",
+    tag = "networks",
+    params(("network" = String, Path, description = "The namespace of the devnet")),
+    responses(
+        (status = 200, description = "The devnet was deleted"),
+        (status = 404, description = "The namespace does not exist", body = ErrorResponse),
+        (status = 409, description = "No devnet assets exist in the namespace", body = ErrorResponse)
+    ),
+    security(("auth_header" = []), ("api_key" = []), ("bearer" = []))
+)]
 pub async fn handle_delete_devnet(
     k8s_manager: StacksDevnetApiK8sManager,
     network: &str,
     user_id: &str,
     responder: Responder,
+    webhooks: &Webhooks,
+    ctx: &Context,
 ) -> Result<Response<Body>, Infallible> {
+    // read before the devnet's webhooks are deleted along with it
+    let devnet_webhooks = k8s_manager.get_devnet_webhooks(network).await;
     match k8s_manager.delete_devnet(network, user_id).await {
-        Ok(_) => responder.ok(),
+        Ok(_) => {
+            let payload = WebhookPayload::new(WebhookEvent::DevnetDeleted, network, ctx);
+            webhooks.send(payload, &devnet_webhooks, ctx);
+            responder.ok()
+        }
         Err(e) => {
-            let msg = format!("error deleting network {}: {}", &network, e.message);
-            responder.respond(e.code, msg)
+            let message = format!("error deleting network {}: {}", &network, e.message);
+            responder.respond_with_error(DevNetError { message, ..e })
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct DevnetMetadata {
     pub secs_since_last_request: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct StacksDevnetInfoWithMetadata {
     #[serde(flatten)]
     pub data: StacksDevnetInfoResponse,
     pub metadata: DevnetMetadata,
 }
 
+/// Parses the `include` query parameter of a devnet's status, e.g. `tips,deployment`.
+pub fn get_devnet_info_includes(uri: &Uri) -> Result<DevnetInfoIncludes, String> {
+    let mut includes = DevnetInfoIncludes::default();
+    let Some(include) = get_query_param(uri, "include") else {
+        return Ok(includes);
+    };
+    for part in include.split(',') {
+        match part {
+            "tips" => includes.tips = true,
+            "chain" => includes.chain = true,
+            "deployment" => includes.deployment = true,
+            "" => {}
+            part => return Err(format!("invalid include query parameter: {part}")),
+        }
+    }
+    Ok(includes)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/network/{network}",
+    tag = "networks",
+    params(
+        ("network" = String, Path, description = "The namespace of the devnet"),
+        ("include" = Option<String>, Query, description = "A comma separated list of `tips`, `chain` and `deployment`, the optional parts of the status to include")
+    ),
+    responses(
+        (status = 200, description = "The pod and chain tip status of the devnet", body = StacksDevnetInfoWithMetadata),
+        (status = 400, description = "A query parameter is invalid", body = ErrorResponse),
+        (status = 404, description = "Not all devnet assets exist in the namespace", body = ErrorResponse)
+    ),
+    security(("auth_header" = []), ("api_key" = []), ("bearer" = []))
+)]
 pub async fn handle_get_devnet(
+    uri: &Uri,
     k8s_manager: StacksDevnetApiK8sManager,
     network: &str,
     user_id: &str,
@@ -109,7 +752,14 @@ pub async fn handle_get_devnet(
     request_time: u64,
     ctx: Context,
 ) -> Result<Response<Body>, Infallible> {
-    match k8s_manager.get_devnet_info(network, user_id).await {
+    let includes = match get_devnet_info_includes(uri) {
+        Ok(includes) => includes,
+        Err(e) => return responder.err_bad_request(e),
+    };
+    match k8s_manager
+        .get_devnet_info(network, user_id, includes)
+        .await
+    {
         Ok(devnet_info) => {
             let last_request_time = match request_store.lock() {
                 Ok(mut store) => match store.get(user_id) {
@@ -139,10 +789,21 @@ pub async fn handle_get_devnet(
                 }
             }
         }
-        Err(e) => responder.respond(e.code, e.message),
+        Err(e) => responder.respond_with_error(e),
     }
 }
 
+#[utoipa::path(
+    head,
+    path = "/api/v1/network/{network}",
+    tag = "networks",
+    params(("network" = String, Path, description = "The namespace of the devnet")),
+    responses(
+        (status = 200, description = "Some devnet assets exist in the namespace"),
+        (status = 404, description = "No devnet assets exist in the namespace")
+    ),
+    security(("auth_header" = []), ("api_key" = []), ("bearer" = []))
+)]
 pub async fn handle_check_devnet(
     k8s_manager: StacksDevnetApiK8sManager,
     network: &str,
@@ -155,12 +816,224 @@ pub async fn handle_check_devnet(
     {
         Ok(assets_exist) => match assets_exist {
             true => responder.ok(),
-            false => responder.err_not_found("not found".to_string()),
+            false => responder.respond_with_error(DevNetError {
+                message: "not found".into(),
+                code: 404,
+                error_code: ErrorCode::DevnetNotFound,
+            }),
         },
-        Err(e) => responder.respond(e.code, e.message),
+        Err(e) => responder.respond_with_error(e),
     }
 }
 
+const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 60;
+// proxies in front of the API must keep requests open this long, so clients needing longer
+// waits should retry instead
+const MAX_WAIT_TIMEOUT_SECS: u64 = 300;
+const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What a request to `/api/v1/network/{network}/wait` waits for, parsed from the `condition`,
+/// `min_stacks_height`, `min_burn_height` and `timeout` query parameters.
+#[derive(Debug, PartialEq)]
+pub struct DevnetWaitParams {
+    pub min_stacks_height: Option<u64>,
+    pub min_burn_height: Option<u64>,
+    pub timeout: Duration,
+}
+
+impl DevnetWaitParams {
+    /// Describes each condition `devnet_info` doesn't meet yet: every component must be
+    /// running and ready, and the chain tips must reach the minimum heights.
+    pub fn get_unmet_conditions(&self, devnet_info: &StacksDevnetInfoResponse) -> Vec<String> {
+        let mut unmet: Vec<String> = devnet_info
+            .components
+            .iter()
+            .filter_map(|(component, status)| match status.phase.as_deref() {
+                Some("Running") if status.ready => None,
+                Some("Running") => Some(format!("{component} is not ready")),
+                Some(phase) => Some(format!("{component} is {phase}")),
+                None => Some(format!("{component} has not been created")),
+            })
+            .collect();
+        if let Some(min_stacks_height) = self.min_stacks_height {
+            let stacks_chain_tip = devnet_info.stacks_chain_tip.unwrap_or_default();
+            if stacks_chain_tip < min_stacks_height {
+                unmet.push(format!(
+                    "stacks chain tip {stacks_chain_tip} is below {min_stacks_height}"
+                ));
+            }
+        }
+        if let Some(min_burn_height) = self.min_burn_height {
+            let bitcoin_chain_tip = devnet_info.bitcoin_chain_tip.unwrap_or_default();
+            if bitcoin_chain_tip < min_burn_height {
+                unmet.push(format!(
+                    "bitcoin chain tip {bitcoin_chain_tip} is below {min_burn_height}"
+                ));
+            }
+        }
+        unmet
+    }
+}
+
+pub fn get_devnet_wait_params(uri: &Uri) -> Result<DevnetWaitParams, String> {
+    match get_query_param(uri, "condition").as_deref() {
+        None | Some("ready") => {}
+        Some(condition) => return Err(format!("invalid condition query parameter: {condition}")),
+    }
+    let parse_number = |name: &str| match get_query_param(uri, name) {
+        Some(value) => value
+            .parse::<u64>()
+            .map(Some)
+            .map_err(|_| format!("invalid {name} query parameter: {value}")),
+        None => Ok(None),
+    };
+    let min_stacks_height = parse_number("min_stacks_height")?;
+    let min_burn_height = parse_number("min_burn_height")?;
+    let timeout = parse_number("timeout")?.unwrap_or(DEFAULT_WAIT_TIMEOUT_SECS);
+    if timeout == 0 || timeout > MAX_WAIT_TIMEOUT_SECS {
+        return Err(format!(
+            "timeout query parameter must be between 1 and {MAX_WAIT_TIMEOUT_SECS} seconds"
+        ));
+    }
+    Ok(DevnetWaitParams {
+        min_stacks_height,
+        min_burn_height,
+        timeout: Duration::from_secs(timeout),
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct DevnetWaitResponse {
+    #[serde(flatten)]
+    pub data: StacksDevnetInfoResponse,
+    /// How long the request waited for the devnet to be ready.
+    pub waited_secs: u64,
+}
+
+/// Polls the devnet until every component is running and ready and its chain tips reach the
+/// requested heights. Failures to reach the Kubernetes API are retried until the timeout.
+#[utoipa::path(
+    get,
+    path = "/api/v1/network/{network}/wait",
+    tag = "networks",
+    params(
+        ("network" = String, Path, description = "The namespace of the devnet"),
+        ("condition" = Option<String>, Query, description = "What to wait for, only `ready` is supported"),
+        ("min_stacks_height" = Option<u64>, Query, description = "The stacks chain tip to wait for"),
+        ("min_burn_height" = Option<u64>, Query, description = "The bitcoin chain tip to wait for"),
+        ("timeout" = Option<u64>, Query, description = "How long to wait, in seconds, defaults to 60 with a maximum of 300")
+    ),
+    responses(
+        (status = 200, description = "The devnet is ready", body = DevnetWaitResponse),
+        (status = 400, description = "A query parameter is invalid", body = ErrorResponse),
+        (status = 404, description = "Not all devnet assets exist in the namespace", body = ErrorResponse),
+        (status = 504, description = "The devnet wasn't ready before the timeout, with the unmet conditions and last devnet status as details", body = ErrorResponse)
+    ),
+    security(("auth_header" = []), ("api_key" = []), ("bearer" = []))
+)]
+pub async fn handle_wait_for_devnet(
+    uri: &Uri,
+    k8s_manager: StacksDevnetApiK8sManager,
+    network: &str,
+    user_id: &str,
+    responder: Responder,
+    ctx: &Context,
+) -> Result<Response<Body>, Infallible> {
+    let params = match get_devnet_wait_params(uri) {
+        Ok(params) => params,
+        Err(e) => return responder.err_bad_request(e),
+    };
+    // only the pods, and the chain tips if heights were given, are needed to check the conditions
+    let includes = DevnetInfoIncludes {
+        tips: params.min_stacks_height.is_some() || params.min_burn_height.is_some(),
+        ..Default::default()
+    };
+    let start = Instant::now();
+    let deadline = start + params.timeout;
+    loop {
+        let devnet_info = k8s_manager
+            .get_devnet_info(network, user_id, includes)
+            .await;
+        // in operator mode, the devnet of a `StacksDevnet` is deployed after it's created
+        let not_deployed = match &devnet_info {
+            Err(e) if e.code == 404 => k8s_manager
+                .stacks_devnet_exists(network)
+                .await
+                .unwrap_or(false),
+            _ => false,
+        };
+        let (devnet_info, unmet) = match devnet_info {
+            Ok(devnet_info) => {
+                let unmet = params.get_unmet_conditions(&devnet_info);
+                if unmet.is_empty() {
+                    let response = DevnetWaitResponse {
+                        data: devnet_info,
+                        waited_secs: start.elapsed().as_secs(),
+                    };
+                    return match serde_json::to_vec(&response) {
+                        Ok(body) => responder.ok_with_json(Body::from(body)),
+                        Err(e) => {
+                            let msg = format!("failed to form response body: {e}");
+                            ctx.try_log(|logger| slog::error!(logger, "{}", msg));
+                            responder.err_internal(msg)
+                        }
+                    };
+                }
+                (Some(devnet_info), unmet)
+            }
+            Err(_) if not_deployed => (
+                None,
+                vec![format!("network {network} has not been deployed yet")],
+            ),
+            // the devnet was deleted, or the request can't succeed
+            Err(e) if e.code < 500 => return responder.respond_with_error(e),
+            Err(e) => {
+                ctx.try_log(|logger| {
+                    slog::warn!(
+                        logger,
+                        "failed to get devnet info while waiting: {}",
+                        e.message
+                    )
+                });
+                (None, vec![e.message])
+            }
+        };
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            let message = format!(
+                "network {network} was not ready after {} seconds: {}",
+                params.timeout.as_secs(),
+                unmet.join(", ")
+            );
+            ctx.try_log(|logger| slog::info!(logger, "{}", message));
+            return responder.respond_with_error_details(
+                504,
+                ErrorCode::DevnetNotReady,
+                message,
+                json!({ "unmet_conditions": unmet, "devnet": devnet_info }),
+            );
+        }
+        sleep(remaining.min(WAIT_POLL_INTERVAL)).await;
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/network/{network}/{service}/{path}",
+    tag = "networks",
+    operation_id = "proxy_get",
+    params(
+        ("network" = String, Path, description = "The namespace of the devnet"),
+        ("service" = String, Path, description = "One of `bitcoin-node`, `stacks-blockchain`, `stacks-blockchain-api`, `stacks-signer-0` or `stacks-signer-1`"),
+        ("path" = String, Path, description = "The path forwarded to the service")
+    ),
+    responses(
+        (status = 200, description = "The response of the service"),
+        (status = 404, description = "Not all devnet assets exist in the namespace", body = ErrorResponse),
+        (status = 500, description = "The request to the service failed", body = ErrorResponse)
+    ),
+    security(("auth_header" = []), ("api_key" = []), ("bearer" = []))
+)]
 pub async fn handle_try_proxy_service(
     remaining_path: &str,
     subroute: &str,
@@ -181,7 +1054,7 @@ pub async fn handle_try_proxy_service(
                         let forward_url = format!("{base_url}:{port}");
                         let proxy_request =
                             mutate_request_for_proxy(request, &forward_url, remaining_path);
-                        proxy(proxy_request, responder, ctx).await
+                        proxy(proxy_request, subroute, responder, ctx).await
                     }
                     None => responder.err_bad_request("invalid request path".into()),
                 }
@@ -192,7 +1065,7 @@ pub async fn handle_try_proxy_service(
                 responder.err_not_found(msg)
             }
         },
-        Err(e) => responder.respond(e.code, e.message),
+        Err(e) => responder.respond_with_error(e),
     }
 }
 
@@ -215,21 +1088,45 @@ pub fn mutate_request_for_proxy(
 }
 
 async fn proxy(
-    request: Request<Body>,
+    mut request: Request<Body>,
+    service: &str,
     responder: Responder,
     ctx: &Context,
 ) -> Result<Response<Body>, Infallible> {
     let client = Client::new();
 
     ctx.try_log(|logger| slog::info!(logger, "forwarding request to {}", request.uri()));
-    match client.request(request).await {
-        Ok(response) => Ok(response),
+    let cx = start_span(
+        format!("proxy {service}"),
+        SpanKind::Client,
+        vec![
+            KeyValue::new("http.request.method", request.method().to_string()),
+            KeyValue::new("url.full", request.uri().to_string()),
+            KeyValue::new("devnet.service", service.to_string()),
+        ],
+        &TraceContext::current(),
+    );
+    inject_trace_context(&cx, request.headers_mut());
+    let result = client.request(request).await;
+    let span = cx.span();
+    let response = match result {
+        Ok(response) => {
+            span.set_attribute(KeyValue::new(
+                "http.response.status_code",
+                response.status().as_u16() as i64,
+            ));
+            Ok(response)
+        }
         Err(e) => {
+            metrics().observe_proxy_upstream_error(service);
             let msg = format!("error proxying request: {e}");
+            span.set_status(Status::error(msg.clone()));
             ctx.try_log(|logger| slog::error!(logger, "{}", msg));
             responder.err_internal(msg)
         }
-    }
+    };
+    span.end();
+    response
 }
 #[derive(Default, PartialEq, Debug, Clone)]
 pub struct PathParts {
@@ -239,6 +1136,68 @@ pub struct PathParts {
     pub remainder: Option<String>,
 }
 pub const API_PATH: &str = "/api/v1/";
+
+/// Identifies a request in logs, in its response and in the requests proxied for it.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Gets the request id sent by the client, or generates one if it's missing or isn't made
+/// of up to 128 visible ASCII characters.
+pub fn get_request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LEN
+                && id.chars().all(|c| c.is_ascii_graphic())
+        })
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+const STATIC_ROUTES: [&str; 8] = [
+    "/api/v1/status",
+    "/api/v1/openapi.json",
+    "/api/v1/networks",
+    "/api/v1/admin/networks",
+    "/api/v1/admin/audit",
+    "/metrics",
+    "/healthz",
+    "/readyz",
+];
+
+/// Whether `path` could be served by one of the API's routes.
+pub fn is_known_route(path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    path.is_empty() || STATIC_ROUTES.contains(&path) || path.starts_with("/api/v1/network/")
+}
+
+/// Gets the route template serving `path`, such as `/api/v1/network/{network}`,
+/// to label metrics without including network ids or proxied paths.
+pub fn get_route_template(path: &str) -> String {
+    let trimmed_path = path.trim_end_matches('/');
+    if trimmed_path.is_empty() {
+        return "/".into();
+    }
+    if STATIC_ROUTES.contains(&trimmed_path) {
+        return trimmed_path.into();
+    }
+    if !path.starts_with("/api/v1/network/") {
+        return "unknown".into();
+    }
+    match get_standardized_path_parts(path).subroute {
+        None => "/api/v1/network/{network}".into(),
+        Some(subroute)
+            if subroute == "commands"
+                || subroute == "wait"
+                || get_service_from_path_part(&subroute).is_some() =>
+        {
+            format!("/api/v1/network/{{network}}/{subroute}")
+        }
+        Some(_) => "/api/v1/network/{network}/{subroute}".into(),
+    }
+}
 pub fn get_standardized_path_parts(path: &str) -> PathParts {
     let path = path.replace(API_PATH, "");
     let path = path.trim_matches('/');
@@ -275,3 +1234,10 @@ pub fn get_standardized_path_parts(path: &str) -> PathParts {
         }
     }
 }
+
+/// Returns the percent-decoded value of the `name` query parameter, or an empty string if it is
+/// present without a value.
+pub fn get_query_param(uri: &Uri, name: &str) -> Option<String> {
+    form_urlencoded::parse(uri.query()?.as_bytes())
+        .find_map(|(key, value)| (key == name).then(|| value.into_owned()))
+}
@@ -0,0 +1,198 @@
+use std::{borrow::Cow, future::Future};
+
+use hyper::{
+    header::{HeaderName, HeaderValue},
+    HeaderMap,
+};
+use opentelemetry::{
+    global,
+    propagation::{Extractor, Injector},
+    trace::{FutureExt, SpanKind, Status, TraceContextExt, Tracer},
+    Context as TraceContext, KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    runtime,
+    trace::{self as sdktrace, Sampler},
+    Resource,
+};
+
+use crate::{api_config::TracingConfig, Context, DevNetError};
+
+const TRACER_NAME: &str = "stacks-devnet-api";
+const DEFAULT_SERVICE_NAME: &str = "stacks-devnet-api";
+
+/// Installs the global OTLP trace exporter and W3C trace context propagator.
+/// Returns whether tracing was enabled, which is the case when `otlp_endpoint` is set.
+pub fn init_tracing(tracing_config: &TracingConfig) -> Result<bool, String> {
+    let Some(otlp_endpoint) = &tracing_config.otlp_endpoint else {
+        return Ok(false);
+    };
+    let sample_ratio = tracing_config.sample_ratio.unwrap_or(1.0);
+    if !(0.0..=1.0).contains(&sample_ratio) {
+        return Err(format!(
+            "tracing sample_ratio must be between 0 and 1, got {sample_ratio}"
+        ));
+    }
+    let service_name = tracing_config
+        .service_name
+        .clone()
+        .unwrap_or(DEFAULT_SERVICE_NAME.into());
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(otlp_endpoint),
+        )
+        .with_trace_config(
+            sdktrace::config()
+                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                    sample_ratio,
+                ))))
+                .with_resource(Resource::new(vec![KeyValue::new(
+                    "service.name",
+                    service_name,
+                )])),
+        )
+        .install_batch(runtime::Tokio)
+        .map_err(|e| format!("unable to start otlp trace exporter: {e}"))?;
+    Ok(true)
+}
+
+/// Flushes the spans that haven't been exported yet.
+pub fn shutdown_tracing() {
+    global::shutdown_tracer_provider();
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// Reads the trace context sent by an upstream service, such as a `traceparent` header.
+pub fn extract_trace_context(headers: &HeaderMap) -> TraceContext {
+    global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)))
+}
+
+/// Adds the trace context of `cx` to the headers of a request sent to another service.
+pub fn inject_trace_context(cx: &TraceContext, headers: &mut HeaderMap) {
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(cx, &mut HeaderInjector(headers))
+    });
+}
+
+/// Starts a span as a child of `parent`, returning a context holding it.
+pub fn start_span(
+    name: impl Into<Cow<'static, str>>,
+    kind: SpanKind,
+    attributes: Vec<KeyValue>,
+    parent: &TraceContext,
+) -> TraceContext {
+    let tracer = global::tracer(TRACER_NAME);
+    let span = tracer
+        .span_builder(name)
+        .with_kind(kind)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, parent);
+    parent.with_span(span)
+}
+
+/// Marks the span of `cx` as failed with `error`.
+pub fn record_error(cx: &TraceContext, error: &DevNetError) {
+    let span = cx.span();
+    let error_code: &'static str = error.error_code.into();
+    span.set_attribute(KeyValue::new("error.code", error_code));
+    span.set_attribute(KeyValue::new(
+        "http.response.status_code",
+        error.code as i64,
+    ));
+    span.set_status(Status::error(error.message.clone()));
+}
+
+/// Runs `future` in a span that is a child of the current span, so that the spans
+/// it starts are nested under it. Does nothing unless tracing is enabled for `ctx`.
+pub async fn with_span<T, F>(
+    ctx: &Context,
+    name: &'static str,
+    attributes: Vec<KeyValue>,
+    future: F,
+) -> Result<T, DevNetError>
+where
+    F: Future<Output = Result<T, DevNetError>>,
+{
+    if !ctx.tracer {
+        return future.await;
+    }
+    let cx = start_span(
+        name,
+        SpanKind::Internal,
+        attributes,
+        &TraceContext::current(),
+    );
+    let result = future.with_context(cx.clone()).await;
+    if let Err(e) = &result {
+        record_error(&cx, e);
+    }
+    cx.span().end();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::HeaderMap;
+    use opentelemetry::{
+        global,
+        trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+        Context as TraceContext,
+    };
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+    use super::{extract_trace_context, inject_trace_context};
+
+    #[test]
+    fn it_propagates_trace_context_through_headers() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let cx = TraceContext::new().with_remote_span_context(span_context.clone());
+
+        let mut headers = HeaderMap::new();
+        inject_trace_context(&cx, &mut headers);
+        assert_eq!(
+            headers.get("traceparent").unwrap(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+
+        let extracted = extract_trace_context(&headers);
+        assert_eq!(extracted.span().span_context(), &span_context);
+    }
+}
@@ -8,23 +8,35 @@ use std::{
 use super::*;
 use hyper::{
     body,
-    header::{ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN},
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+        VARY,
+    },
     http::{request::Builder, HeaderValue},
-    Client, HeaderMap, Method, StatusCode,
+    Client, HeaderMap, Method, StatusCode, Uri,
 };
 use k8s_openapi::api::core::v1::Namespace;
 use stacks_devnet_api::{
-    api_config::{AuthConfig, ResponderConfig},
-    config::StacksDevnetConfig,
+    api_config::{
+        ApiConfigReloader, ApiKeyConfig, ApiKeyRole, AuditConfig, AuthConfig, ConfigError,
+        ConfigSources, ResponderConfig,
+    },
+    auth::load_key_files,
+    config::{StacksDevnet, StacksDevnetConfig},
+    diagnostics::DevnetComponentStatus,
     resources::service::{
         get_service_from_path_part, get_service_port, get_service_url, ServicePort,
         StacksDevnetService,
     },
+    responder::origin_matches,
     routes::{
-        get_standardized_path_parts, mutate_request_for_proxy, PathParts,
+        get_devnet_info_includes, get_devnet_list_params, get_devnet_wait_params,
+        get_standardized_path_parts, mutate_request_for_proxy, DevnetWaitParams, PathParts,
         StacksDevnetInfoWithMetadata,
     },
-    StacksDevnetInfoResponse,
+    DevnetInfoIncludes, DevnetListFilter, StacksDevnetInfoResponse,
 };
 use test_case::test_case;
 use tower_test::mock::{self, Handle};
@@ -60,6 +72,7 @@ async fn get_k8s_manager() -> (StacksDevnetApiK8sManager, Context) {
     let ctx = Context {
         logger: Some(logger),
         tracer: false,
+        request_id: None,
     };
     let k8s_manager = StacksDevnetApiK8sManager::new(&ctx).await;
     (k8s_manager, ctx)
@@ -77,6 +90,16 @@ fn get_random_namespace() -> String {
     format!("test-ns-{random_digit}")
 }
 
+/// Reads a response body, unwrapping the message of JSON error responses.
+fn get_body_message(bytes: Vec<u8>) -> String {
+    match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(body) if body["error"]["message"].is_string() => {
+            body["error"]["message"].as_str().unwrap().to_string()
+        }
+        _ => String::from_utf8(bytes).unwrap(),
+    }
+}
+
 fn assert_not_all_assets_exist_err((code, body): (StatusCode, String)) {
     assert_eq!(code, StatusCode::NOT_FOUND);
     assert!(body.starts_with("not all devnet assets exist NAMESPACE: test-ns-"));
@@ -95,9 +118,9 @@ fn assert_cannot_delete_devnet_multiple_errs((code, body): (StatusCode, String))
 
 fn assert_cannot_create_devnet_err((code, body): (StatusCode, String)) {
     assert_eq!(code, StatusCode::CONFLICT);
-    assert!(
-        body.starts_with("cannot create devnet because assets already exist NAMESPACE: test-ns-")
-    );
+    assert!(body.starts_with(
+        "cannot create devnet because assets of a different config already exist NAMESPACE: test-ns-"
+    ));
 }
 
 fn assert_failed_proxy((code, body): (StatusCode, String)) {
@@ -108,20 +131,38 @@ fn assert_failed_proxy((code, body): (StatusCode, String)) {
 fn assert_get_network((code, body): (StatusCode, String)) {
     assert_eq!(code, StatusCode::OK);
     let body: StacksDevnetInfoResponse = serde_json::from_str(&body).unwrap();
-    assert!(body.bitcoind_node_status.is_some());
-    assert!(body.stacks_node_status.is_some());
-    assert!(body.stacks_api_status.is_some());
-    assert!(body.bitcoind_node_started_at.is_some());
-    assert!(body.stacks_node_started_at.is_some());
+    let components: Vec<&str> = body.components.keys().map(|c| c.as_str()).collect();
+    assert_eq!(
+        components,
+        vec![
+            "bitcoind-chain-coordinator",
+            "stacks-blockchain",
+            "stacks-blockchain-api",
+            "stacks-signer-0",
+            "stacks-signer-1",
+        ]
+    );
+    let bitcoind_node = &body.components["bitcoind-chain-coordinator"];
+    assert!(bitcoind_node.phase.is_some());
+    assert!(bitcoind_node.started_at.is_some());
+    assert!(body.components["stacks-blockchain"].phase.is_some());
+    let stacks_api_containers: Vec<&str> = body.components["stacks-blockchain-api"]
+        .containers
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert!(stacks_api_containers.contains(&"postgres"));
 }
 
 enum TestBody {
     CreateNetwork,
+    CreateChangedNetwork,
 }
 
 #[test_case("/api/v1/network/{namespace}", Method::DELETE, None, false => is equal_to (StatusCode::OK, "Ok".to_string()); "200 for network DELETE request")]
 #[test_case("/api/v1/network/{namespace}", Method::DELETE, None, true => using assert_cannot_delete_devnet_multiple_errs; "500 for network DELETE request with multiple errors")]
-#[test_case("/api/v1/networks", Method::POST, Some(TestBody::CreateNetwork), true => using assert_cannot_create_devnet_err; "409 for create network POST request if devnet exists")]
+#[test_case("/api/v1/networks", Method::POST, Some(TestBody::CreateNetwork), true => is equal_to (StatusCode::OK, "Ok".to_string()); "200 for retried create network POST request if devnet exists")]
+#[test_case("/api/v1/networks", Method::POST, Some(TestBody::CreateChangedNetwork), true => using assert_cannot_create_devnet_err; "409 for create network POST request if devnet exists with a different config")]
 #[test_case("/api/v1/network/{namespace}", Method::GET, None, true => using assert_get_network; "200 for network GET request to existing network")]
 #[test_case("/api/v1/network/{namespace}", Method::HEAD, None, true => is equal_to (StatusCode::OK, "Ok".to_string()); "200 for network HEAD request to existing network")]
 #[test_case("/api/v1/network/{namespace}/stacks-blockchain/v2/info/", Method::GET, None, true => using assert_failed_proxy; "proxies requests to downstream nodes")]
@@ -163,6 +204,12 @@ async fn it_responds_to_valid_requests_with_deploy(
             config.namespace = namespace.to_owned();
             Body::from(serde_json::to_string(&config).unwrap())
         }
+        Some(TestBody::CreateChangedNetwork) => {
+            let mut config = get_template_config();
+            config.namespace = namespace.to_owned();
+            config.disable_stacks_api = !config.disable_stacks_api;
+            Body::from(serde_json::to_string(&config).unwrap())
+        }
     };
 
     let request: Request<Body> = request_builder.body(body).unwrap();
@@ -179,7 +226,7 @@ async fn it_responds_to_valid_requests_with_deploy(
 
     let body = response.body_mut();
     let bytes = body::to_bytes(body).await.unwrap().to_vec();
-    let mut body_str = String::from_utf8(bytes).unwrap();
+    let mut body_str = get_body_message(bytes);
     let mut status = response.status();
 
     if tear_down {
@@ -239,7 +286,7 @@ async fn it_responds_to_valid_requests(
 
     let body = response.body_mut();
     let bytes = body::to_bytes(body).await.unwrap().to_vec();
-    let body_str = String::from_utf8(bytes).unwrap();
+    let body_str = get_body_message(bytes);
 
     if set_up {
         k8s_manager.delete_namespace(namespace).await.unwrap();
@@ -475,33 +522,36 @@ async fn get_mock_k8s_manager() -> (StacksDevnetApiK8sManager, Context) {
     (k8s_manager, ctx)
 }
 
-#[test_case("/path", Method::GET, "some-user" => is equal_to (StatusCode::BAD_REQUEST, "invalid request path".to_string()) ; "400 for invalid requet path /path")]
-#[test_case("/api", Method::GET, "some-user" => is equal_to (StatusCode::BAD_REQUEST, "invalid request path".to_string()) ; "400 for invalid requet path /api")]
-#[test_case("/api/v1", Method::GET, "some-user" => is equal_to (StatusCode::BAD_REQUEST, "invalid request path".to_string()) ; "400 for invalid requet path /api/v1")]
-#[test_case("/api/v1/network2", Method::GET, "some-user" => is equal_to (StatusCode::BAD_REQUEST, "invalid request path".to_string()) ; "400 for invalid requet path /api/v1/network2")]
-#[test_case("/api/v1/network/undeployed", Method::GET, "undeployed" =>
+#[test_case("/path", Method::GET, "some-user", "BAD_REQUEST" => is equal_to (StatusCode::BAD_REQUEST, "invalid request path".to_string()) ; "400 for invalid requet path /path")]
+#[test_case("/api", Method::GET, "some-user", "BAD_REQUEST" => is equal_to (StatusCode::BAD_REQUEST, "invalid request path".to_string()) ; "400 for invalid requet path /api")]
+#[test_case("/api/v1", Method::GET, "some-user", "BAD_REQUEST" => is equal_to (StatusCode::BAD_REQUEST, "invalid request path".to_string()) ; "400 for invalid requet path /api/v1")]
+#[test_case("/api/v1/network2", Method::GET, "some-user", "BAD_REQUEST" => is equal_to (StatusCode::BAD_REQUEST, "invalid request path".to_string()) ; "400 for invalid requet path /api/v1/network2")]
+#[test_case("/api/v1/network/undeployed", Method::GET, "undeployed", "DEVNET_NOT_FOUND" =>
         is equal_to (StatusCode::NOT_FOUND, "network undeployed does not exist".to_string()); "404 for undeployed namespace")]
-#[test_case("/api/v1/network/500_err", Method::GET, "500_err" =>
+#[test_case("/api/v1/network/500_err", Method::GET, "500_err", "KUBERNETES_ERROR" =>
     is equal_to (StatusCode::INTERNAL_SERVER_ERROR, "error getting namespace 500_err: \"\"".to_string()); "forwarded error if fetching namespace returns error")]
-#[test_case("/api/v1/network/test", Method::POST, "test" =>
+#[test_case("/api/v1/network/test", Method::POST, "test", "METHOD_NOT_ALLOWED" =>
     is equal_to (StatusCode::METHOD_NOT_ALLOWED, "can only GET/DELETE/HEAD at provided route".to_string()); "405 for network route with POST request")]
-#[test_case("/api/v1/network/test/commands", Method::GET, "test" =>
-is equal_to (StatusCode::NOT_FOUND, "commands route in progress".to_string()); "404 for network commands route")]
-#[test_case("/api/v1/network/", Method::GET, "test" =>
+#[test_case("/api/v1/network/test/commands", Method::GET, "test", "NOT_IMPLEMENTED" =>
+is equal_to (StatusCode::NOT_IMPLEMENTED, "commands route in progress".to_string()); "501 for network commands route")]
+#[test_case("/api/v1/network/", Method::GET, "test", "BAD_REQUEST" =>
         is equal_to (StatusCode::BAD_REQUEST, "no network id provided".to_string()); "400 for missing namespace")]
-#[test_case("/api/v1/networks", Method::GET, "test" =>
+#[test_case("/api/v1/networks", Method::GET, "test", "METHOD_NOT_ALLOWED" =>
         is equal_to (StatusCode::METHOD_NOT_ALLOWED, "network creation must be a POST request".to_string()); "405 for network creation request with GET method")]
-#[test_case("/api/v1/networks", Method::DELETE, "test" =>
+#[test_case("/api/v1/networks", Method::DELETE, "test", "METHOD_NOT_ALLOWED" =>
         is equal_to (StatusCode::METHOD_NOT_ALLOWED, "network creation must be a POST request".to_string()); "405 for network creation request with DELETE method")]
-#[test_case("/api/v1/networks", Method::POST, "test" =>
+#[test_case("/api/v1/networks", Method::POST, "test", "INVALID_CONFIG" =>
         is equal_to (StatusCode::BAD_REQUEST, "invalid configuration to create network: EOF while parsing a value at line 1 column 0".to_string()); "400 for network creation request invalid config")]
-#[test_case("/api/v1/network/test", Method::GET, "wrong-id" =>
-        is equal_to (StatusCode::BAD_REQUEST, "network id must match authenticated user id".to_string()); "400 for request with non-matching user")]
+#[test_case("/api/v1/network/test", Method::GET, "wrong-id", "FORBIDDEN" =>
+        is equal_to (StatusCode::FORBIDDEN, "network id must match authenticated user id".to_string()); "403 for request with non-matching user")]
+#[test_case("/api/v1/admin/networks", Method::GET, "test", "FORBIDDEN" =>
+        is equal_to (StatusCode::FORBIDDEN, "this route requires an admin api key".to_string()); "403 for network listing without admin role")]
 #[tokio::test]
 async fn it_responds_to_invalid_requests(
     request_path: &str,
     method: Method,
     user_id: &str,
+    error_code: &str,
 ) -> (StatusCode, String) {
     let (k8s_manager, ctx) = get_mock_k8s_manager().await;
 
@@ -519,10 +569,45 @@ async fn it_responds_to_invalid_requests(
     .unwrap();
     let body = response.body_mut();
     let bytes = body::to_bytes(body).await.unwrap().to_vec();
-    let body_str = String::from_utf8(bytes).unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["error"]["code"], error_code);
+    let body_str = get_body_message(bytes);
     (response.status(), body_str)
 }
 
+#[test_case(Some(16 * 1024 * 1024 + 1), 0; "with a content length over the limit")]
+#[test_case(None, 16 * 1024 * 1024 + 1; "with a body over the limit")]
+#[tokio::test]
+async fn it_rejects_large_request_bodies(content_length: Option<usize>, body_size: usize) {
+    let (k8s_manager, ctx) = get_mock_k8s_manager().await;
+
+    let mut request_builder = get_request_builder("/api/v1/networks", Method::POST, "test");
+    if let Some(content_length) = content_length {
+        request_builder = request_builder.header("Content-Length", content_length);
+    }
+    let request: Request<Body> = request_builder
+        .body(Body::from(vec![b' '; body_size]))
+        .unwrap();
+    let request_store = Arc::new(Mutex::new(HashMap::new()));
+    let mut response = handle_request(
+        request,
+        k8s_manager.clone(),
+        ApiConfig::default(),
+        request_store,
+        ctx,
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let bytes = body::to_bytes(response.body_mut()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["error"]["code"], "PAYLOAD_TOO_LARGE");
+    assert_eq!(
+        body["error"]["message"],
+        "request body exceeds the maximum size of 16777216 bytes"
+    );
+}
+
 #[tokio::test]
 async fn it_responds_to_invalid_request_header() {
     let (k8s_manager, ctx) = get_mock_k8s_manager().await;
@@ -543,14 +628,27 @@ async fn it_responds_to_invalid_request_header() {
     .unwrap();
     let body = response.body_mut();
     let bytes = body::to_bytes(body).await.unwrap().to_vec();
-    let body_str = String::from_utf8(bytes).unwrap();
-    assert_eq!(response.status(), 400);
-    assert_eq!(body_str, "missing required auth header".to_string());
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(response.status(), 401);
+    assert_eq!(
+        response.headers().get("Content-Type").unwrap(),
+        "application/json"
+    );
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "error": {
+                "code": "UNAUTHORIZED",
+                "message": "missing required auth header"
+            }
+        })
+    );
 }
 
 #[test_case("/api/v1/network/test", Method::OPTIONS => is equal_to *"Ok")]
 #[test_case("/api/v1/status", Method::GET => is equal_to get_version_info() )]
 #[test_case("/", Method::GET => is equal_to get_version_info())]
+#[test_case("/healthz", Method::GET => is equal_to *r#"{"status":"ok"}"#)]
 #[tokio::test]
 async fn it_ignores_request_header_for_some_requests(request_path: &str, method: Method) -> String {
     let (k8s_manager, ctx) = get_mock_k8s_manager().await;
@@ -573,6 +671,407 @@ async fn it_ignores_request_header_for_some_requests(request_path: &str, method:
     String::from_utf8(bytes).unwrap()
 }
 
+#[tokio::test]
+async fn it_serves_openapi_description_without_auth() {
+    let (k8s_manager, ctx) = get_mock_k8s_manager().await;
+
+    let request_builder = Request::builder()
+        .uri("/api/v1/openapi.json")
+        .method(Method::GET);
+    let request: Request<Body> = request_builder.body(Body::empty()).unwrap();
+    let request_store = Arc::new(Mutex::new(HashMap::new()));
+    let mut response = handle_request(
+        request,
+        k8s_manager.clone(),
+        ApiConfig::default(),
+        request_store,
+        ctx,
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 200);
+    let body = response.body_mut();
+    let bytes = body::to_bytes(body).await.unwrap().to_vec();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["info"]["version"], VERSION);
+    assert!(body["paths"]["/api/v1/networks"]["post"].is_object());
+    assert!(body["components"]["schemas"]["StacksDevnetConfig"].is_object());
+}
+
+async fn mock_k8s_list_handler(handle: &mut Handle<Request<Body>, Response<Body>>) {
+    // deployments, stateful sets and pods are listed to find the managed devnets
+    for _ in 0..3 {
+        let (request, send) = handle.next_request().await.expect("Service not called");
+        assert_eq!(request.method(), Method::GET);
+        let body = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "List",
+            "metadata": {},
+            "items": []
+        });
+        send.send_response(
+            Response::builder()
+                .status(200)
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        );
+    }
+}
+
+#[tokio::test]
+async fn it_serves_metrics_to_admins() {
+    let (mock_service, mut handle) = mock::pair::<Request<Body>, Response<Body>>();
+    let _spawned = tokio::spawn(async move {
+        mock_k8s_list_handler(&mut handle).await;
+    });
+    let ctx = Context::empty();
+    let k8s_manager = StacksDevnetApiK8sManager::from_service(mock_service, "default", &ctx).await;
+    let request_store = Arc::new(Mutex::new(HashMap::new()));
+    let config = ApiConfig {
+        auth_config: AuthConfig {
+            api_keys: Some(vec![ApiKeyConfig {
+                name: "prometheus".into(),
+                key: "admin-key".into(),
+                role: ApiKeyRole::Admin,
+                user_id: None,
+                network_prefix: None,
+            }]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    update_devnet_metrics(&k8s_manager, &request_store, 0, &ctx).await;
+
+    for (path, api_key, status) in [
+        ("/api/v1/status", None, 200),
+        ("/metrics", None, 401),
+        ("/metrics", Some("admin-key"), 200),
+    ] {
+        let mut request_builder = Request::builder().uri(path).method(Method::GET);
+        if let Some(api_key) = api_key {
+            request_builder = request_builder.header("x-api-key", api_key);
+        }
+        let request = request_builder.body(Body::empty()).unwrap();
+        let mut response = handle_request(
+            request,
+            k8s_manager.clone(),
+            config.clone(),
+            request_store.clone(),
+            ctx.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), status);
+        if status == 200 && path == "/metrics" {
+            assert_eq!(
+                response.headers().get("Content-Type").unwrap(),
+                "text/plain; version=0.0.4"
+            );
+            let body = response.body_mut();
+            let bytes = body::to_bytes(body).await.unwrap().to_vec();
+            let body = String::from_utf8(bytes).unwrap();
+            assert!(body.contains(
+                "stacks_devnet_api_http_requests_total{method=\"GET\",route=\"/api/v1/status\",status=\"200\"}"
+            ));
+            assert!(body.contains(
+                "stacks_devnet_api_kube_requests_total{resource=\"deployments\",verb=\"get\"}"
+            ));
+            assert!(body.contains("stacks_devnet_api_active_devnets 0"));
+        }
+    }
+}
+
+#[tokio::test]
+async fn it_reports_readiness_without_auth() {
+    let (mock_service, mut handle) = mock::pair::<Request<Body>, Response<Body>>();
+    let _spawned = tokio::spawn(async move {
+        for status in [200, 403] {
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.uri().to_string(), "/api/v1/namespaces/default");
+            let body = match status {
+                200 => serde_json::to_vec(&serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "Namespace",
+                    "metadata": { "name": "default" },
+                }))
+                .unwrap(),
+                _ => vec![],
+            };
+            send.send_response(
+                Response::builder()
+                    .status(status)
+                    .body(Body::from(body))
+                    .unwrap(),
+            );
+        }
+    });
+    let ctx = Context::empty();
+    let k8s_manager = StacksDevnetApiK8sManager::from_service(mock_service, "default", &ctx).await;
+    let request_store = Arc::new(Mutex::new(HashMap::new()));
+    let get_readiness = |config: ApiConfig| {
+        let request = Request::builder()
+            .uri("/readyz")
+            .method(Method::GET)
+            .body(Body::empty())
+            .unwrap();
+        handle_request(
+            request,
+            k8s_manager.clone(),
+            config,
+            request_store.clone(),
+            ctx.clone(),
+        )
+    };
+
+    let mut response = get_readiness(ApiConfig::default()).await.unwrap();
+    assert_eq!(response.status(), 200);
+    let bytes = body::to_bytes(response.body_mut()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "status": "ready",
+            "checks": { "kubernetes": { "ok": true }, "config": { "ok": true } }
+        })
+    );
+
+    let mut config = ApiConfig {
+        auth_config: AuthConfig {
+            api_keys_path: Some("src/tests/fixtures/missing-api-keys.toml".into()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    load_key_files(&mut config.auth_config);
+    let mut response = get_readiness(config).await.unwrap();
+    assert_eq!(response.status(), 503);
+    let bytes = body::to_bytes(response.body_mut()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["error"]["code"], "SERVICE_UNAVAILABLE");
+    assert_eq!(
+        body["error"]["message"],
+        "readiness checks failed: kubernetes, config"
+    );
+    let checks = &body["error"]["details"]["checks"];
+    assert_eq!(checks["kubernetes"]["ok"], false);
+    assert_eq!(checks["config"]["ok"], false);
+    assert!(checks["config"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("unable to read api keys file"));
+}
+
+#[tokio::test]
+async fn it_waits_for_operator_devnets_to_be_deployed() {
+    let (mock_service, mut handle) = mock::pair::<Request<Body>, Response<Body>>();
+    let _spawned = tokio::spawn(async move {
+        while let Some((request, send)) = handle.next_request().await {
+            let (body, status) = match request.uri().path() {
+                "/api/v1/namespaces/operator-test" => (
+                    serde_json::json!({
+                        "apiVersion": "v1",
+                        "kind": "Namespace",
+                        "metadata": { "name": "operator-test" },
+                    }),
+                    200,
+                ),
+                "/apis/devnet.hiro.so/v1alpha1/namespaces/operator-test/stacksdevnets/operator-test" => {
+                    let mut devnet = StacksDevnet::new("operator-test", get_template_config());
+                    devnet.metadata.namespace = Some("operator-test".into());
+                    (serde_json::to_value(&devnet).unwrap(), 200)
+                }
+                // the devnet's resources haven't been deployed by the operator yet
+                _ => (
+                    serde_json::json!({
+                        "apiVersion": "v1",
+                        "kind": "Status",
+                        "status": "Failure",
+                        "message": "not found",
+                        "reason": "NotFound",
+                        "code": 404,
+                    }),
+                    404,
+                ),
+            };
+            send.send_response(
+                Response::builder()
+                    .status(status)
+                    .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                    .unwrap(),
+            );
+        }
+    });
+    let ctx = Context::empty();
+    let k8s_manager = StacksDevnetApiK8sManager::from_service(mock_service, "default", &ctx)
+        .await
+        .with_operator(true);
+    let request_store = Arc::new(Mutex::new(HashMap::new()));
+
+    let request = get_request_builder(
+        "/api/v1/network/operator-test/wait?timeout=1",
+        Method::GET,
+        "operator-test",
+    )
+    .body(Body::empty())
+    .unwrap();
+    let mut response = handle_request(
+        request,
+        k8s_manager,
+        ApiConfig::default(),
+        request_store,
+        ctx,
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 504);
+    let bytes = body::to_bytes(response.body_mut()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["error"]["code"], "DEVNET_NOT_READY");
+    assert_eq!(
+        body["error"]["details"]["unmet_conditions"],
+        serde_json::json!(["network operator-test has not been deployed yet"])
+    );
+}
+
+#[tokio::test]
+async fn it_audits_devnet_and_admin_actions() {
+    let (k8s_manager, ctx) = get_mock_k8s_manager().await;
+    let dir = tempfile::tempdir().unwrap();
+    let audit_path = dir.path().join("audit.jsonl");
+    let config = ApiConfig {
+        auth_config: AuthConfig {
+            api_keys: Some(vec![ApiKeyConfig {
+                name: "support".into(),
+                key: "admin-key".into(),
+                role: ApiKeyRole::Admin,
+                user_id: None,
+                network_prefix: None,
+            }]),
+            ..Default::default()
+        },
+        audit_config: AuditConfig {
+            path: Some(audit_path.to_str().unwrap().into()),
+        },
+        ..Default::default()
+    };
+    let request_store = Arc::new(Mutex::new(HashMap::new()));
+
+    let request = get_request_builder("/api/v1/networks", Method::POST, "test")
+        .header("X-Request-Id", "create-request")
+        .body(Body::from("{}"))
+        .unwrap();
+    let response = handle_request(
+        request,
+        k8s_manager.clone(),
+        config.clone(),
+        request_store.clone(),
+        ctx.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let request = Request::builder()
+        .uri("/api/v1/admin/audit?action=create_devnet")
+        .method(Method::GET)
+        .header("x-api-key", "admin-key")
+        .body(Body::empty())
+        .unwrap();
+    let mut response = handle_request(
+        request,
+        k8s_manager.clone(),
+        config.clone(),
+        request_store.clone(),
+        ctx.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 200);
+    let body = response.body_mut();
+    let bytes = body::to_bytes(body).await.unwrap().to_vec();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let records = body["records"].as_array().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["action"], "create_devnet");
+    assert_eq!(records[0]["request_id"], "create-request");
+    assert_eq!(records[0]["user_id"], "test");
+    assert_eq!(records[0]["outcome"], "failure");
+    assert_eq!(records[0]["error_code"], "INVALID_CONFIG");
+    assert_eq!(
+        records[0]["config_hash"],
+        "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+    );
+
+    // the query is audited too
+    let audit_log = std::fs::read_to_string(&audit_path).unwrap();
+    let actions: Vec<serde_json::Value> = audit_log
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["action"].clone())
+        .collect();
+    assert_eq!(actions, vec!["create_devnet", "query_audit_log"]);
+
+    let request = get_request_builder("/api/v1/admin/audit", Method::GET, "test")
+        .body(Body::empty())
+        .unwrap();
+    let response = handle_request(request, k8s_manager, config, request_store, ctx)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+}
+
+#[tokio::test]
+async fn it_echoes_or_generates_request_ids() {
+    let (mock_service, _handle) = mock::pair::<Request<Body>, Response<Body>>();
+    let ctx = Context::empty();
+    let k8s_manager = StacksDevnetApiK8sManager::from_service(mock_service, "default", &ctx).await;
+    let request_store = Arc::new(Mutex::new(HashMap::new()));
+
+    for (request_id, echoed) in [
+        (Some("some-request-id"), true),
+        (None, false),
+        (Some("not a valid id"), false),
+    ] {
+        let mut builder = Request::builder().uri("/api/v1/status").method(Method::GET);
+        if let Some(request_id) = request_id {
+            builder = builder.header("X-Request-Id", request_id);
+        }
+        let response = handle_request(
+            builder.body(Body::empty()).unwrap(),
+            k8s_manager.clone(),
+            ApiConfig::default(),
+            request_store.clone(),
+            ctx.clone(),
+        )
+        .await
+        .unwrap();
+        let response_id = response
+            .headers()
+            .get("X-Request-Id")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        if echoed {
+            assert_eq!(response_id, request_id.unwrap());
+        } else {
+            assert_eq!(response_id.len(), 36);
+            assert_ne!(Some(response_id), request_id);
+        }
+    }
+}
+
+#[test_case("/" => is equal_to "/"; "for root path")]
+#[test_case("/api/v1/status/" => is equal_to "/api/v1/status"; "for status path")]
+#[test_case("/metrics" => is equal_to "/metrics"; "for metrics path")]
+#[test_case("/readyz" => is equal_to "/readyz"; "for readiness path")]
+#[test_case("/api/v1/network/some-network" => is equal_to "/api/v1/network/{network}"; "for network path")]
+#[test_case("/api/v1/network/some-network/stacks-blockchain/v2/info" => is equal_to "/api/v1/network/{network}/stacks-blockchain"; "for proxied path")]
+#[test_case("/api/v1/network/some-network/wait" => is equal_to "/api/v1/network/{network}/wait"; "for wait path")]
+#[test_case("/api/v1/network/some-network/some-subroute/path" => is equal_to "/api/v1/network/{network}/{subroute}"; "for unknown subroute")]
+#[test_case("/wp-admin" => is equal_to "unknown"; "for unknown path")]
+fn it_gets_route_template(path: &str) -> String {
+    get_route_template(path)
+}
+
 #[test_case("" => is equal_to PathParts { route: String::new(), ..Default::default() }; "for empty path")]
 #[test_case("/api/v1/" => is equal_to PathParts { route: String::new(), ..Default::default() }; "for /api/v1/ path")]
 #[test_case("/api/v1/some-route" => is equal_to PathParts { route: String::from("some-route"), ..Default::default() }; "for /api/v1/some-route path")]
@@ -588,6 +1087,89 @@ fn request_paths_are_parsed_correctly(path: &str) -> PathParts {
     get_standardized_path_parts(path)
 }
 
+#[test_case("/api/v1/admin/networks" => is equal_to Ok((DevnetListFilter::default(), 0, 50)); "with defaults")]
+#[test_case("/api/v1/admin/networks?offset=100&limit=25&owner=platform-user&phase=Failed" =>
+    is equal_to Ok((DevnetListFilter { owner: Some("platform-user".into()), phase: Some("Failed".into()), ..Default::default() }, 100, 25)); "with pagination and filters")]
+#[test_case("/api/v1/admin/networks?namespace_prefix=platform%2Dci%2D&owner=platform+user" =>
+    is equal_to Ok((DevnetListFilter { owner: Some("platform user".into()), namespace_prefix: Some("platform-ci-".into()), ..Default::default() }, 0, 50)); "with encoded filters")]
+#[test_case("/api/v1/admin/networks?limit=1000" => is equal_to Err("limit query parameter must be between 1 and 500".to_string()); "with limit too large")]
+#[test_case("/api/v1/admin/networks?offset=-1" => is equal_to Err("invalid offset query parameter: -1".to_string()); "with invalid offset")]
+fn network_list_params_are_parsed_correctly(
+    path: &str,
+) -> Result<(DevnetListFilter, usize, usize), String> {
+    get_devnet_list_params(&path.parse::<Uri>().unwrap())
+}
+
+#[test_case("/api/v1/network/test/wait" => is equal_to Ok(DevnetWaitParams { min_stacks_height: None, min_burn_height: None, timeout: Duration::from_secs(60) }); "with defaults")]
+#[test_case("/api/v1/network/test/wait?condition=ready&min_stacks_height=10&min_burn_height=120&timeout=300" =>
+    is equal_to Ok(DevnetWaitParams { min_stacks_height: Some(10), min_burn_height: Some(120), timeout: Duration::from_secs(300) }); "with heights and timeout")]
+#[test_case("/api/v1/network/test/wait?condition=deleted" => is equal_to Err("invalid condition query parameter: deleted".to_string()); "with unknown condition")]
+#[test_case("/api/v1/network/test/wait?timeout=3600" => is equal_to Err("timeout query parameter must be between 1 and 300 seconds".to_string()); "with timeout too long")]
+#[test_case("/api/v1/network/test/wait?min_stacks_height=tip" => is equal_to Err("invalid min_stacks_height query parameter: tip".to_string()); "with invalid height")]
+fn network_wait_params_are_parsed_correctly(path: &str) -> Result<DevnetWaitParams, String> {
+    get_devnet_wait_params(&path.parse::<Uri>().unwrap())
+}
+
+#[test_case("/api/v1/network/test" => is equal_to Ok(DevnetInfoIncludes::default()); "with defaults")]
+#[test_case("/api/v1/network/test?include=chain" => is equal_to Ok(DevnetInfoIncludes { chain: true, ..Default::default() }); "with chain")]
+#[test_case("/api/v1/network/test?include=tips%2Cchain%2Cdeployment" => is equal_to Ok(DevnetInfoIncludes { tips: true, chain: true, deployment: true }); "with encoded list")]
+#[test_case("/api/v1/network/test?include=deployment,logs" => is equal_to Err("invalid include query parameter: logs".to_string()); "with unknown include")]
+fn network_info_includes_are_parsed_correctly(path: &str) -> Result<DevnetInfoIncludes, String> {
+    get_devnet_info_includes(&path.parse::<Uri>().unwrap())
+}
+
+#[test]
+fn it_gets_unmet_wait_conditions() {
+    let running = |ready: bool| DevnetComponentStatus {
+        phase: Some("Running".into()),
+        ready,
+        ..Default::default()
+    };
+    let mut devnet_info = StacksDevnetInfoResponse {
+        components: [
+            ("bitcoind-chain-coordinator".to_string(), running(true)),
+            ("stacks-blockchain".to_string(), running(false)),
+            (
+                "stacks-signer-0".to_string(),
+                DevnetComponentStatus {
+                    phase: Some("Pending".into()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "stacks-signer-1".to_string(),
+                DevnetComponentStatus::default(),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        stacks_chain_tip: Some(5),
+        bitcoin_chain_tip: Some(110),
+        chain: None,
+        deployment: None,
+    };
+    let params = DevnetWaitParams {
+        min_stacks_height: Some(10),
+        min_burn_height: Some(110),
+        timeout: Duration::from_secs(60),
+    };
+    assert_eq!(
+        params.get_unmet_conditions(&devnet_info),
+        vec![
+            "stacks-blockchain is not ready",
+            "stacks-signer-0 is Pending",
+            "stacks-signer-1 has not been created",
+            "stacks chain tip 5 is below 10",
+        ]
+    );
+
+    for status in devnet_info.components.values_mut() {
+        *status = running(true);
+    }
+    devnet_info.stacks_chain_tip = Some(10);
+    assert!(params.get_unmet_conditions(&devnet_info).is_empty());
+}
+
 #[tokio::test]
 async fn request_mutation_should_create_valid_proxy_destination() {
     let path = "/api/v1/some-route/some-network/stacks-blockchain/the//remaining///path";
@@ -622,6 +1204,8 @@ fn responder_allows_configuring_allowed_origins() {
         allowed_origins: Some(vec!["*".to_string()]),
         allowed_methods: Some(vec!["GET".to_string()]),
         allowed_headers: None,
+        exposed_headers: Some(vec!["x-request-id".to_string()]),
+        max_age: None,
     };
     let mut headers = HeaderMap::new();
     headers.append("ORIGIN", HeaderValue::from_str("example.com").unwrap());
@@ -629,10 +1213,86 @@ fn responder_allows_configuring_allowed_origins() {
     let builder = responder.response_builder();
     let built_headers = builder.headers_ref().unwrap();
     assert_eq!(built_headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "*");
+    assert!(built_headers
+        .get(ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        .is_none());
+    assert_eq!(built_headers.get(VARY).unwrap(), "Origin");
+    assert_eq!(
+        built_headers.get(ACCESS_CONTROL_EXPOSE_HEADERS).unwrap(),
+        "x-request-id"
+    );
+}
+
+fn preflight(origin: &str, method: &str) -> (StatusCode, HeaderMap) {
+    let config = ResponderConfig {
+        allowed_origins: Some(vec![
+            "https://*.example.com".to_string(),
+            "localhost:3002".to_string(),
+        ]),
+        allowed_methods: Some(vec!["GET".to_string(), "DELETE".to_string()]),
+        allowed_headers: None,
+        exposed_headers: None,
+        max_age: Some(60),
+    };
+    let mut headers = HeaderMap::new();
+    headers.append("ORIGIN", HeaderValue::from_str(origin).unwrap());
+    headers.append(
+        ACCESS_CONTROL_REQUEST_METHOD,
+        HeaderValue::from_str(method).unwrap(),
+    );
+    headers.append(
+        ACCESS_CONTROL_REQUEST_HEADERS,
+        HeaderValue::from_static("x-api-key"),
+    );
+    let responder = Responder::new(config, headers, Context::empty()).unwrap();
+    assert!(responder.is_preflight());
+    let response = responder.preflight().unwrap();
+    (response.status(), response.headers().clone())
+}
+
+#[test]
+fn responder_answers_cors_preflight() {
+    let (status, headers) = preflight("https://app.example.com", "DELETE");
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(
+        headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+        "https://app.example.com"
+    );
+    assert_eq!(
+        headers.get(ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(),
+        "true"
+    );
     assert_eq!(
-        built_headers.get(ACCESS_CONTROL_ALLOW_METHODS).unwrap(),
-        "GET"
+        headers.get(ACCESS_CONTROL_ALLOW_METHODS).unwrap(),
+        "GET, DELETE"
     );
+    assert_eq!(
+        headers.get(ACCESS_CONTROL_ALLOW_HEADERS).unwrap(),
+        "x-api-key"
+    );
+    assert_eq!(headers.get(ACCESS_CONTROL_MAX_AGE).unwrap(), "60");
+
+    let (status, _) = preflight("http://localhost:3002", "GET");
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, headers) = preflight("https://example.com.evil.io", "GET");
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+    let (status, _) = preflight("https://app.example.com", "POST");
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[test_case("*", "https://any.io" => true; "for any origin")]
+#[test_case("https://*.example.com", "https://a.b.example.com" => true; "for nested subdomain")]
+#[test_case("https://*.example.com", "https://example.com" => false; "for bare domain")]
+#[test_case("https://*.example.com", "http://a.example.com" => false; "for other scheme")]
+#[test_case("https://*.example.com", "https://evil.io/.example.com" => false; "for path injection")]
+#[test_case("*.example.com", "http://a.example.com" => true; "for pattern without scheme")]
+#[test_case("dev.platform.so", "https://dev.platform.so" => true; "for exact origin without scheme")]
+#[test_case("https://dev.platform.so", "https://dev.platform.so:8080" => false; "for other port")]
+fn origins_are_matched_correctly(pattern: &str, origin: &str) -> bool {
+    origin_matches(pattern, origin)
 }
 
 #[serial_test::serial]
@@ -674,7 +1334,7 @@ async fn namespace_prefix_config_prepends_header() {
 
     let body = response.body_mut();
     let bytes = body::to_bytes(body).await.unwrap().to_vec();
-    let body_str = String::from_utf8(bytes).unwrap();
+    let body_str = get_body_message(bytes);
     assert_eq!(response.status(), 404);
     assert_eq!(body_str, "not found");
 }
@@ -688,6 +1348,210 @@ fn config_reads_from_file() {
     assert!(config.auth_config.namespace_prefix.is_some());
 }
 
+#[test]
+fn config_layers_env_and_flags_over_file() {
+    let env = HashMap::from([
+        ("CONFIG_PATH".to_string(), "Config.toml".to_string()),
+        ("PORT".to_string(), "9000".to_string()),
+        (
+            "STACKS_DEVNET_API_AUTH_HEADER".to_string(),
+            "x-env-user".to_string(),
+        ),
+        (
+            "STACKS_DEVNET_API_ALLOWED_ORIGINS".to_string(),
+            "a.com, b.com".to_string(),
+        ),
+        (
+            "STACKS_DEVNET_API_WEBHOOK_MAX_ATTEMPTS".to_string(),
+            "3".to_string(),
+        ),
+    ]);
+    let args: Vec<String> = vec![
+        "--auth-header=x-flag-user".into(),
+        "--host".into(),
+        "127.0.0.1".into(),
+        "--webhook-ready-timeout=60".into(),
+    ];
+    let config = ConfigSources::from_env_and_args(&env, &args)
+        .unwrap()
+        .load()
+        .unwrap();
+    assert_eq!(config.server_config.host, Some("127.0.0.1".to_string()));
+    assert_eq!(config.server_config.port, Some(9000));
+    assert_eq!(
+        config.auth_config.auth_header,
+        Some("x-flag-user".to_string())
+    );
+    assert_eq!(
+        config.http_response_config.allowed_origins,
+        Some(vec!["a.com".to_string(), "b.com".to_string()])
+    );
+    assert_eq!(config.webhook_config.max_attempts, Some(3));
+    assert_eq!(config.webhook_config.ready_timeout, Some(60));
+    // untouched fields come from the file
+    assert_eq!(
+        config.auth_config.namespace_prefix,
+        Some("platform-".to_string())
+    );
+
+    // no config file is needed
+    let config =
+        ConfigSources::from_env_and_args(&HashMap::new(), &["--auth-mode".into(), "jwt".into()])
+            .unwrap()
+            .load()
+            .unwrap();
+    assert!(config.auth_config.mode.is_some());
+}
+
+#[test_case(&["--port", "http"] => is equal_to ConfigError { source: "--port".into(), message: "expected a number, got http".into() }; "for invalid number")]
+#[test_case(&["--auth-header"] => is equal_to ConfigError { source: "--auth-header".into(), message: "missing value".into() }; "for missing value")]
+#[test_case(&["--unknown=1"] => is equal_to ConfigError { source: "--unknown".into(), message: "unknown flag, see --help".into() }; "for unknown flag")]
+fn config_reports_invalid_flags(args: &[&str]) -> ConfigError {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    ConfigSources::from_env_and_args(&HashMap::new(), &args).unwrap_err()
+}
+
+#[test]
+fn config_reports_invalid_values() {
+    let err = ConfigSources::from_env_and_args(&HashMap::new(), &["--auth-mode=oauth".into()])
+        .unwrap()
+        .load()
+        .unwrap_err();
+    assert_eq!(err.source, "config");
+    assert!(err
+        .message
+        .starts_with("invalid config: unknown variant `oauth`"));
+
+    let err = ConfigSources::from_env_and_args(
+        &HashMap::new(),
+        &["--exposed-headers=x-request-id,x-bad\nheader".into()],
+    )
+    .unwrap()
+    .load()
+    .unwrap_err();
+    assert_eq!(
+        err.message,
+        "http_response.exposed_headers is not a valid header value: x-request-id, x-bad\nheader"
+    );
+}
+
+#[test]
+fn config_reads_tracing_flags() {
+    let env = HashMap::from([(
+        "STACKS_DEVNET_API_OTLP_ENDPOINT".to_string(),
+        "http://otel-collector:4317".to_string(),
+    )]);
+    let config = ConfigSources::from_env_and_args(&env, &["--tracing-sample-ratio=0.25".into()])
+        .unwrap()
+        .load()
+        .unwrap();
+    assert_eq!(
+        config.tracing_config.otlp_endpoint.as_deref(),
+        Some("http://otel-collector:4317")
+    );
+    assert_eq!(config.tracing_config.sample_ratio, Some(0.25));
+
+    let err =
+        ConfigSources::from_env_and_args(&HashMap::new(), &["--tracing-sample-ratio=all".into()])
+            .unwrap_err();
+    assert_eq!(err.source, "--tracing-sample-ratio");
+    assert_eq!(err.message, "expected a number, got all");
+}
+
+#[test]
+fn config_reads_operator_flag() {
+    let config = ConfigSources::from_env_and_args(&HashMap::new(), &[])
+        .unwrap()
+        .load()
+        .unwrap();
+    assert!(!config.operator_config.is_enabled());
+
+    let env = HashMap::from([(
+        "STACKS_DEVNET_API_OPERATOR_ENABLED".to_string(),
+        "true".to_string(),
+    )]);
+    let config = ConfigSources::from_env_and_args(&env, &[])
+        .unwrap()
+        .load()
+        .unwrap();
+    assert!(config.operator_config.is_enabled());
+
+    let err = ConfigSources::from_env_and_args(&HashMap::new(), &["--operator-enabled=yes".into()])
+        .unwrap_err();
+    assert_eq!(err.source, "--operator-enabled");
+    assert_eq!(err.message, "expected true or false, got yes");
+}
+
+#[test]
+fn config_reloads_from_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("Config.toml");
+    let config_path = config_path.to_str().unwrap();
+    std::fs::copy("Config.toml", config_path).unwrap();
+
+    let sources =
+        ConfigSources::from_env_and_args(&HashMap::new(), &["--config".into(), config_path.into()])
+            .unwrap();
+    let config = Arc::new(RwLock::new(sources.load().unwrap()));
+    let mut reloader = ApiConfigReloader::new(sources, config.clone());
+    assert_eq!(reloader.reload(), Ok(false));
+
+    let updated = std::fs::read_to_string("Config.toml").unwrap().replace(
+        "allowed_origins = [\"*\"]",
+        "allowed_origins = [\"example.com\"]",
+    );
+    std::fs::write(config_path, updated).unwrap();
+    assert_eq!(reloader.reload(), Ok(true));
+    assert_eq!(
+        config.read().unwrap().http_response_config.allowed_origins,
+        Some(vec!["example.com".to_string()])
+    );
+
+    // malformed configs are rejected and the current config is kept
+    std::fs::write(config_path, "[http_response\n").unwrap();
+    assert!(reloader.reload().is_err());
+    assert_eq!(
+        config.read().unwrap().http_response_config.allowed_origins,
+        Some(vec!["example.com".to_string()])
+    );
+}
+
+#[test]
+fn config_reloads_changed_key_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let api_keys_path = dir.path().join("api-keys.toml");
+    let api_keys_path = api_keys_path.to_str().unwrap();
+    let write_api_key = |name: &str| {
+        let api_keys =
+            format!("[[api_keys]]\nname = \"{name}\"\nkey = \"secret\"\nrole = \"admin\"\n");
+        std::fs::write(api_keys_path, api_keys).unwrap();
+    };
+    write_api_key("support");
+
+    let sources = ConfigSources::from_env_and_args(
+        &HashMap::new(),
+        &["--api-keys-path".into(), api_keys_path.into()],
+    )
+    .unwrap();
+    let config = Arc::new(RwLock::new(sources.load().unwrap()));
+    let mut reloader = ApiConfigReloader::new(sources, config.clone());
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-key", HeaderValue::from_static("secret"));
+    let authenticate_api_key = || {
+        authenticate(&headers, None, &config.read().unwrap().auth_config)
+            .unwrap()
+            .user_id
+    };
+    assert_eq!(authenticate_api_key(), "support");
+    assert_eq!(reloader.reload(), Ok(false));
+
+    // the file is only read again when it changes
+    write_api_key("on-call");
+    assert_eq!(authenticate_api_key(), "support");
+    assert_eq!(reloader.reload(), Ok(true));
+    assert_eq!(authenticate_api_key(), "on-call");
+}
+
 #[tokio::test]
 async fn main_starts_server() {
     let _handle = std::thread::spawn(move || {
@@ -0,0 +1,197 @@
+use std::{
+    fs,
+    io::BufReader,
+    sync::{Arc, RwLock},
+};
+
+use tokio_rustls::{
+    rustls::{
+        server::AllowAnyAuthenticatedClient, Certificate, PrivateKey, RootCertStore, ServerConfig,
+    },
+    TlsAcceptor,
+};
+use x509_parser::parse_x509_certificate;
+
+use crate::api_config::TlsConfig;
+
+/// The identity of a client that authenticated with a certificate,
+/// attached to each request of the connection as an extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientCertificate {
+    pub common_name: String,
+}
+
+impl ClientCertificate {
+    /// Reads the subject common name of the leaf certificate of a verified client chain.
+    pub fn from_chain(chain: &[Certificate]) -> Option<ClientCertificate> {
+        let (_, certificate) = parse_x509_certificate(&chain.first()?.0).ok()?;
+        let common_name = certificate
+            .subject()
+            .iter_common_name()
+            .next()?
+            .as_str()
+            .ok()?
+            .to_string();
+        Some(ClientCertificate { common_name })
+    }
+}
+
+pub fn load_tls_acceptor(tls_config: &TlsConfig) -> Result<TlsAcceptor, String> {
+    let certs = read_certs(&tls_config.cert_path)?;
+    let key = read_private_key(&tls_config.key_path)?;
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let mut server_config = match &tls_config.client_ca_path {
+        Some(client_ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in read_certs(client_ca_path)? {
+                roots
+                    .add(&cert)
+                    .map_err(|e| format!("invalid certificate in {client_ca_path}: {e}"))?;
+            }
+            builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+        }
+        None => builder.with_no_client_auth(),
+    }
+    .with_single_cert(certs, key)
+    .map_err(|e| format!("invalid tls certificate or key: {e}"))?;
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+fn read_certs(path: &str) -> Result<Vec<Certificate>, String> {
+    let file = fs::read(path).map_err(|e| format!("unable to read file {path}: {e}"))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file.as_slice()))
+        .map_err(|e| format!("unable to parse certificates in {path}: {e}"))?;
+    if certs.is_empty() {
+        return Err(format!("no certificates found in {path}"));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_private_key(path: &str) -> Result<PrivateKey, String> {
+    let file = fs::read(path).map_err(|e| format!("unable to read file {path}: {e}"))?;
+    let items = rustls_pemfile::read_all(&mut BufReader::new(file.as_slice()))
+        .map_err(|e| format!("unable to parse private key in {path}: {e}"))?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or(format!("no private key found in {path}"))
+}
+
+/// Keeps a shared `TlsAcceptor` in sync with its certificate, key and client CA files,
+/// so renewed certificates are picked up by new connections without a restart.
+/// Files that fail to load are rejected and the current acceptor is kept.
+pub struct TlsAcceptorReloader {
+    tls_config: TlsConfig,
+    acceptor: Arc<RwLock<TlsAcceptor>>,
+    file_buffers: Vec<Vec<u8>>,
+}
+
+impl TlsAcceptorReloader {
+    pub fn new(tls_config: TlsConfig, acceptor: Arc<RwLock<TlsAcceptor>>) -> TlsAcceptorReloader {
+        let file_buffers = read_tls_files(&tls_config);
+        TlsAcceptorReloader {
+            tls_config,
+            acceptor,
+            file_buffers,
+        }
+    }
+
+    /// Reloads the acceptor if any of its files changed. Returns whether the acceptor was swapped.
+    pub fn reload(&mut self) -> Result<bool, String> {
+        let file_buffers = read_tls_files(&self.tls_config);
+        if file_buffers == self.file_buffers {
+            return Ok(false);
+        }
+        // certificates and keys are often replaced one at a time, so a failed
+        // load is retried on the next change rather than remembered
+        let acceptor = load_tls_acceptor(&self.tls_config)?;
+        match self.acceptor.write() {
+            Ok(mut current) => *current = acceptor,
+            Err(e) => return Err(format!("unable to update tls acceptor: {e}")),
+        }
+        self.file_buffers = file_buffers;
+        Ok(true)
+    }
+}
+
+fn read_tls_files(tls_config: &TlsConfig) -> Vec<Vec<u8>> {
+    [
+        Some(&tls_config.cert_path),
+        Some(&tls_config.key_path),
+        tls_config.client_ca_path.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|path| fs::read(path).unwrap_or_default())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use super::{load_tls_acceptor, read_certs, ClientCertificate, TlsAcceptorReloader};
+    use crate::api_config::TlsConfig;
+
+    fn get_tls_config() -> TlsConfig {
+        TlsConfig {
+            cert_path: "src/tests/fixtures/tls-server.pem".into(),
+            key_path: "src/tests/fixtures/tls-server-key.pem".into(),
+            client_ca_path: Some("src/tests/fixtures/tls-ca.pem".into()),
+        }
+    }
+
+    #[test]
+    fn it_loads_tls_acceptor() {
+        assert!(load_tls_acceptor(&get_tls_config()).is_ok());
+
+        let tls_config = TlsConfig {
+            key_path: "src/tests/fixtures/tls-server.pem".into(),
+            ..get_tls_config()
+        };
+        let err = load_tls_acceptor(&tls_config).err().unwrap();
+        assert_eq!(
+            err,
+            "no private key found in src/tests/fixtures/tls-server.pem"
+        );
+    }
+
+    #[test]
+    fn it_reads_client_certificate_common_name() {
+        let chain = read_certs("src/tests/fixtures/tls-client.pem").unwrap();
+        assert_eq!(
+            ClientCertificate::from_chain(&chain),
+            Some(ClientCertificate {
+                common_name: "auth0|some-user".into()
+            })
+        );
+        assert_eq!(ClientCertificate::from_chain(&[]), None);
+    }
+
+    #[test]
+    fn it_reloads_changed_tls_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert_path = dir.path().join("tls.pem");
+        std::fs::copy("src/tests/fixtures/tls-server.pem", &cert_path).unwrap();
+        let tls_config = TlsConfig {
+            cert_path: cert_path.to_str().unwrap().into(),
+            ..get_tls_config()
+        };
+        let acceptor = Arc::new(RwLock::new(load_tls_acceptor(&tls_config).unwrap()));
+        let mut reloader = TlsAcceptorReloader::new(tls_config, acceptor);
+        assert_eq!(reloader.reload(), Ok(false));
+
+        // a partially written certificate is rejected
+        std::fs::write(&cert_path, "-----BEGIN CERT").unwrap();
+        assert!(reloader.reload().is_err());
+
+        std::fs::copy("src/tests/fixtures/tls-server.pem", &cert_path).unwrap();
+        assert_eq!(reloader.reload(), Ok(false));
+    }
+}
@@ -0,0 +1,784 @@
+use std::{
+    fs::OpenOptions,
+    future::Future,
+    io::{self, Write},
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::LazyLock,
+    task::{self, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use hiro_system_kit::slog;
+use hmac::{Hmac, Mac};
+use hyper::{
+    body::Bytes,
+    client::{
+        connect::dns::{GaiResolver, Name},
+        HttpConnector,
+    },
+    header::CONTENT_TYPE,
+    Body, Client, Method, Request, StatusCode, Uri,
+};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::time::{sleep, timeout, Instant};
+use tower::Service;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    api_config::WebhookConfig,
+    config::{is_valid_webhook_url, ConfigValidationError},
+    responder::origin_matches,
+    routes::DevnetWaitParams,
+    Context, DevnetInfoIncludes, StacksDevnetApiK8sManager, StacksDevnetInfoResponse,
+};
+
+pub const SIGNATURE_HEADER: &str = "x-devnet-api-signature";
+pub const EVENT_HEADER: &str = "x-devnet-api-event";
+pub const DELIVERY_HEADER: &str = "x-devnet-api-delivery";
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_READY_TIMEOUT_SECS: u64 = 900;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+const READY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+static CLIENT: LazyLock<Client<HttpsConnector<HttpConnector>>> = LazyLock::new(|| {
+    let connector = HttpsConnectorBuilder::new()
+        .with_native_roots()
+        .https_or_http()
+        .enable_http1()
+        .build();
+    Client::builder().build(connector)
+});
+
+/// Sends the webhooks of devnet configs whose hosts aren't in the `allowed_hosts`, only
+/// connecting to the public addresses they resolve to.
+static DEVNET_CLIENT: LazyLock<Client<HttpsConnector<HttpConnector<PublicResolver>>>> =
+    LazyLock::new(|| {
+        let mut http_connector =
+            HttpConnector::new_with_resolver(PublicResolver(GaiResolver::new()));
+        http_connector.enforce_http(false);
+        let connector = HttpsConnectorBuilder::new()
+            .with_native_roots()
+            .https_or_http()
+            .enable_http1()
+            .wrap_connector(http_connector);
+        Client::builder().build(connector)
+    });
+
+/// Resolves hosts to their public addresses, failing for hosts that only resolve to
+/// private, loopback or link-local addresses, such as the cluster's services. The addresses
+/// that are connected to are the ones checked, so a host can't be rebound in between.
+#[derive(Clone)]
+struct PublicResolver(GaiResolver);
+
+impl Service<Name> for PublicResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, io::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let resolving = self.0.call(name.clone());
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = resolving
+                .await?
+                .filter(|addr| is_public_ip(&addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{} does not resolve to a public address", name.as_str()),
+                ));
+            }
+            Ok(addrs.into_iter())
+        })
+    }
+}
+
+/// Whether `ip` is reachable on the internet, rather than a private, loopback, link-local
+/// or otherwise reserved address.
+fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a == 0
+                // shared address space, used for carrier-grade NAT
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(&IpAddr::V4(ip)),
+            None => {
+                let first_segment = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    // unique local addresses
+                    || (first_segment & 0xfe00) == 0xfc00
+                    // link-local addresses
+                    || (first_segment & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, ToSchema, strum::IntoStaticStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum WebhookEvent {
+    /// Every component of a new devnet is running and ready.
+    DevnetReady,
+    /// A devnet failed to deploy, a component failed, or it wasn't ready in time.
+    DevnetFailed,
+    DevnetDeleted,
+}
+
+/// The JSON body POSTed to webhooks.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct WebhookPayload {
+    /// Identifies the payload across delivery attempts. Also sent in the
+    /// `x-devnet-api-delivery` header.
+    pub id: String,
+    pub event: WebhookEvent,
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    pub network: String,
+    /// The id of the request that caused the event.
+    pub request_id: Option<String>,
+    /// Why the devnet failed.
+    pub message: Option<String>,
+    /// The status of the devnet when it became ready or failed.
+    pub devnet: Option<StacksDevnetInfoResponse>,
+}
+
+impl WebhookPayload {
+    pub fn new(event: WebhookEvent, network: &str, ctx: &Context) -> WebhookPayload {
+        WebhookPayload {
+            id: Uuid::new_v4().to_string(),
+            event,
+            timestamp: now(),
+            network: network.to_string(),
+            request_id: ctx.request_id.clone(),
+            message: None,
+            devnet: None,
+        }
+    }
+
+    pub fn failed(network: &str, message: String, ctx: &Context) -> WebhookPayload {
+        WebhookPayload {
+            message: Some(message),
+            ..WebhookPayload::new(WebhookEvent::DevnetFailed, network, ctx)
+        }
+    }
+}
+
+/// A line of the delivery log, written for every attempt to deliver a payload.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WebhookDelivery {
+    pub timestamp: u64,
+    /// The `id` of the delivered payload.
+    pub payload_id: String,
+    pub event: WebhookEvent,
+    pub network: String,
+    pub url: String,
+    pub attempt: u32,
+    /// The status the webhook responded with.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub delivered: bool,
+}
+
+/// Sends signed payloads to webhooks in the background, retrying failed deliveries.
+#[derive(Clone)]
+pub struct Webhooks {
+    config: WebhookConfig,
+}
+
+impl Webhooks {
+    pub fn new(webhook_config: &WebhookConfig) -> Webhooks {
+        Webhooks {
+            config: webhook_config.clone(),
+        }
+    }
+
+    /// Gets the configured webhooks along with those of a devnet's config.
+    fn get_urls(&self, devnet_webhooks: &[String]) -> Vec<String> {
+        let mut urls = self.config.urls.clone().unwrap_or_default();
+        for url in devnet_webhooks {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        urls
+    }
+
+    fn is_configured_url(&self, url: &str) -> bool {
+        self.config.urls.iter().flatten().any(|u| u == url)
+    }
+
+    /// Checks that a webhook of a devnet's config may be sent to, returning whether its
+    /// host is one of the `allowed_hosts`. Other hosts are only sent to when no
+    /// `allowed_hosts` are configured, and only at their public addresses.
+    fn check_devnet_url(&self, url: &str) -> Result<bool, String> {
+        let uri = url
+            .parse::<Uri>()
+            .map_err(|e| format!("invalid webhook {url}: {e}"))?;
+        let allow_http = self.config.allow_http.unwrap_or(false);
+        match uri.scheme_str() {
+            Some("https") => {}
+            Some("http") if allow_http => {}
+            _ if allow_http => return Err(format!("webhook {url} must be an http or https url")),
+            _ => return Err(format!("webhook {url} must be an https url")),
+        }
+        let host = uri
+            .host()
+            .ok_or_else(|| format!("webhook {url} must have a host"))?;
+        if let Some(allowed_hosts) = &self.config.allowed_hosts {
+            return match allowed_hosts
+                .iter()
+                .any(|pattern| origin_matches(pattern, host))
+            {
+                true => Ok(true),
+                false => Err(format!("webhook host {host} is not allowed")),
+            };
+        }
+        // addresses are connected to without being resolved, so they're checked here
+        match host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+        {
+            Ok(ip) if !is_public_ip(&ip) => {
+                Err(format!("webhook {url} must not target a private address"))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Checks the `webhooks` of a devnet's config against the `allowed_hosts` and
+    /// `allow_http` settings.
+    pub fn validate_devnet_webhooks(
+        &self,
+        devnet_webhooks: &[String],
+    ) -> Vec<ConfigValidationError> {
+        devnet_webhooks
+            .iter()
+            .enumerate()
+            // invalid urls are reported by the config's own validation
+            .filter(|(_, url)| is_valid_webhook_url(url) && !self.is_configured_url(url))
+            .filter_map(|(i, url)| {
+                let message = self.check_devnet_url(url).err()?;
+                Some(ConfigValidationError::new(
+                    &format!("/webhooks/{i}"),
+                    &message,
+                ))
+            })
+            .collect()
+    }
+
+    /// Sends `payload` to the configured webhooks and `devnet_webhooks`, without waiting
+    /// for the deliveries.
+    pub fn send(&self, payload: WebhookPayload, devnet_webhooks: &[String], ctx: &Context) {
+        let urls = self.get_urls(devnet_webhooks);
+        if urls.is_empty() {
+            return;
+        }
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => Bytes::from(body),
+            Err(e) => {
+                ctx.try_log(|logger| slog::error!(logger, "failed to form webhook payload: {}", e));
+                return;
+            }
+        };
+        for url in urls {
+            let checked = match self.is_configured_url(&url) {
+                true => Ok(true),
+                false => self.check_devnet_url(&url),
+            };
+            let mut delivery = WebhookDelivery {
+                timestamp: payload.timestamp,
+                payload_id: payload.id.clone(),
+                event: payload.event,
+                network: payload.network.clone(),
+                url,
+                attempt: 0,
+                status: None,
+                error: None,
+                delivered: false,
+            };
+            // devnets created before the webhook settings changed may have disallowed urls
+            let trusted = match checked {
+                Ok(trusted) => trusted,
+                Err(e) => {
+                    delivery.error = Some(e);
+                    self.log_delivery(&delivery, ctx);
+                    continue;
+                }
+            };
+            let webhooks = self.clone();
+            let body = body.clone();
+            let ctx = ctx.clone();
+            tokio::spawn(async move { webhooks.deliver(delivery, body, trusted, &ctx).await });
+        }
+    }
+
+    /// Watches a newly deployed devnet in the background, sending `devnet_ready` once every
+    /// component is running and ready, or `devnet_failed` if a component fails or the devnet
+    /// isn't ready before the `ready_timeout`.
+    pub fn watch_new_devnet(
+        &self,
+        k8s_manager: StacksDevnetApiK8sManager,
+        network: &str,
+        user_id: &str,
+        devnet_webhooks: Vec<String>,
+        ctx: &Context,
+    ) {
+        if self.get_urls(&devnet_webhooks).is_empty() {
+            return;
+        }
+        let webhooks = self.clone();
+        let network = network.to_string();
+        let user_id = user_id.to_string();
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            if let Some(payload) = webhooks
+                .wait_for_devnet(&k8s_manager, &network, &user_id, &ctx)
+                .await
+            {
+                webhooks.send(payload, &devnet_webhooks, &ctx);
+            }
+        });
+    }
+
+    async fn wait_for_devnet(
+        &self,
+        k8s_manager: &StacksDevnetApiK8sManager,
+        network: &str,
+        user_id: &str,
+        ctx: &Context,
+    ) -> Option<WebhookPayload> {
+        let params = DevnetWaitParams {
+            min_stacks_height: None,
+            min_burn_height: None,
+            timeout: Duration::from_secs(
+                self.config
+                    .ready_timeout
+                    .unwrap_or(DEFAULT_READY_TIMEOUT_SECS),
+            ),
+        };
+        let deadline = Instant::now() + params.timeout;
+        loop {
+            let devnet_info = k8s_manager
+                .get_devnet_info(network, user_id, DevnetInfoIncludes::default())
+                .await;
+            // in operator mode, the devnet of a `StacksDevnet` is deployed after it's created
+            let not_deployed = match &devnet_info {
+                Err(e) if e.code == 404 => k8s_manager
+                    .stacks_devnet_exists(network)
+                    .await
+                    .unwrap_or(false),
+                _ => false,
+            };
+            match devnet_info {
+                Ok(devnet_info) => {
+                    let unmet = params.get_unmet_conditions(&devnet_info);
+                    let failure = devnet_info
+                        .components
+                        .iter()
+                        .find_map(|(component, status)| {
+                            Some(format!("{component} {}", status.get_failure()?))
+                        });
+                    let mut payload = if unmet.is_empty() {
+                        WebhookPayload::new(WebhookEvent::DevnetReady, network, ctx)
+                    } else if let Some(message) = failure {
+                        WebhookPayload::failed(network, message, ctx)
+                    } else if Instant::now() >= deadline {
+                        let message = format!(
+                            "network {network} was not ready after {} seconds: {}",
+                            params.timeout.as_secs(),
+                            unmet.join(", ")
+                        );
+                        WebhookPayload::failed(network, message, ctx)
+                    } else {
+                        sleep(READY_POLL_INTERVAL).await;
+                        continue;
+                    };
+                    payload.devnet = Some(devnet_info);
+                    return Some(payload);
+                }
+                Err(_) if not_deployed => {
+                    if Instant::now() >= deadline {
+                        let message = format!(
+                            "network {network} was not deployed after {} seconds",
+                            params.timeout.as_secs()
+                        );
+                        return Some(WebhookPayload::failed(network, message, ctx));
+                    }
+                }
+                // the devnet was deleted before it became ready
+                Err(e) if e.code < 500 => return None,
+                Err(e) => {
+                    ctx.try_log(|logger| {
+                        slog::warn!(
+                            logger,
+                            "failed to get devnet info for webhooks NAMESPACE: {}, ERROR: {}",
+                            network,
+                            e.message
+                        )
+                    });
+                    if Instant::now() >= deadline {
+                        return Some(WebhookPayload::failed(network, e.message, ctx));
+                    }
+                }
+            }
+            sleep(READY_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Posts `body` until the webhook accepts it, backing off between attempts. Client
+    /// errors other than 429 are not retried, and redirects are not followed. Returns
+    /// whether the payload was delivered.
+    async fn deliver(
+        &self,
+        mut delivery: WebhookDelivery,
+        body: Bytes,
+        trusted: bool,
+        ctx: &Context,
+    ) -> bool {
+        let max_attempts = self
+            .config
+            .max_attempts
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+            .max(1);
+        for attempt in 1..=max_attempts {
+            delivery.attempt = attempt;
+            delivery.timestamp = now();
+            let retryable = match self.post(&delivery, body.clone(), trusted).await {
+                Ok(status) => {
+                    delivery.status = Some(status.as_u16());
+                    delivery.error = None;
+                    delivery.delivered = status.is_success();
+                    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => {
+                    delivery.status = None;
+                    delivery.error = Some(e);
+                    true
+                }
+            };
+            self.log_delivery(&delivery, ctx);
+            if delivery.delivered || !retryable || attempt == max_attempts {
+                break;
+            }
+            sleep(get_retry_delay(attempt)).await;
+        }
+        delivery.delivered
+    }
+
+    /// Posts `body` to the webhook, only connecting to public addresses unless the webhook
+    /// is `trusted`.
+    async fn post(
+        &self,
+        delivery: &WebhookDelivery,
+        body: Bytes,
+        trusted: bool,
+    ) -> Result<StatusCode, String> {
+        let event: &'static str = delivery.event.into();
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(&delivery.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event)
+            .header(DELIVERY_HEADER, &delivery.payload_id);
+        if let Some(secret) = &self.config.secret {
+            request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)));
+        }
+        let request = request
+            .body(Body::from(body))
+            .map_err(|e| format!("invalid webhook request: {e}"))?;
+        let response = match trusted {
+            true => CLIENT.request(request),
+            false => DEVNET_CLIENT.request(request),
+        };
+        match timeout(DELIVERY_TIMEOUT, response).await {
+            Ok(Ok(response)) => Ok(response.status()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!(
+                "no response after {} seconds",
+                DELIVERY_TIMEOUT.as_secs()
+            )),
+        }
+    }
+
+    fn log_delivery(&self, delivery: &WebhookDelivery, ctx: &Context) {
+        let Some(path) = &self.config.delivery_log_path else {
+            ctx.try_log(|logger| {
+                slog::info!(
+                    logger,
+                    "webhook delivery {} of {} to {}: {}",
+                    delivery.attempt,
+                    delivery.payload_id,
+                    delivery.url,
+                    match (&delivery.status, &delivery.error) {
+                        (_, Some(error)) => error.clone(),
+                        (Some(status), None) => status.to_string(),
+                        (None, None) => String::new(),
+                    }
+                )
+            });
+            return;
+        };
+        let result = serde_json::to_vec(delivery)
+            .map_err(|e| e.to_string())
+            .and_then(|mut line| {
+                line.push(b'\n');
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| file.write_all(&line))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            ctx.try_log(|logger| slog::error!(logger, "failed to write webhook delivery: {}", e));
+        }
+    }
+}
+
+/// The hex encoded HMAC-SHA256 of `body`, sent as `sha256=<signature>` in the
+/// `x-devnet-api-signature` header.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any size");
+    mac.update(body);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// The delay before retrying a delivery, doubling after each attempt.
+fn get_retry_delay(attempt: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Could not get current time in secs")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use hyper::{
+        body::Bytes,
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, Server,
+    };
+
+    use super::{
+        get_retry_delay, is_public_ip, sign, WebhookDelivery, WebhookEvent, Webhooks,
+        SIGNATURE_HEADER,
+    };
+    use crate::{api_config::WebhookConfig, Context};
+
+    #[test]
+    fn it_signs_payloads() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn it_backs_off_between_attempts() {
+        assert_eq!(get_retry_delay(1), Duration::from_secs(1));
+        assert_eq!(get_retry_delay(2), Duration::from_secs(2));
+        assert_eq!(get_retry_delay(4), Duration::from_secs(8));
+        assert_eq!(get_retry_delay(10), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn it_retries_and_logs_deliveries() {
+        // fails the first delivery attempt, then records the signature of the next
+        let signatures: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+        let received = signatures.clone();
+        let make_service = make_service_fn(move |_| {
+            let received = received.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let received = received.clone();
+                    async move {
+                        let mut received = received.lock().unwrap();
+                        received.push(
+                            request
+                                .headers()
+                                .get(SIGNATURE_HEADER)
+                                .unwrap()
+                                .to_str()
+                                .unwrap()
+                                .to_string(),
+                        );
+                        let status = if received.len() == 1 { 503 } else { 200 };
+                        Ok::<_, Infallible>(
+                            Response::builder()
+                                .status(status)
+                                .body(Body::empty())
+                                .unwrap(),
+                        )
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let url = format!("http://{}/hooks", server.local_addr());
+        tokio::spawn(server);
+
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("deliveries.jsonl");
+        let webhooks = Webhooks::new(&WebhookConfig {
+            secret: Some("key".into()),
+            max_attempts: Some(3),
+            delivery_log_path: Some(log_path.to_str().unwrap().into()),
+            ..Default::default()
+        });
+        let delivery = WebhookDelivery {
+            timestamp: 0,
+            payload_id: "some-payload".into(),
+            event: WebhookEvent::DevnetDeleted,
+            network: "some-network".into(),
+            url: url.clone(),
+            attempt: 0,
+            status: None,
+            error: None,
+            delivered: false,
+        };
+        let body = Bytes::from_static(b"{}");
+        assert!(
+            webhooks
+                .deliver(delivery, body.clone(), true, &Context::empty())
+                .await
+        );
+
+        let expected_signature = format!("sha256={}", sign("key", &body));
+        assert_eq!(
+            *signatures.lock().unwrap(),
+            vec![expected_signature.clone(), expected_signature]
+        );
+        let deliveries: Vec<WebhookDelivery> = std::fs::read_to_string(&log_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            deliveries
+                .iter()
+                .map(|delivery| (delivery.attempt, delivery.status, delivery.delivered))
+                .collect::<Vec<_>>(),
+            vec![(1, Some(503), false), (2, Some(200), true)]
+        );
+        assert!(deliveries.iter().all(|delivery| delivery.url == url));
+    }
+
+    #[test]
+    fn it_checks_devnet_webhook_urls() {
+        let webhooks = Webhooks::new(&WebhookConfig::default());
+        assert_eq!(
+            webhooks.check_devnet_url("https://hooks.example.com/devnets"),
+            Ok(false)
+        );
+        for url in [
+            "http://hooks.example.com/devnets",
+            "https://127.0.0.1/devnets",
+            "https://169.254.169.254/latest",
+            "https://10.0.0.5/devnets",
+            "https://[::1]/devnets",
+        ] {
+            assert!(webhooks.check_devnet_url(url).is_err(), "allowed {url}");
+        }
+
+        let webhooks = Webhooks::new(&WebhookConfig {
+            urls: Some(vec!["http://audit.internal/hooks".into()]),
+            allowed_hosts: Some(vec!["*.example.com".into()]),
+            allow_http: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(
+            webhooks.check_devnet_url("http://hooks.example.com/devnets"),
+            Ok(true)
+        );
+        let errors = webhooks.validate_devnet_webhooks(&[
+            "http://audit.internal/hooks".into(),
+            "https://hooks.other.io/devnets".into(),
+        ]);
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.pointer.as_str(), e.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("/webhooks/1", "webhook host hooks.other.io is not allowed")]
+        );
+    }
+
+    #[test]
+    fn it_only_considers_public_ips_public() {
+        for (ip, public) in [
+            ("93.184.216.34", true),
+            ("10.1.2.3", false),
+            ("172.16.0.1", false),
+            ("192.168.1.1", false),
+            ("100.64.0.1", false),
+            ("169.254.169.254", false),
+            ("2606:2800:220:1::", true),
+            ("fd00::1", false),
+            ("fe80::1", false),
+            ("::ffff:127.0.0.1", false),
+        ] {
+            assert_eq!(is_public_ip(&ip.parse().unwrap()), public, "{ip}");
+        }
+    }
+
+    #[tokio::test]
+    async fn it_refuses_devnet_webhooks_resolving_to_private_addresses() {
+        let webhooks = Webhooks::new(&WebhookConfig::default());
+        let delivery = WebhookDelivery {
+            timestamp: 0,
+            payload_id: "some-payload".into(),
+            event: WebhookEvent::DevnetDeleted,
+            network: "some-network".into(),
+            url: "http://localhost:9/hooks".into(),
+            attempt: 0,
+            status: None,
+            error: None,
+            delivered: false,
+        };
+        let err = webhooks
+            .post(&delivery, Bytes::from_static(b"{}"), false)
+            .await
+            .unwrap_err();
+        assert!(
+            err.contains("does not resolve to a public address"),
+            "{err}"
+        );
+    }
+}